@@ -31,30 +31,53 @@ fn main() {
     use matrix_rain_core::*;
 
     println!("2. CHARACTER SET DATA (built into binary)");
-    let charsets = vec![
-        ("Japanese", CharacterSet::Japanese),
-        ("Hindi", CharacterSet::Hindi),
-        ("Tamil", CharacterSet::Tamil),
-        ("Sinhala", CharacterSet::Sinhala),
-        ("Korean", CharacterSet::Korean),
-        ("Jawi", CharacterSet::Jawi),
-        ("Mixed", CharacterSet::Mixed),
-    ];
-
-    for (name, charset) in charsets {
-        let chars = charset.get_characters();
-        println!("   ✅ {}: {} characters loaded", name, chars.len());
+    for charset in CharacterSet::all_sets() {
+        let info = charset.info();
+        println!(
+            "   ✅ {}: {} characters loaded",
+            info.english_name, info.glyph_count
+        );
+    }
+
+    // Real glyph coverage check when a font file is given
+    // Usage: cargo run --example diagnose -- /path/to/font.ttf
+    if let Some(font_path) = env::args().nth(1) {
+        println!("\n   FONT COVERAGE: {}", font_path);
+        match FontCoverage::from_file(&font_path) {
+            Ok(coverage) => {
+                for report in coverage.check_all() {
+                    let status = if report.is_complete() {
+                        "✅"
+                    } else {
+                        "⚠️ "
+                    };
+                    println!(
+                        "   {} {:?}: {}/{} glyphs ({:.1}%)",
+                        status,
+                        report.character_set,
+                        report.covered,
+                        report.total,
+                        report.ratio() * 100.0
+                    );
+                }
+                println!("   Run matrix-rain-coverage for the full missing-codepoint list");
+            }
+            Err(err) => println!("   ❌ Could not read font: {}", err),
+        }
     }
 
     println!("\n3. SAMPLE CHARACTERS (visual test)");
-    println!("   If you see boxes (□) or question marks (?), your font is missing glyphs.\n");
-
-    println!("   Japanese:  ハミヒーウシナモニサワツオリアホテマケメエカキムユラセネスタヌヘ");
-    println!("   Hindi:     अआइईउऊऋऌएऐओऔकखगघङचछजझञटठडढण");
-    println!("   Tamil:     அஆஇஈஉஊஎஏஐஒஓஔகஙசஞடணதநபமயரலவழளறன");
-    println!("   Sinhala:   අආඇඈඉඊඋඌඍඎඏඐඑඒඓඔඕඖ");
-    println!("   Korean:    가나다라마바사아자차카타파하");
-    println!("   Jawi:      ابتثجحخدذرزسشصضطظعغفقكلمنهوي");
+    println!("   If you see boxes (□) or question marks (?), your font is missing glyphs.");
+    println!("   Pass a font file path to check coverage exactly.\n");
+
+    for charset in CharacterSet::all_sets() {
+        let info = charset.info();
+        println!(
+            "   {:<10} {}",
+            format!("{}:", info.english_name),
+            info.preview
+        );
+    }
 
     println!("\n4. CONFIGURATION TEST");
     let config = ScreenSaverConfig::new(
@@ -6,19 +6,13 @@ fn main() {
     println!("Testing Character Sets\n");
     println!("======================\n");
 
-    let charsets = vec![
-        ("Japanese (Katakana)", CharacterSet::Japanese),
-        ("Hindi (Devanagari)", CharacterSet::Hindi),
-        ("Tamil", CharacterSet::Tamil),
-        ("Sinhala", CharacterSet::Sinhala),
-        ("Korean (Hangul)", CharacterSet::Korean),
-        ("Jawi (Arabic)", CharacterSet::Jawi),
-        ("Mixed", CharacterSet::Mixed),
-    ];
-
-    for (name, charset) in charsets {
+    for charset in CharacterSet::all_sets() {
+        let info = charset.info();
         let chars = charset.get_characters();
-        println!("{}: {} characters", name, chars.len());
+        println!(
+            "{} ({}): {} characters",
+            info.english_name, info.native_name, info.glyph_count
+        );
 
         // Show first 20 characters
         print!("  Sample: ");
@@ -69,8 +63,14 @@ fn main() {
     println!("  1. A font that supports Unicode");
     println!("  2. Support for the specific scripts you want to use");
     println!("\nRecommended fonts:");
-    println!("  - macOS: SF Mono, Menlo (built-in)");
-    println!("  - Cross-platform: Noto Sans Mono, Cascadia Code");
+    for charset in CharacterSet::all_sets() {
+        let info = charset.info();
+        println!(
+            "  - {}: {}",
+            info.english_name,
+            info.recommended_fonts.join(", ")
+        );
+    }
     println!("\nNote: The application bundles the CHARACTER DATA,");
     println!("but your terminal font must have the GLYPHS to display them.");
 }
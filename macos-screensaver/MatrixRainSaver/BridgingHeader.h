@@ -10,13 +10,14 @@
 
 #include <stdint.h>
 #include <stddef.h>
+#include <stdbool.h>
 
 // Opaque type for Rust engine
 typedef struct MatrixRainHandle MatrixRainHandle;
 
 // FFI structure for render data
 typedef struct {
-    uint32_t character;  // Unicode codepoint
+    uint32_t character;  // First Unicode codepoint of the glyph
     float x;
     float y;
     uint8_t r;
@@ -24,8 +25,102 @@ typedef struct {
     uint8_t b;
     float a;
     float font_size;
+    uint8_t transform;   // Bit flags: 1 = mirror x, 2 = mirror y, 4 = rotate 90, 8 = rotate 180
+    uint8_t glyph_len;   // Number of UTF-8 bytes in glyph_utf8
+    uint8_t glyph_utf8[32]; // Full glyph, may be a grapheme cluster (not NUL-terminated when full)
+    uint8_t leader;      // 1 for leader (head) glyphs
+    float glow;          // Glow/halo intensity (0 = none)
+    int32_t glyph_index; // Index in the glyph pool and atlases built from it, -1 if outside it
 } RenderCharFFI;
 
+// A run of render chars that share a drawing style
+typedef struct {
+    float font_size;
+    uint8_t r;           // Batch colour, quantized so the whole batch can be drawn in it
+    uint8_t g;
+    uint8_t b;
+    float a;
+    float glow;          // Glow/halo intensity shared by the batch (0 = none)
+    size_t start;        // Index of the batch's first glyph in the render chars array
+    size_t count;        // Number of glyphs in the batch
+} RenderBatchFFI;
+
+// Extended-range render data: linear extended sRGB, 1.0 = SDR white, leaders may exceed it
+typedef struct {
+    uint32_t character;
+    float x;
+    float y;
+    float r;
+    float g;
+    float b;
+    float a;
+    float font_size;
+    uint8_t transform;
+    uint8_t glyph_len;
+    uint8_t glyph_utf8[32];
+    uint8_t leader;
+    float glow;          // Drawn in the glyph's extended colour
+    int32_t glyph_index;
+} RenderCharHdrFFI;
+
+// A cell that changed since the previous delta
+typedef struct {
+    uint8_t layer;       // 0 = background, 1 = foreground
+    uint8_t change;      // 0 = added, 1 = removed, 2 = recoloured, 3 = re-glyphed, 4 = restyled
+    uint32_t column;
+    int32_t row;
+    RenderCharFFI glyph; // The cell's glyph now, or the one it last had if removed
+} CellDeltaFFI;
+
+// A region to redraw, in the same coordinates as render char positions
+typedef struct {
+    float x;
+    float y;
+    float width;
+    float height;
+} DirtyRectFFI;
+
+// What changed since the previous delta (arrays valid until the next call)
+typedef struct {
+    uint8_t full_frame;  // 1 if the whole screen must be redrawn
+    const CellDeltaFFI* changes;
+    size_t change_count;
+    const DirtyRectFFI* dirty_rects; // Just the screen when full_frame is set
+    size_t dirty_rect_count;
+} FrameDeltaFFI;
+
+// Background (colours packed as 0xRRGGBB)
+typedef struct {
+    uint8_t kind;       // 0 = solid, 1 = vertical gradient, 2 = radial gradient, 3 = transparent
+    uint32_t color_a;   // Solid colour, gradient top or radial centre
+    uint32_t color_b;   // Gradient bottom or radial edge
+} BackgroundFFI;
+
+// Metadata structures (strings are static, NUL-terminated UTF-8)
+typedef struct {
+    const char* id;
+    const char* english_name;
+    const char* native_name;
+    uint8_t direction;              // 0 = left-to-right, 1 = right-to-left, 2 = mixed
+    uint32_t glyph_count;
+    const char* recommended_fonts;  // Comma-separated, most preferred first
+    const char* preview;
+} CharacterSetInfoFFI;
+
+typedef struct {
+    const char* id;
+    const char* name;
+    uint8_t r;
+    uint8_t g;
+    uint8_t b;
+} ColorSchemeInfoFFI;
+
+typedef struct {
+    const char* id;
+    const char* name;
+    uint64_t update_interval_ms;
+} RainSpeedInfoFFI;
+
 // Lifecycle functions
 MatrixRainHandle* matrix_rain_new(uint32_t width, uint32_t height,
                                    uint8_t charset, uint8_t color, uint8_t speed);
@@ -35,11 +130,84 @@ void matrix_rain_destroy(MatrixRainHandle* handle);
 // Configuration
 void matrix_rain_set_config(MatrixRainHandle* handle, uint32_t width, uint32_t height,
                              uint8_t charset, uint8_t color, uint8_t speed);
+void matrix_rain_set_glyph_transforms(MatrixRainHandle* handle, float mirror_x, float mirror_y,
+                                      float rotate_90, float rotate_180);
+size_t matrix_rain_set_coverage_font(MatrixRainHandle* handle, const char* font_path);
+bool matrix_rain_set_sequence_text(MatrixRainHandle* handle, const char* text, float mutation_rate);
+size_t matrix_rain_set_custom_glyphs(MatrixRainHandle* handle, const char* text);
+// The active glyph pool, which render data glyph_index values and glyph atlases refer to;
+// matrix_rain_get_glyph writes up to 32 UTF-8 bytes and returns the count (0 if out of range)
+uint32_t matrix_rain_glyph_count(const MatrixRainHandle* handle);
+uint8_t matrix_rain_get_glyph(const MatrixRainHandle* handle, uint32_t index, uint8_t* out_utf8);
+// Colours are packed as 0xRRGGBB
+void matrix_rain_set_custom_colors(MatrixRainHandle* handle, uint32_t leader, uint32_t primary,
+                                   uint32_t secondary, uint32_t tertiary, uint32_t background);
+// interpolation: 0 = linear RGB, 1 = OKLab, 2 = step; count 0 returns to the scheme's colours
+bool matrix_rain_set_gradient(MatrixRainHandle* handle, const uint32_t* colors, const float* positions,
+                              size_t count, uint8_t interpolation);
+// breathe_scheme past the preset count disables breathing; periods of 0 disable cycling/breathing
+void matrix_rain_set_palette_animation(MatrixRainHandle* handle, float hue_cycle_period,
+                                       float column_hue_offset, uint8_t breathe_scheme,
+                                       float breathe_period);
+// color above 0xFFFFFF uses the scheme's leader colour; head_glyphs 0 leads the first 5% of the trail
+void matrix_rain_set_leader_style(MatrixRainHandle* handle, bool enabled, uint32_t color,
+                                  uint32_t head_glyphs, float glow);
+// Leader brightness above SDR white for the HDR render data (1.0 = no boost)
+void matrix_rain_set_leader_brightness(MatrixRainHandle* handle, float brightness);
+void matrix_rain_set_time(MatrixRainHandle* handle, float seconds);
+float matrix_rain_get_time(const MatrixRainHandle* handle);
+// kind past 3 follows the colour scheme; tint mixes in the scheme's primary colour
+void matrix_rain_set_background(MatrixRainHandle* handle, uint8_t kind, uint32_t color_a,
+                                uint32_t color_b, float tint);
+bool matrix_rain_get_background(const MatrixRainHandle* handle, BackgroundFFI* out_background);
+uint32_t matrix_rain_get_background_color(const MatrixRainHandle* handle);
+
+// Blending: mode 0 = normal, 1 = additive, 2 = screen
+// With precomposite, exported colours are opaque and already blended
+void matrix_rain_set_blend_mode(MatrixRainHandle* handle, uint8_t mode, bool precomposite);
+
+// Density: how often idle columns start a new trail (1.0 = normal)
+void matrix_rain_set_density(MatrixRainHandle* handle, float density);
+
+// Themes (JSON files bundling charset, palette, speed, density, layers and effects)
+bool matrix_rain_apply_theme_file(MatrixRainHandle* handle, const char* path);
+bool matrix_rain_apply_theme(MatrixRainHandle* handle, const char* themes_dir, const char* name);
+bool matrix_rain_save_theme(const MatrixRainHandle* handle, const char* themes_dir,
+                            const char* name, const char* author);
+
+// Palette import (.gpl, base16 .yaml, .ppm, .png when built with the png feature)
+bool matrix_rain_import_palette(MatrixRainHandle* handle, const char* path);
 
 // Rendering
 const RenderCharFFI* matrix_rain_get_render_chars(MatrixRainHandle* handle, size_t* out_count);
+const RenderCharHdrFFI* matrix_rain_get_render_chars_hdr(MatrixRainHandle* handle,
+                                                         size_t* out_count);
+
+// Render chars grouped by font size, glow and quantized colour (background layer first);
+// the glyphs are written to the array matrix_rain_get_render_chars returns, batch by batch
+const RenderBatchFFI* matrix_rain_get_render_batches(MatrixRainHandle* handle, size_t* out_count,
+                                                     const RenderCharFFI** out_chars,
+                                                     size_t* out_char_count);
+
+// Deltas: what changed since the previous call, so only dirty rects need redrawing
+bool matrix_rain_get_delta(MatrixRainHandle* handle, FrameDeltaFFI* out_delta);
+void matrix_rain_invalidate_delta(MatrixRainHandle* handle);
 
 // Utilities
 uint64_t matrix_rain_get_update_interval_ms(uint8_t speed);
 
+// Metadata enumeration (index order matches the charset/color/speed parameters)
+uint8_t matrix_rain_charset_count(void);
+bool matrix_rain_charset_info(uint8_t index, CharacterSetInfoFFI* out_info);
+uint8_t matrix_rain_color_scheme_count(void);
+bool matrix_rain_color_scheme_info(uint8_t index, ColorSchemeInfoFFI* out_info);
+uint8_t matrix_rain_speed_count(void);
+bool matrix_rain_speed_info(uint8_t index, RainSpeedInfoFFI* out_info);
+
+// Colour-vision accessible palettes
+uint8_t matrix_rain_accessible_palette_count(void);
+bool matrix_rain_accessible_palette_info(uint8_t index, ColorSchemeInfoFFI* out_info);
+bool matrix_rain_set_accessible_palette(MatrixRainHandle* handle, uint8_t index);
+float matrix_rain_min_contrast(const MatrixRainHandle* handle);
+
 #endif /* BridgingHeader_h */
@@ -54,6 +54,11 @@ class MatrixRainView: ScreenSaverView {
 
     override func startAnimation() {
         super.startAnimation()
+
+        // Nothing of an earlier run is on screen any more
+        if let engine = matrixEngine {
+            matrix_rain_invalidate_delta(engine)
+        }
     }
 
     override func stopAnimation() {
@@ -64,12 +69,19 @@ class MatrixRainView: ScreenSaverView {
         super.animateOneFrame()
 
         // Update the Rust engine
-        if let engine = matrixEngine {
-            matrix_rain_update(engine)
-        }
+        guard let engine = matrixEngine else { return }
+        matrix_rain_update(engine)
 
-        // Trigger redraw
-        setNeedsDisplay(bounds)
+        // Redraw only what changed, or everything when the engine asks for it
+        var delta = FrameDeltaFFI()
+        guard matrix_rain_get_delta(engine, &delta), delta.full_frame == 0 else {
+            setNeedsDisplay(bounds)
+            return
+        }
+        for rect in UnsafeBufferPointer(start: delta.dirty_rects, count: delta.dirty_rect_count) {
+            setNeedsDisplay(NSRect(x: CGFloat(rect.x), y: CGFloat(rect.y),
+                                   width: CGFloat(rect.width), height: CGFloat(rect.height)))
+        }
     }
 
     // MARK: - Drawing
@@ -77,9 +89,8 @@ class MatrixRainView: ScreenSaverView {
     override func draw(_ rect: NSRect) {
         super.draw(rect)
 
-        // Fill background
-        NSColor.black.setFill()
-        bounds.fill()
+        // Paint the background reported by the engine (black if unavailable)
+        drawBackground()
 
         // Get render data from Rust engine
         guard let engine = matrixEngine else { return }
@@ -93,11 +104,24 @@ class MatrixRainView: ScreenSaverView {
         // Convert pointer to array
         let renderChars = UnsafeBufferPointer(start: dataPtr, count: count)
 
-        // Draw each character
+        // Draw each character that overlaps a region being redrawn
         for renderChar in renderChars {
-            // Convert Unicode codepoint to character
+            let size = CGFloat(renderChar.font_size)
+            let margin = size * (CGFloat(renderChar.glow) + 0.3)
+            let area = NSRect(x: CGFloat(renderChar.x) - margin, y: CGFloat(renderChar.y) - margin,
+                              width: size * 0.6 + 2 * margin, height: size * 1.2 + 2 * margin)
+            guard needsToDraw(area) else { continue }
+
+            // Single codepoints take the fast path; clusters are decoded from UTF-8
             guard let scalar = UnicodeScalar(renderChar.character) else { continue }
-            let character = String(Character(scalar))
+            let character: String
+            if scalar.utf8.count == Int(renderChar.glyph_len) {
+                character = String(Character(scalar))
+            } else {
+                character = withUnsafeBytes(of: renderChar.glyph_utf8) { bytes in
+                    String(decoding: bytes.prefix(Int(renderChar.glyph_len)), as: UTF8.self)
+                }
+            }
 
             // Create color with alpha
             let color = NSColor(
@@ -108,17 +132,86 @@ class MatrixRainView: ScreenSaverView {
             )
 
             // Create attributes
-            let attrs: [NSAttributedString.Key: Any] = [
+            var attrs: [NSAttributedString.Key: Any] = [
                 .font: NSFont.monospacedSystemFont(ofSize: CGFloat(renderChar.font_size), weight: .regular),
                 .foregroundColor: color
             ]
 
+            // Leader glow: a soft halo in the glyph's own colour
+            if renderChar.glow > 0 {
+                let shadow = NSShadow()
+                shadow.shadowColor = color
+                shadow.shadowBlurRadius = CGFloat(renderChar.font_size * renderChar.glow)
+                shadow.shadowOffset = .zero
+                attrs[.shadow] = shadow
+            }
+
             // Draw character
             let point = NSPoint(x: CGFloat(renderChar.x), y: CGFloat(renderChar.y))
-            (character as NSString).draw(at: point, withAttributes: attrs)
+            if renderChar.transform == 0 {
+                (character as NSString).draw(at: point, withAttributes: attrs)
+            } else {
+                drawTransformed(character, at: point, attributes: attrs,
+                                transform: renderChar.transform, fontSize: CGFloat(renderChar.font_size))
+            }
         }
     }
 
+    /// Paint the engine's backdrop: a solid colour, a vertical or radial gradient, or nothing
+    private func drawBackground() {
+        var background = BackgroundFFI(kind: 0, color_a: 0, color_b: 0)
+        if let engine = matrixEngine {
+            _ = matrix_rain_get_background(engine, &background)
+        }
+
+        func nsColor(_ rgb: UInt32) -> NSColor {
+            NSColor(
+                red: CGFloat((rgb >> 16) & 0xFF) / 255.0,
+                green: CGFloat((rgb >> 8) & 0xFF) / 255.0,
+                blue: CGFloat(rgb & 0xFF) / 255.0,
+                alpha: 1.0
+            )
+        }
+
+        switch background.kind {
+        case 1:
+            // Vertical: top colour at the top of the (unflipped) view
+            NSGradient(starting: nsColor(background.color_b), ending: nsColor(background.color_a))?
+                .draw(in: bounds, angle: 90)
+        case 2:
+            NSGradient(starting: nsColor(background.color_a), ending: nsColor(background.color_b))?
+                .draw(in: bounds, relativeCenterPosition: .zero)
+        case 3:
+            NSColor.clear.setFill()
+            bounds.fill(using: .copy)
+        default:
+            nsColor(background.color_a).setFill()
+            bounds.fill()
+        }
+    }
+
+    /// Draw a glyph mirrored and/or rotated around its centre
+    private func drawTransformed(_ character: String, at point: NSPoint,
+                                 attributes: [NSAttributedString.Key: Any],
+                                 transform flags: UInt8, fontSize: CGFloat) {
+        let center = NSPoint(x: point.x + fontSize * 0.3, y: point.y + fontSize * 0.6)
+
+        var degrees: CGFloat = 0
+        if flags & 4 != 0 { degrees += 90 }
+        if flags & 8 != 0 { degrees += 180 }
+
+        let transform = NSAffineTransform()
+        transform.translateX(by: center.x, yBy: center.y)
+        transform.rotate(byDegrees: -degrees)
+        transform.scaleX(by: flags & 1 != 0 ? -1 : 1, yBy: flags & 2 != 0 ? -1 : 1)
+        transform.translateX(by: -center.x, yBy: -center.y)
+
+        NSGraphicsContext.saveGraphicsState()
+        transform.concat()
+        (character as NSString).draw(at: point, withAttributes: attributes)
+        NSGraphicsContext.restoreGraphicsState()
+    }
+
     // MARK: - Configuration
 
     override var hasConfigureSheet: Bool {
@@ -151,6 +244,7 @@ class MatrixRainView: ScreenSaverView {
                 preferences.colorScheme,
                 preferences.speed
             )
+            matrix_rain_invalidate_delta(engine)
         }
 
         // Update animation interval
@@ -43,38 +43,22 @@ class Preferences: ObservableObject {
         self.speed = savedSpeed == 0 ? 2 : UInt8(savedSpeed) // Default to Medium (2)
     }
 
-    // Character set names for UI
-    static let characterSetNames = [
-        "Japanese",
-        "Hindi",
-        "Tamil",
-        "Sinhala",
-        "Korean",
-        "Jawi",
-        "Mixed"
-    ]
+    // Names for UI, enumerated from the Rust library so they never drift
+    static let characterSetNames: [String] = (0..<matrix_rain_charset_count()).map { index in
+        var info = CharacterSetInfoFFI()
+        guard matrix_rain_charset_info(index, &info) else { return "" }
+        return String(cString: info.english_name)
+    }
 
-    // Color scheme names for UI
-    static let colorSchemeNames = [
-        "Matrix Green",
-        "Dark Blue",
-        "Purple",
-        "Orange",
-        "Red",
-        "Cyan",
-        "Yellow",
-        "Pink",
-        "White",
-        "Lime Green",
-        "Teal"
-    ]
+    static let colorSchemeNames: [String] = (0..<matrix_rain_color_scheme_count()).map { index in
+        var info = ColorSchemeInfoFFI()
+        guard matrix_rain_color_scheme_info(index, &info) else { return "" }
+        return String(cString: info.name)
+    }
 
-    // Speed names for UI
-    static let speedNames = [
-        "Very Slow",
-        "Slow",
-        "Medium",
-        "Fast",
-        "Very Fast"
-    ]
+    static let speedNames: [String] = (0..<matrix_rain_speed_count()).map { index in
+        var info = RainSpeedInfoFFI()
+        guard matrix_rain_speed_info(index, &info) else { return "" }
+        return String(cString: info.name)
+    }
 }
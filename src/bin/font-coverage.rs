@@ -0,0 +1,154 @@
+//! Font Glyph Coverage Checker
+//!
+//! Reports which characters of each character set a font file lacks.
+//!
+//! Usage: matrix-rain-coverage <FONT> [--index N] [--charset NAME]... [--json] [--strict]
+
+use matrix_rain_core::fonts::{CoverageReport, FontCoverage};
+use matrix_rain_core::CharacterSet;
+use serde::Serialize;
+use std::process::ExitCode;
+
+/// Maximum number of missing codepoints listed per set in text output
+const MAX_LISTED: usize = 12;
+
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    font: &'a str,
+    face_index: u32,
+    reports: &'a [CoverageReport],
+}
+
+struct Options {
+    font: String,
+    face_index: u32,
+    charsets: Vec<CharacterSet>,
+    json: bool,
+    strict: bool,
+}
+
+fn usage() -> &'static str {
+    "Usage: matrix-rain-coverage <FONT> [--index N] [--charset NAME]... [--json] [--strict]\n\
+     \n\
+     Options:\n\
+     \x20 --index N        Face index inside a .ttc collection (default 0)\n\
+     \x20 --charset NAME   Only check this character set (repeatable)\n\
+     \x20 --json           Print a machine-readable JSON report\n\
+     \x20 --strict         Exit with status 1 if any checked set is incomplete"
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut args = std::env::args().skip(1);
+    let mut font = None;
+    let mut options = Options {
+        font: String::new(),
+        face_index: 0,
+        charsets: Vec::new(),
+        json: false,
+        strict: false,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--index" => {
+                let value = args.next().ok_or("--index needs a value")?;
+                options.face_index = value
+                    .parse()
+                    .map_err(|_| format!("invalid face index: {}", value))?;
+            }
+            "--charset" => {
+                let value = args.next().ok_or("--charset needs a value")?;
+                let set = CharacterSet::from_id(&value)
+                    .ok_or_else(|| format!("unknown character set: {}", value))?;
+                options.charsets.push(set);
+            }
+            "--json" => options.json = true,
+            "--strict" => options.strict = true,
+            "-h" | "--help" => return Err(usage().to_string()),
+            _ if arg.starts_with('-') => return Err(format!("unknown option: {}", arg)),
+            _ => font = Some(arg),
+        }
+    }
+
+    options.font = font.ok_or_else(|| usage().to_string())?;
+    if options.charsets.is_empty() {
+        options.charsets = CharacterSet::all_sets();
+    }
+    Ok(options)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::from(2);
+        }
+    };
+
+    let coverage = match FontCoverage::from_file_index(&options.font, options.face_index) {
+        Ok(coverage) => coverage,
+        Err(err) => {
+            eprintln!("{}: {}", options.font, err);
+            return ExitCode::from(2);
+        }
+    };
+
+    let reports: Vec<CoverageReport> = options
+        .charsets
+        .iter()
+        .map(|&set| coverage.check(set))
+        .collect();
+
+    if options.json {
+        let output = JsonOutput {
+            font: &options.font,
+            face_index: options.face_index,
+            reports: &reports,
+        };
+        match serde_json::to_string_pretty(&output) {
+            Ok(json) => println!("{}", json),
+            Err(err) => {
+                eprintln!("failed to write JSON: {}", err);
+                return ExitCode::from(2);
+            }
+        }
+    } else {
+        println!("Font: {} (face {})", options.font, options.face_index);
+        for report in &reports {
+            let status = if report.is_complete() {
+                "✅"
+            } else {
+                "⚠️ "
+            };
+            println!(
+                "{} {:<10} {:>5}/{:<5} ({:5.1}%)",
+                status,
+                report.character_set.info().english_name,
+                report.covered,
+                report.total,
+                report.ratio() * 100.0
+            );
+            if !report.is_complete() {
+                let listed: Vec<String> = report
+                    .missing
+                    .iter()
+                    .take(MAX_LISTED)
+                    .map(|cp| format!("U+{:04X}", cp))
+                    .collect();
+                let more = report.missing.len().saturating_sub(MAX_LISTED);
+                if more > 0 {
+                    println!("     missing: {} … and {} more", listed.join(" "), more);
+                } else {
+                    println!("     missing: {}", listed.join(" "));
+                }
+            }
+        }
+    }
+
+    if options.strict && reports.iter().any(|r| !r.is_complete()) {
+        ExitCode::from(1)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
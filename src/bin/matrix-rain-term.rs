@@ -0,0 +1,342 @@
+//! Matrix Rain in the terminal
+//!
+//! Usage: matrix-rain-term [--charset NAME] [--color NAME] [--speed NAME] [--duration SECS]
+//!
+//! Press q or Esc (or Ctrl+C) to exit.
+
+use matrix_rain_core::config::Theme;
+use matrix_rain_core::rendering::{ColorMode, TerminalRenderer};
+use matrix_rain_core::{CharacterSet, ColorScheme, MatrixRain, RainSpeed, ScreenSaverConfig};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+struct Options {
+    config: ScreenSaverConfig,
+    duration: Option<Duration>,
+    color_mode: Option<ColorMode>,
+    list: bool,
+}
+
+fn usage() -> &'static str {
+    "Usage: matrix-rain-term [OPTIONS]\n\
+     \n\
+     Options:\n\
+     \x20 -c, --charset NAME    Character set (default japanese)\n\
+     \x20 -o, --color NAME      Color scheme (default matrix-green)\n\
+     \x20 -s, --speed NAME      Animation speed (default medium)\n\
+     \x20 -d, --duration SECS   Exit after this many seconds\n\
+     \x20 --theme FILE          Apply a JSON theme (before --charset/--color/--speed)\n\
+     \x20 --colors MODE         truecolor, 256 or 16 (default: from COLORTERM/TERM)\n\
+     \x20 -l, --list            List character sets, color schemes and speeds\n\
+     \n\
+     Press q or Esc to exit."
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut args = std::env::args().skip(1);
+    let mut options = Options {
+        config: ScreenSaverConfig::default(),
+        duration: None,
+        color_mode: None,
+        list: false,
+    };
+    let mut theme = None;
+    let mut charset = None;
+    let mut color = None;
+    let mut speed = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "-c" | "--charset" => {
+                let value = value()?;
+                charset = Some(
+                    CharacterSet::from_id(&value)
+                        .ok_or_else(|| format!("unknown character set: {}", value))?,
+                );
+            }
+            "-o" | "--color" => {
+                let value = value()?;
+                color = Some(
+                    ColorScheme::from_id(&value)
+                        .ok_or_else(|| format!("unknown color scheme: {}", value))?,
+                );
+            }
+            "-s" | "--speed" => {
+                let value = value()?;
+                speed = Some(
+                    RainSpeed::from_id(&value)
+                        .ok_or_else(|| format!("unknown speed: {}", value))?,
+                );
+            }
+            "-d" | "--duration" => {
+                let value = value()?;
+                let seconds: f64 = value
+                    .parse()
+                    .ok()
+                    .filter(|seconds: &f64| seconds.is_finite() && *seconds >= 0.0)
+                    .ok_or_else(|| format!("invalid duration: {}", value))?;
+                options.duration = Some(Duration::from_secs_f64(seconds));
+            }
+            "--theme" => theme = Some(value()?),
+            "--colors" => {
+                let value = value()?;
+                options.color_mode = Some(
+                    ColorMode::from_id(&value)
+                        .ok_or_else(|| format!("unknown color mode: {}", value))?,
+                );
+            }
+            "-l" | "--list" => options.list = true,
+            "-h" | "--help" => return Err(usage().to_string()),
+            _ => return Err(format!("unknown option: {}", arg)),
+        }
+    }
+
+    if let Some(path) = theme {
+        let theme = Theme::load(&path).map_err(|err| format!("{}: {}", path, err))?;
+        theme.apply(&mut options.config);
+    }
+    if let Some(charset) = charset {
+        options.config.character_set = charset;
+    }
+    if let Some(color) = color {
+        options.config.color_scheme = color;
+    }
+    if let Some(speed) = speed {
+        options.config.speed = speed;
+    }
+    Ok(options)
+}
+
+fn list() {
+    println!("Character sets (--charset):");
+    for set in CharacterSet::all_sets() {
+        let info = set.info();
+        println!("  {:<12} {}", info.id, info.preview);
+    }
+    println!("\nColor schemes (--color):");
+    for scheme in ColorScheme::all_schemes() {
+        let info = scheme.info();
+        println!("  {:<12} {}", info.id, info.primary_hex);
+    }
+    println!("\nSpeeds (--speed):");
+    for speed in RainSpeed::all_speeds() {
+        let info = speed.info();
+        println!("  {:<12} {}ms per update", info.id, info.update_interval_ms);
+    }
+}
+
+/// Terminal columns per rain column: 2 when the set has double-width glyphs
+fn column_width(character_set: CharacterSet) -> usize {
+    character_set
+        .get_glyphs()
+        .iter()
+        .map(|glyph| glyph.display_width())
+        .max()
+        .unwrap_or(1)
+}
+
+#[cfg(unix)]
+mod unix {
+    //! Raw mode, window size and signals through libc
+
+    use std::io;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static RESIZED: AtomicBool = AtomicBool::new(false);
+    static QUIT: AtomicBool = AtomicBool::new(false);
+
+    extern "C" fn on_resize(_: libc::c_int) {
+        RESIZED.store(true, Ordering::Relaxed);
+    }
+
+    extern "C" fn on_quit(_: libc::c_int) {
+        QUIT.store(true, Ordering::Relaxed);
+    }
+
+    /// Watch for window size changes and interrupt/termination requests
+    pub fn install_signal_handlers() {
+        // SAFETY: the handlers only store to atomics, which is async-signal-safe
+        unsafe {
+            libc::signal(
+                libc::SIGWINCH,
+                on_resize as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+            for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+                libc::signal(
+                    signal,
+                    on_quit as extern "C" fn(libc::c_int) as libc::sighandler_t,
+                );
+            }
+        }
+    }
+
+    /// Check (and reset) whether the window was resized
+    pub fn take_resized() -> bool {
+        RESIZED.swap(false, Ordering::Relaxed)
+    }
+
+    /// Check whether a quit signal arrived
+    pub fn quit_requested() -> bool {
+        QUIT.load(Ordering::Relaxed)
+    }
+
+    /// Check whether stdout is a terminal
+    pub fn stdout_is_terminal() -> bool {
+        // SAFETY: isatty only inspects the descriptor
+        unsafe { libc::isatty(libc::STDOUT_FILENO) == 1 }
+    }
+
+    /// Terminal size in cells (columns, rows)
+    pub fn terminal_size() -> Option<(usize, usize)> {
+        // SAFETY: TIOCGWINSZ fills in the winsize struct we pass
+        unsafe {
+            let mut size: libc::winsize = std::mem::zeroed();
+            if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) != 0
+                || size.ws_col == 0
+                || size.ws_row == 0
+            {
+                return None;
+            }
+            Some((size.ws_col as usize, size.ws_row as usize))
+        }
+    }
+
+    /// Unbuffered, non-echoing, non-blocking keyboard input until dropped
+    /// Signals stay enabled so Ctrl+C still reaches the quit handler.
+    pub struct RawMode {
+        original: libc::termios,
+    }
+
+    impl RawMode {
+        pub fn enable() -> io::Result<Self> {
+            // SAFETY: termios is plain data filled in by tcgetattr
+            unsafe {
+                let mut termios: libc::termios = std::mem::zeroed();
+                if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let original = termios;
+                termios.c_lflag &= !(libc::ICANON | libc::ECHO);
+                termios.c_cc[libc::VMIN] = 0;
+                termios.c_cc[libc::VTIME] = 0;
+                if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(Self { original })
+            }
+        }
+
+        /// Check the pending key presses for q, Q or Esc
+        pub fn quit_pressed(&self) -> bool {
+            let mut buffer = [0u8; 32];
+            // SAFETY: reads at most buffer.len() bytes into the buffer
+            let read = unsafe {
+                libc::read(
+                    libc::STDIN_FILENO,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    buffer.len(),
+                )
+            };
+            read > 0
+                && buffer[..read as usize]
+                    .iter()
+                    .any(|b| matches!(b, b'q' | b'Q' | 0x1b))
+        }
+    }
+
+    impl Drop for RawMode {
+        fn drop(&mut self) {
+            // SAFETY: restores the settings read in `enable`
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn run(options: Options) -> Result<(), String> {
+    use matrix_rain_core::Renderer;
+
+    if !unix::stdout_is_terminal() {
+        return Err("matrix-rain-term needs to run in a terminal".to_string());
+    }
+    let raw_mode =
+        unix::RawMode::enable().map_err(|err| format!("terminal setup failed: {}", err))?;
+    unix::install_signal_handlers();
+
+    let mut config = options.config;
+    let (columns, rows) = unix::terminal_size().unwrap_or((80, 24));
+    let mut matrix = MatrixRain::new(config.clone());
+    let stdout = std::io::stdout();
+    let mut renderer = TerminalRenderer::new(stdout.lock(), columns, rows, matrix.cell_size())
+        .with_color_mode(options.color_mode.unwrap_or_else(ColorMode::from_env))
+        .with_column_width(column_width(config.character_set));
+    config.screen_width = renderer.width();
+    config.screen_height = renderer.height();
+    matrix.set_config(config.clone());
+
+    renderer
+        .enter()
+        .map_err(|err| format!("terminal setup failed: {}", err))?;
+    let interval = Duration::from_millis(config.speed.update_interval_ms());
+    let start = Instant::now();
+    loop {
+        let frame_start = Instant::now();
+        if unix::quit_requested()
+            || raw_mode.quit_pressed()
+            || options
+                .duration
+                .is_some_and(|duration| start.elapsed() >= duration)
+        {
+            break;
+        }
+        if unix::take_resized() {
+            if let Some((columns, rows)) = unix::terminal_size() {
+                renderer.resize(columns, rows);
+                config.screen_width = renderer.width();
+                config.screen_height = renderer.height();
+                matrix.set_config(config.clone());
+            }
+        }
+
+        matrix.update();
+        matrix.render(&mut renderer);
+        if let Some(remaining) = interval.checked_sub(frame_start.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+
+    // Leave the alternate screen before raw mode is dropped
+    renderer
+        .leave()
+        .map_err(|err| format!("terminal restore failed: {}", err))
+}
+
+#[cfg(not(unix))]
+fn run(_options: Options) -> Result<(), String> {
+    Err("matrix-rain-term only supports Unix terminals".to_string())
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::from(2);
+        }
+    };
+    if options.list {
+        list();
+        return ExitCode::SUCCESS;
+    }
+
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,496 @@
+//! Matrix Rain command line tool
+//!
+//! Usage:
+//!   matrix-rain render [OPTIONS]    Render frames to numbered PNG or SVG files
+//!   matrix-rain preview [OPTIONS]   Render a looping GIF/APNG preview
+//!   matrix-rain atlas [OPTIONS]     Pack the glyphs into a texture atlas for GPU hosts
+
+use matrix_rain_core::config::Theme;
+use matrix_rain_core::export::{
+    atlas_metadata_path, export_animation, export_png_sequence, export_previews,
+    export_svg_sequence, AnimationFormat, ExportError, FrameOptions, FrameSequence, GlyphAtlas,
+};
+use matrix_rain_core::fonts::FontCoverage;
+use matrix_rain_core::rendering::{FontRasterizer, FrameStats, SvgRenderer};
+use matrix_rain_core::{CharacterSet, ColorScheme, MatrixRain, RainSpeed, ScreenSaverConfig};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Fonts tried in order when `--font` is not given; the one covering the most of the
+/// character set wins
+const FONT_CANDIDATES: &[&str] = &[
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/System/Library/Fonts/Menlo.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Command {
+    Render,
+    Preview,
+    Atlas,
+}
+
+/// File format of rendered frames
+#[derive(Clone, Copy, PartialEq)]
+enum FrameFormat {
+    Png,
+    Svg,
+}
+
+struct Options {
+    command: Command,
+    frames: FrameOptions,
+    out: Option<PathBuf>,
+    font: Option<PathBuf>,
+    face_index: u32,
+    config: ScreenSaverConfig,
+    format: Option<AnimationFormat>,
+    frame_format: FrameFormat,
+    embed_font: bool,
+    font_size: f32,
+    all: bool,
+    quiet: bool,
+}
+
+fn usage() -> &'static str {
+    "Usage: matrix-rain <COMMAND> [OPTIONS]\n\
+     \n\
+     Commands:\n\
+     \x20 render               Render frames to numbered PNG or SVG files without a window\n\
+     \x20 preview              Render a looping animated GIF or APNG preview\n\
+     \x20 atlas                Pack the glyphs into a PNG texture atlas with JSON metadata\n\
+     \n\
+     Options:\n\
+     \x20 --width N            Frame width in pixels (render 1920, preview 480)\n\
+     \x20 --height N           Frame height in pixels (render 1080, preview 270)\n\
+     \x20 --frames N           Number of frames (render 60, preview 48)\n\
+     \x20 --fps N              Simulated frame rate (render 60, preview 24)\n\
+     \x20 --seed N             Random seed, for reproducible output (default 0)\n\
+     \x20 --warmup SECS        Simulated seconds before the first frame (default 3)\n\
+     \x20 --out PATH           Output directory (render: frames, preview --all: previews)\n\
+     \x20                      or file (preview: preview.gif, atlas: atlas.png)\n\
+     \x20 --font PATH          TTF/OTF/TTC font (default: best installed match)\n\
+     \x20 --index N            Face index inside a .ttc collection (default 0)\n\
+     \x20 -c, --charset NAME   Character set\n\
+     \x20 -o, --color NAME     Color scheme\n\
+     \x20 -s, --speed NAME     Animation speed\n\
+     \x20 --theme FILE         Apply a JSON theme (before --charset/--color/--speed)\n\
+     \x20 -q, --quiet          Only print errors\n\
+     \n\
+     Render options:\n\
+     \x20 --format png|svg     Frame format (default png)\n\
+     \x20 --embed-font         Embed a subset of the font in SVG frames (TrueType only)\n\
+     \n\
+     Preview options:\n\
+     \x20 --format gif|apng    Animation format (default: from --out, else gif)\n\
+     \x20 --all                One preview per character set and color scheme\n\
+     \n\
+     Atlas options:\n\
+     \x20 --font-size N        Font size glyphs are rasterized at (default 48)\n\
+     \x20                      Metadata is written next to the PNG (atlas.json)"
+}
+
+fn parse_value<T: std::str::FromStr>(option: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", option))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {}", option, value))
+}
+
+fn parse_args(command: Command, mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let frames = match command {
+        Command::Render | Command::Atlas => FrameOptions::default(),
+        Command::Preview => FrameOptions {
+            width: 480,
+            height: 270,
+            frames: 48,
+            fps: 24.0,
+            ..Default::default()
+        },
+    };
+    let mut options = Options {
+        command,
+        frames,
+        out: None,
+        font: None,
+        face_index: 0,
+        config: ScreenSaverConfig::default(),
+        format: None,
+        frame_format: FrameFormat::Png,
+        embed_font: false,
+        font_size: 48.0,
+        all: false,
+        quiet: false,
+    };
+    let mut theme = None;
+    let mut charset = None;
+    let mut color = None;
+    let mut speed = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--width" => options.frames.width = parse_value(&arg, args.next())?,
+            "--height" => options.frames.height = parse_value(&arg, args.next())?,
+            "--frames" => options.frames.frames = parse_value(&arg, args.next())?,
+            "--fps" => options.frames.fps = parse_value(&arg, args.next())?,
+            "--seed" => options.frames.seed = parse_value(&arg, args.next())?,
+            "--warmup" => options.frames.warmup = parse_value(&arg, args.next())?,
+            "--out" => options.out = Some(parse_value(&arg, args.next())?),
+            "--font" => options.font = Some(parse_value(&arg, args.next())?),
+            "--index" => options.face_index = parse_value(&arg, args.next())?,
+            "--theme" => theme = Some(parse_value::<PathBuf>(&arg, args.next())?),
+            "-c" | "--charset" => {
+                let value: String = parse_value(&arg, args.next())?;
+                charset = Some(
+                    CharacterSet::from_id(&value)
+                        .ok_or_else(|| format!("unknown character set: {}", value))?,
+                );
+            }
+            "-o" | "--color" => {
+                let value: String = parse_value(&arg, args.next())?;
+                color = Some(
+                    ColorScheme::from_id(&value)
+                        .ok_or_else(|| format!("unknown color scheme: {}", value))?,
+                );
+            }
+            "-s" | "--speed" => {
+                let value: String = parse_value(&arg, args.next())?;
+                speed = Some(
+                    RainSpeed::from_id(&value)
+                        .ok_or_else(|| format!("unknown speed: {}", value))?,
+                );
+            }
+            "--format" if command == Command::Preview => {
+                let value: String = parse_value(&arg, args.next())?;
+                options.format = Some(
+                    AnimationFormat::from_id(&value)
+                        .ok_or_else(|| format!("unknown format: {}", value))?,
+                );
+            }
+            "--format" if command == Command::Render => {
+                let value: String = parse_value(&arg, args.next())?;
+                options.frame_format = match value.as_str() {
+                    "png" => FrameFormat::Png,
+                    "svg" => FrameFormat::Svg,
+                    _ => return Err(format!("unknown format: {}", value)),
+                };
+            }
+            "--embed-font" if command == Command::Render => options.embed_font = true,
+            "--all" if command == Command::Preview => options.all = true,
+            "--font-size" if command == Command::Atlas => {
+                options.font_size = parse_value(&arg, args.next())?
+            }
+            "-q" | "--quiet" => options.quiet = true,
+            "-h" | "--help" => return Err(usage().to_string()),
+            _ => return Err(format!("unknown option: {}", arg)),
+        }
+    }
+
+    if options.frames.width == 0 || options.frames.height == 0 {
+        return Err("--width and --height must be greater than 0".to_string());
+    }
+    if options.frames.fps <= 0.0 {
+        return Err("--fps must be greater than 0".to_string());
+    }
+    if options.embed_font && options.frame_format != FrameFormat::Svg {
+        return Err("--embed-font needs --format svg".to_string());
+    }
+    if options.font_size <= 0.0 {
+        return Err("--font-size must be greater than 0".to_string());
+    }
+    if command == Command::Preview && options.frames.frames == 0 {
+        return Err("--frames must be greater than 0".to_string());
+    }
+
+    if let Some(path) = theme {
+        let theme = Theme::load(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        theme.apply(&mut options.config);
+    }
+    if let Some(charset) = charset {
+        options.config.character_set = charset;
+    }
+    if let Some(color) = color {
+        options.config.color_scheme = color;
+    }
+    if let Some(speed) = speed {
+        options.config.speed = speed;
+    }
+    Ok(options)
+}
+
+/// Pick the installed candidate font that covers the most of a character set
+fn find_font(character_set: CharacterSet) -> Option<PathBuf> {
+    FONT_CANDIDATES
+        .iter()
+        .filter_map(|path| {
+            let coverage = FontCoverage::from_file(path).ok()?;
+            Some((coverage.check(character_set).covered, PathBuf::from(path)))
+        })
+        .max_by_key(|(covered, _)| *covered)
+        .map(|(_, path)| path)
+}
+
+/// Loads fonts once and points configs at the one they are drawn with
+struct FontLoader {
+    font: Option<PathBuf>,
+    face_index: u32,
+    loaded: HashMap<PathBuf, FontRasterizer>,
+}
+
+impl FontLoader {
+    /// Get a rasterizer for a config, setting its coverage font so the engine
+    /// only spawns glyphs the font can draw
+    fn setup(&mut self, config: &mut ScreenSaverConfig) -> Result<FontRasterizer, String> {
+        let font = self.path(config.character_set)?;
+        if !self.loaded.contains_key(&font) {
+            let rasterizer = FontRasterizer::from_file_index(&font, self.face_index)
+                .map_err(|err| format!("{}: {}", font.display(), err))?;
+            self.loaded.insert(font.clone(), rasterizer);
+        }
+        config.coverage_font = Some(font.clone());
+        config.coverage_font_index = self.face_index;
+        Ok(self.loaded[&font].clone())
+    }
+
+    /// The font file to draw a character set with
+    fn path(&self, character_set: CharacterSet) -> Result<PathBuf, String> {
+        match &self.font {
+            Some(font) => Ok(font.clone()),
+            None => find_font(character_set)
+                .ok_or_else(|| "no usable font found; pass one with --font PATH".to_string()),
+        }
+    }
+
+    /// Embed the subset of the font a config's character set needs in an SVG renderer
+    fn embed(
+        &self,
+        renderer: SvgRenderer,
+        config: &mut ScreenSaverConfig,
+    ) -> Result<SvgRenderer, String> {
+        let font = self.path(config.character_set)?;
+        let data = std::fs::read(&font).map_err(|err| format!("{}: {}", font.display(), err))?;
+        let chars: Vec<char> = config
+            .character_set
+            .get_glyphs()
+            .iter()
+            .flat_map(|glyph| glyph.chars())
+            .collect();
+        let renderer = renderer
+            .with_embedded_font(&data, self.face_index, chars)
+            .map_err(|err| format!("{}: {}", font.display(), err))?;
+        config.coverage_font = Some(font);
+        config.coverage_font_index = self.face_index;
+        Ok(renderer)
+    }
+}
+
+fn describe(options: &Options, what: &str) {
+    if !options.quiet {
+        println!(
+            "Rendering {} at {}x{} ({} frames, {} fps, seed {})",
+            what,
+            options.frames.width,
+            options.frames.height,
+            options.frames.frames,
+            options.frames.fps,
+            options.frames.seed,
+        );
+    }
+}
+
+fn render(options: Options, fonts: &mut FontLoader) -> Result<(), String> {
+    let mut config = options.config.clone();
+    let out = options
+        .out
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("frames"));
+    let total = options.frames.frames;
+    let quiet = options.quiet;
+    let progress = |index: u32, path: &Path| {
+        if !quiet {
+            println!("[{}/{}] {}", index + 1, total, path.display());
+        }
+    };
+
+    let paths = match options.frame_format {
+        FrameFormat::Png => {
+            let rasterizer = fonts.setup(&mut config)?;
+            describe(&options, "frames");
+            let mut sequence = FrameSequence::new(config, &options.frames, rasterizer);
+            let paths = export_png_sequence(&mut sequence, &out, progress);
+            if let Some(summary) =
+                batch_summary(sequence.frame_stats()).filter(|_| !quiet && paths.is_ok())
+            {
+                println!("{}", summary);
+            }
+            paths
+        }
+        FrameFormat::Svg => {
+            let mut renderer = SvgRenderer::new(options.frames.width, options.frames.height);
+            if options.embed_font {
+                renderer = fonts.embed(renderer, &mut config)?;
+            }
+            describe(&options, "SVG frames");
+            export_svg_sequence(config, &options.frames, renderer, &out, progress)
+        }
+    }
+    .map_err(|err| err.to_string())?;
+
+    if !quiet {
+        println!("Wrote {} frames to {}", paths.len(), out.display());
+    }
+    Ok(())
+}
+
+/// Draw batches per frame: the average and range, with the average glyph count
+fn batch_summary(stats: &[FrameStats]) -> Option<String> {
+    let batches = stats.iter().map(|frame| frame.batches);
+    let (min, max) = (batches.clone().min()?, batches.clone().max()?);
+    let mean = |total: usize| total as f32 / stats.len() as f32;
+    Some(format!(
+        "Batches per frame: {:.1} on average ({}-{}) for {:.0} glyphs",
+        mean(batches.sum()),
+        min,
+        max,
+        mean(stats.iter().map(|frame| frame.glyphs).sum())
+    ))
+}
+
+fn preview(options: Options, fonts: &mut FontLoader) -> Result<(), String> {
+    let format = options
+        .format
+        .or_else(|| options.out.as_ref().and_then(AnimationFormat::from_path))
+        .unwrap_or(AnimationFormat::Gif);
+
+    if options.all {
+        let dir = options
+            .out
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("previews"));
+        describe(&options, "previews");
+        let quiet = options.quiet;
+        let paths = export_previews(
+            &options.config,
+            &options.frames,
+            format,
+            &dir,
+            |config| fonts.setup(config).map_err(ExportError::Setup),
+            |path: &Path| {
+                if !quiet {
+                    println!("{}", path.display());
+                }
+            },
+        )
+        .map_err(|err| err.to_string())?;
+        if !quiet {
+            println!("Wrote {} previews to {}", paths.len(), dir.display());
+        }
+    } else {
+        let out = options
+            .out
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(format!("preview.{}", format.extension())));
+        let mut config = options.config.clone();
+        let rasterizer = fonts.setup(&mut config)?;
+        describe(&options, "preview");
+        export_animation(config, &options.frames, rasterizer, format, &out)
+            .map_err(|err| format!("{}: {}", out.display(), err))?;
+        if !options.quiet {
+            println!("Wrote {}", out.display());
+        }
+    }
+    Ok(())
+}
+
+fn atlas(options: Options, fonts: &mut FontLoader) -> Result<(), String> {
+    let out = options
+        .out
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("atlas.png"));
+    let metadata = atlas_metadata_path(&out);
+    let mut config = options.config.clone();
+    let mut rasterizer = fonts.setup(&mut config)?;
+
+    // Pack the pool the engine spawns from, so indices match RenderChar::glyph_index
+    let matrix = MatrixRain::new(config);
+    let atlas = GlyphAtlas::build(
+        matrix.glyph_pool().glyphs(),
+        &mut rasterizer,
+        options.font_size,
+    )
+    .map_err(|err| err.to_string())?;
+    if let Some(dir) = out.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+    }
+    atlas
+        .write(&out, &metadata)
+        .map_err(|err| format!("{}: {}", out.display(), err))?;
+
+    let missing = atlas
+        .glyphs
+        .iter()
+        .filter(|entry| entry.is_empty() && !entry.glyph.chars().all(char::is_whitespace))
+        .count();
+    if missing > 0 {
+        eprintln!(
+            "warning: {} of {} glyphs have nothing to draw in this font; try another with --font",
+            missing,
+            atlas.glyphs.len()
+        );
+    }
+    if !options.quiet {
+        println!(
+            "Wrote {} glyphs at {}px to {} ({}x{}) and {}",
+            atlas.glyphs.len(),
+            atlas.font_size,
+            out.display(),
+            atlas.width,
+            atlas.height,
+            metadata.display()
+        );
+    }
+    Ok(())
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let mut fonts = FontLoader {
+        font: options.font.clone(),
+        face_index: options.face_index,
+        loaded: HashMap::new(),
+    };
+    match options.command {
+        Command::Render => render(options, &mut fonts),
+        Command::Preview => preview(options, &mut fonts),
+        Command::Atlas => atlas(options, &mut fonts),
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let result = match args.next().as_deref() {
+        Some("render") => parse_args(Command::Render, args).map(run),
+        Some("preview") => parse_args(Command::Preview, args).map(run),
+        Some("atlas") => parse_args(Command::Atlas, args).map(run),
+        Some("-h" | "--help") | None => Err(usage().to_string()),
+        Some(command) => Err(format!("unknown command: {}\n\n{}", command, usage())),
+    };
+
+    match result {
+        Ok(Ok(())) => ExitCode::SUCCESS,
+        Ok(Err(message)) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -5,8 +5,7 @@
 
 use cocoa::appkit::{
     NSApp, NSApplication, NSApplicationActivationOptions, NSApplicationActivationPolicyRegular,
-    NSBackingStoreBuffered, NSMenu, NSMenuItem, NSRunningApplication, NSWindow,
-    NSWindowStyleMask,
+    NSBackingStoreBuffered, NSMenu, NSMenuItem, NSRunningApplication, NSWindow, NSWindowStyleMask,
 };
 use cocoa::base::{id, nil, selector, NO, YES};
 use cocoa::foundation::{NSAutoreleasePool, NSPoint, NSRect, NSSize, NSString};
@@ -23,8 +22,14 @@ struct MatrixState {
     engine: MatrixRain,
     last_update: Instant,
     update_interval: Duration,
+    frames_drawn: u64,
+    // Log batching stats every STATS_INTERVAL frames (--stats)
+    log_stats: bool,
 }
 
+// How often (in drawn frames) to log batching stats
+const STATS_INTERVAL: u64 = 300;
+
 impl MatrixState {
     fn new(width: u32, height: u32) -> Self {
         let config = ScreenSaverConfig::new(
@@ -41,6 +46,8 @@ impl MatrixState {
             engine,
             last_update: Instant::now(),
             update_interval,
+            frames_drawn: 0,
+            log_stats: std::env::args().any(|arg| arg == "--stats"),
         }
     }
 
@@ -54,8 +61,61 @@ impl MatrixState {
         }
     }
 
-    fn get_render_chars(&self) -> Vec<rendering::RenderChar> {
-        self.engine.get_render_data()
+    fn get_render_batches(&mut self) -> Vec<rendering::RenderBatch> {
+        let batches = self.engine.get_render_batches();
+        self.frames_drawn += 1;
+        if self.log_stats && self.frames_drawn % STATS_INTERVAL == 0 {
+            eprintln!(
+                "Frame {}: {}",
+                self.frames_drawn,
+                rendering::FrameStats::of(&batches)
+            );
+        }
+        batches
+    }
+}
+
+// Create an NSColor from a rain colour
+unsafe fn ns_color(color: rendering::Color) -> id {
+    msg_send![
+        class!(NSColor),
+        colorWithRed: color.r as CGFloat / 255.0
+        green: color.g as CGFloat / 255.0
+        blue: color.b as CGFloat / 255.0
+        alpha: color.a as CGFloat
+    ]
+}
+
+// Paint a backdrop over the whole view
+unsafe fn draw_backdrop(backdrop: rendering::Backdrop, bounds: NSRect) {
+    match backdrop {
+        rendering::Backdrop::Solid(color) => {
+            let _: () = msg_send![ns_color(color), setFill];
+            let _: () = msg_send![class!(NSBezierPath), fillRect: bounds];
+        }
+        rendering::Backdrop::Vertical { top, bottom } => {
+            // Angle 90 runs from the bottom to the top of the (unflipped) view
+            let gradient: id = msg_send![class!(NSGradient), alloc];
+            let gradient: id = msg_send![gradient, initWithStartingColor:ns_color(bottom) endingColor:ns_color(top)];
+            let _: () = msg_send![gradient, drawInRect:bounds angle:90.0 as CGFloat];
+            let _: () = msg_send![gradient, release];
+        }
+        rendering::Backdrop::Radial { center, edge } => {
+            let gradient: id = msg_send![class!(NSGradient), alloc];
+            let gradient: id = msg_send![gradient, initWithStartingColor:ns_color(center) endingColor:ns_color(edge)];
+            let _: () = msg_send![gradient, drawInRect:bounds relativeCenterPosition:NSPoint::new(0.0, 0.0)];
+            let _: () = msg_send![gradient, release];
+        }
+        rendering::Backdrop::Transparent => {
+            // Copy clear pixels in, rather than blending them over the last frame
+            let context: id = msg_send![class!(NSGraphicsContext), currentContext];
+            let _: () = msg_send![context, saveGraphicsState];
+            let _: () = msg_send![context, setCompositingOperation: 1usize]; // NSCompositingOperationCopy
+            let clear: id = msg_send![class!(NSColor), clearColor];
+            let _: () = msg_send![clear, setFill];
+            let _: () = msg_send![class!(NSBezierPath), fillRect: bounds];
+            let _: () = msg_send![context, restoreGraphicsState];
+        }
     }
 }
 
@@ -67,6 +127,9 @@ fn create_matrix_view_class() -> *const Class {
     // Add ivar to store the matrix state
     decl.add_ivar::<*mut std::ffi::c_void>("_matrixState");
 
+    // Add ivar for whether the view paints every pixel (NO for transparent backdrops)
+    decl.add_ivar::<objc::runtime::BOOL>("_opaque");
+
     // Override drawRect:
     extern "C" fn draw_rect(this: &Object, _cmd: Sel, _dirty_rect: NSRect) {
         unsafe {
@@ -81,53 +144,64 @@ fn create_matrix_view_class() -> *const Class {
             // Update engine if needed
             state_guard.update_if_needed();
 
-            // Fill background with black
-            let black: id = msg_send![class!(NSColor), blackColor];
-            let _: () = msg_send![black, setFill];
+            // Paint the backdrop the engine reports for the color scheme
             let bounds: NSRect = msg_send![this, bounds];
-            let _: () = msg_send![class!(NSBezierPath), fillRect: bounds];
-
-            // Get render characters
-            let chars = state_guard.get_render_chars();
+            draw_backdrop(state_guard.engine.backdrop(), bounds);
 
-            // Create font
-            let font_size: CGFloat = 16.0;
-            let font: id = msg_send![class!(NSFont), monospacedSystemFontOfSize:font_size weight:0.0];
+            // Get render batches: glyphs grouped by font size, glow and colour
+            let batches = state_guard.get_render_batches();
 
-            // Draw each character with simple color set
-            for render_char in chars.iter() {
-                // Convert character to NSString
-                let char_string = render_char.character.to_string();
-                let ns_string: id = NSString::alloc(nil).init_str(&char_string);
+            let font_attr_name = NSString::alloc(nil).init_str("NSFont");
+            let color_attr_name = NSString::alloc(nil).init_str("NSColor");
 
-                // Create color
+            // Set up the text attributes once per batch
+            for batch in batches.iter() {
+                let font: id = msg_send![class!(NSFont), monospacedSystemFontOfSize:batch.font_size as CGFloat weight:0.0];
                 let color: id = msg_send![
                     class!(NSColor),
-                    colorWithRed: render_char.color.r as CGFloat / 255.0
-                    green: render_char.color.g as CGFloat / 255.0
-                    blue: render_char.color.b as CGFloat / 255.0
-                    alpha: render_char.color.a as CGFloat / 255.0
+                    colorWithRed: batch.color.r as CGFloat / 255.0
+                    green: batch.color.g as CGFloat / 255.0
+                    blue: batch.color.b as CGFloat / 255.0
+                    alpha: batch.color.a as CGFloat
                 ];
-
-                // Set the color as current
-                let _: () = msg_send![color, set];
-
-                // Draw the string at position with font
-                let point = NSPoint::new(render_char.x as f64, render_char.y as f64);
-
-                // Create attributed string with font
-                let attr_string: id = msg_send![class!(NSAttributedString), alloc];
-                let font_attr_name = NSString::alloc(nil).init_str("NSFont");
-                let attrs: id = msg_send![class!(NSDictionary), dictionaryWithObject:font forKey:font_attr_name];
-                let attr_string: id = msg_send![attr_string, initWithString:ns_string attributes:attrs];
-
-                let _: () = msg_send![attr_string, drawAtPoint:point];
-
-                // Release
-                let _: () = msg_send![ns_string, release];
-                let _: () = msg_send![attr_string, release];
-                let _: () = msg_send![font_attr_name, release];
+                let keys: [id; 2] = [font_attr_name, color_attr_name];
+                let objects: [id; 2] = [font, color];
+                let attrs: id = msg_send![class!(NSDictionary), dictionaryWithObjects:objects.as_ptr() forKeys:keys.as_ptr() count:2usize];
+
+                for render_char in batch.chars.iter() {
+                    // Convert character to NSString
+                    let char_string = render_char.character.to_string();
+                    let ns_string: id = NSString::alloc(nil).init_str(&char_string);
+                    let point = NSPoint::new(render_char.x as f64, render_char.y as f64);
+
+                    if render_char.transform.is_none() {
+                        let _: () = msg_send![ns_string, drawAtPoint:point withAttributes:attrs];
+                    } else {
+                        // Mirror/rotate around the glyph centre
+                        let size = render_char.font_size as CGFloat;
+                        let center_x = point.x + size * 0.3;
+                        let center_y = point.y + size * 0.6;
+                        let (scale_x, scale_y) = render_char.transform.mirror_scale();
+
+                        let _: () = msg_send![class!(NSGraphicsContext), saveGraphicsState];
+                        let xform: id = msg_send![class!(NSAffineTransform), transform];
+                        let _: () = msg_send![xform, translateXBy:center_x yBy:center_y];
+                        let _: () = msg_send![xform, rotateByDegrees:-(render_char.transform.rotation_degrees() as CGFloat)];
+                        let _: () =
+                            msg_send![xform, scaleXBy:scale_x as CGFloat yBy:scale_y as CGFloat];
+                        let _: () = msg_send![xform, translateXBy:-center_x yBy:-center_y];
+                        let _: () = msg_send![xform, concat];
+                        let _: () = msg_send![ns_string, drawAtPoint:point withAttributes:attrs];
+                        let _: () = msg_send![class!(NSGraphicsContext), restoreGraphicsState];
+                    }
+
+                    // Release
+                    let _: () = msg_send![ns_string, release];
+                }
             }
+
+            let _: () = msg_send![font_attr_name, release];
+            let _: () = msg_send![color_attr_name, release];
         }
     }
 
@@ -138,13 +212,16 @@ fn create_matrix_view_class() -> *const Class {
         );
     }
 
-    // Override isOpaque (return YES for better performance)
-    extern "C" fn is_opaque(_this: &Object, _cmd: Sel) -> objc::runtime::BOOL {
-        YES
+    // Override isOpaque (YES for better performance, unless the backdrop is transparent)
+    extern "C" fn is_opaque(this: &Object, _cmd: Sel) -> objc::runtime::BOOL {
+        unsafe { *this.get_ivar("_opaque") }
     }
 
     unsafe {
-        decl.add_method(sel!(isOpaque), is_opaque as extern "C" fn(&Object, Sel) -> objc::runtime::BOOL);
+        decl.add_method(
+            sel!(isOpaque),
+            is_opaque as extern "C" fn(&Object, Sel) -> objc::runtime::BOOL,
+        );
     }
 
     decl.register()
@@ -210,8 +287,17 @@ fn main() {
             window_width as u32,
             window_height as u32,
         )));
+        let transparent = state.lock().unwrap().engine.backdrop().is_transparent();
         let state_ptr = Box::into_raw(Box::new(state)) as *mut std::ffi::c_void;
         (*view).set_ivar("_matrixState", state_ptr);
+        (*view).set_ivar::<objc::runtime::BOOL>("_opaque", if transparent { NO } else { YES });
+
+        // A transparent backdrop shows whatever is behind the window
+        if transparent {
+            let clear: id = msg_send![class!(NSColor), clearColor];
+            let _: () = msg_send![window, setOpaque: NO];
+            let _: () = msg_send![window, setBackgroundColor: clear];
+        }
 
         window.setContentView_(view);
 
@@ -231,7 +317,9 @@ fn main() {
 
         // Activate app
         let current_app = NSRunningApplication::currentApplication(nil);
-        current_app.activateWithOptions_(NSApplicationActivationOptions::NSApplicationActivateIgnoringOtherApps);
+        current_app.activateWithOptions_(
+            NSApplicationActivationOptions::NSApplicationActivateIgnoringOtherApps,
+        );
 
         // Run the application
         app.run();
@@ -0,0 +1,296 @@
+//! Colour-vision accessibility: CVD-safe palettes, WCAG contrast and CVD simulation
+
+use super::{ColorScheme, CustomColors};
+use crate::rendering::{linear_to_srgb, srgb_to_linear, BlendMode, Color};
+use serde::{Deserialize, Serialize};
+
+/// A palette designed to stay readable with colour-vision deficiencies
+///
+/// Every trail stage keeps at least 3:1 contrast against black as it is drawn
+/// (faded by its trail alpha), with normal vision and under protan, deutan and
+/// tritan simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessiblePalette {
+    /// Stable identifier (lowercase, never localized)
+    pub id: &'static str,
+    /// Display name in English
+    pub name: &'static str,
+    /// Palette colours
+    pub colors: CustomColors,
+}
+
+impl AccessiblePalette {
+    /// All accessible palettes, in a stable order
+    pub fn all() -> Vec<AccessiblePalette> {
+        vec![
+            Self::new(
+                "cvd-sky-blue",
+                "Sky Blue (CVD safe)",
+                [(86, 180, 233), (60, 160, 222), (28, 138, 210)],
+            ),
+            Self::new(
+                "cvd-amber",
+                "Amber (CVD safe)",
+                [(230, 159, 0), (210, 141, 0), (185, 121, 0)],
+            ),
+            Self::new(
+                "cvd-yellow",
+                "Yellow (CVD safe)",
+                [(240, 228, 66), (200, 190, 50), (160, 150, 40)],
+            ),
+            Self::new(
+                "cvd-monochrome",
+                "Monochrome (CVD safe)",
+                [(220, 220, 220), (170, 170, 170), (125, 125, 125)],
+            ),
+        ]
+    }
+
+    fn new(id: &'static str, name: &'static str, trail: [(u8, u8, u8); 3]) -> Self {
+        Self {
+            id,
+            name,
+            colors: CustomColors {
+                leader: (255, 255, 255),
+                primary: trail[0],
+                secondary: trail[1],
+                tertiary: trail[2],
+                background: (0, 0, 0),
+            },
+        }
+    }
+
+    /// Look up a palette by its stable string ID
+    pub fn from_id(id: &str) -> Option<AccessiblePalette> {
+        Self::all()
+            .into_iter()
+            .find(|palette| palette.id.eq_ignore_ascii_case(id))
+    }
+
+    /// The palette as a color scheme
+    pub fn scheme(&self) -> ColorScheme {
+        ColorScheme::Custom(self.colors)
+    }
+}
+
+/// Kinds of colour-vision deficiency that can be simulated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ColorVisionDeficiency {
+    /// Missing long-wavelength (red) cones
+    Protanopia,
+    /// Missing medium-wavelength (green) cones
+    Deuteranopia,
+    /// Missing short-wavelength (blue) cones
+    Tritanopia,
+}
+
+impl ColorVisionDeficiency {
+    /// All simulated deficiencies
+    pub fn all() -> [ColorVisionDeficiency; 3] {
+        [
+            ColorVisionDeficiency::Protanopia,
+            ColorVisionDeficiency::Deuteranopia,
+            ColorVisionDeficiency::Tritanopia,
+        ]
+    }
+
+    /// Linear-RGB simulation matrix (Machado, Oliveira & Fernandes 2009, severity 1.0)
+    fn matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            ColorVisionDeficiency::Protanopia => [
+                [0.152_286, 1.052_583, -0.204_868],
+                [0.114_503, 0.786_281, 0.099_216],
+                [-0.003_882, -0.048_116, 1.051_998],
+            ],
+            ColorVisionDeficiency::Deuteranopia => [
+                [0.367_322, 0.860_646, -0.227_968],
+                [0.280_085, 0.672_501, 0.047_413],
+                [-0.011_820, 0.042_940, 0.968_881],
+            ],
+            ColorVisionDeficiency::Tritanopia => [
+                [1.255_528, -0.076_749, -0.178_779],
+                [-0.078_411, 0.930_809, 0.147_602],
+                [0.004_733, 0.691_367, 0.303_900],
+            ],
+        }
+    }
+
+    /// Simulate how a colour appears with this deficiency
+    pub fn simulate(&self, (r, g, b): (u8, u8, u8)) -> (u8, u8, u8) {
+        let linear = [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b)];
+        let m = self.matrix();
+        let row = |i: usize| {
+            linear_to_srgb(m[i][0] * linear[0] + m[i][1] * linear[1] + m[i][2] * linear[2])
+        };
+        (row(0), row(1), row(2))
+    }
+
+    /// Simulate a whole color scheme (returns a custom scheme with simulated colours)
+    pub fn simulate_scheme(&self, scheme: &ColorScheme) -> ColorScheme {
+        let colors = scheme.custom_colors();
+        ColorScheme::Custom(CustomColors {
+            leader: self.simulate(colors.leader),
+            primary: self.simulate(colors.primary),
+            secondary: self.simulate(colors.secondary),
+            tertiary: self.simulate(colors.tertiary),
+            background: self.simulate(colors.background),
+        })
+    }
+}
+
+/// WCAG relative luminance of an sRGB colour (0.0 = black, 1.0 = white)
+pub fn relative_luminance((r, g, b): (u8, u8, u8)) -> f32 {
+    0.2126 * srgb_to_linear(r) + 0.7152 * srgb_to_linear(g) + 0.0722 * srgb_to_linear(b)
+}
+
+/// WCAG contrast ratio between two colours (1.0-21.0)
+pub fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f32 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+/// WCAG contrast of each trail stage against a background
+///
+/// Each stage is measured where it begins in the trail, composited at that
+/// position's `trail_alpha` over the background as the renderer draws it. Past
+/// that, the tail keeps fading out towards the background by design.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ContrastReport {
+    /// Leader (head) glyph
+    pub leader: f32,
+    /// Glyphs right behind the leader
+    pub primary: f32,
+    /// Mid-trail glyphs
+    pub secondary: f32,
+    /// Oldest/fading glyphs
+    pub tertiary: f32,
+}
+
+impl ContrastReport {
+    /// Check a scheme's stages against a background colour
+    pub fn for_scheme(scheme: &ColorScheme, background: (u8, u8, u8)) -> Self {
+        let stage = |position: f32| {
+            let (r, g, b, alpha) = scheme.get_color_with_alpha(position);
+            let drawn = Color::rgba(r, g, b, alpha)
+                .blend_over(Color::from_rgb_tuple(background), BlendMode::Normal);
+            contrast_ratio((drawn.r, drawn.g, drawn.b), background)
+        };
+        // Where each stage starts in `get_color_with_alpha`
+        Self {
+            leader: stage(0.0),
+            primary: stage(0.05),
+            secondary: stage(0.15),
+            tertiary: stage(0.5),
+        }
+    }
+
+    /// The lowest contrast of any stage
+    pub fn min(&self) -> f32 {
+        self.leader
+            .min(self.primary)
+            .min(self.secondary)
+            .min(self.tertiary)
+    }
+
+    /// Check whether every stage reaches a ratio (3.0 is the WCAG minimum for graphics,
+    /// 4.5 for normal text)
+    pub fn passes(&self, ratio: f32) -> bool {
+        self.min() >= ratio
+    }
+}
+
+impl ColorScheme {
+    /// WCAG contrast of each trail stage against the scheme's own background
+    pub fn contrast_report(&self) -> ContrastReport {
+        ContrastReport::for_scheme(self, self.get_background_color())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert!((contrast_ratio((255, 255, 255), (0, 0, 0)) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio((0, 0, 0), (0, 0, 0)) - 1.0).abs() < 1e-6);
+        // Symmetric
+        assert_eq!(
+            contrast_ratio((0, 255, 70), (10, 10, 10)),
+            contrast_ratio((10, 10, 10), (0, 255, 70))
+        );
+        // Known value: WCAG mid-grey #767676 on white is 4.54:1
+        assert!((contrast_ratio((0x76, 0x76, 0x76), (255, 255, 255)) - 4.54).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_default_tail_is_low_contrast() {
+        let report = ColorScheme::MatrixGreen.contrast_report();
+        assert!(report.primary > 10.0);
+        assert!(!report.passes(3.0));
+        assert_eq!(report.min(), report.tertiary);
+    }
+
+    #[test]
+    fn test_accessible_palettes_pass_under_simulation() {
+        let palettes = AccessiblePalette::all();
+        assert!(palettes.len() >= 4);
+
+        for palette in palettes {
+            let scheme = palette.scheme();
+            assert!(scheme.contrast_report().passes(3.0), "{}", palette.id);
+            for cvd in ColorVisionDeficiency::all() {
+                let simulated = cvd.simulate_scheme(&scheme);
+                assert!(
+                    simulated.contrast_report().passes(3.0),
+                    "{} under {:?}",
+                    palette.id,
+                    cvd
+                );
+            }
+            assert_eq!(AccessiblePalette::from_id(palette.id), Some(palette));
+        }
+    }
+
+    #[test]
+    fn test_contrast_is_measured_as_drawn() {
+        for palette in AccessiblePalette::all() {
+            let scheme = palette.scheme();
+            let background = scheme.get_background_color();
+            for position in [0.0, 0.05, 0.15, 0.5] {
+                let (r, g, b, alpha) = scheme.get_color_with_alpha(position);
+                assert!(alpha < 1.0 || position < 0.1);
+                let drawn = Color::rgba(r, g, b, alpha)
+                    .blend_over(Color::from_rgb_tuple(background), BlendMode::Normal);
+                let ratio = contrast_ratio((drawn.r, drawn.g, drawn.b), background);
+                assert!(ratio >= 3.0, "{} at {}: {}", palette.id, position, ratio);
+            }
+
+            // The faded tail is dimmer than its opaque colour
+            let opaque = contrast_ratio(scheme.get_tertiary_color(), background);
+            assert!(scheme.contrast_report().tertiary < opaque, "{}", palette.id);
+        }
+    }
+
+    #[test]
+    fn test_simulation() {
+        for cvd in ColorVisionDeficiency::all() {
+            // Greys are unaffected
+            assert_eq!(cvd.simulate((0, 0, 0)), (0, 0, 0));
+            assert_eq!(cvd.simulate((128, 128, 128)), (128, 128, 128));
+        }
+
+        // Red and green become hard to tell apart for protans and deutans
+        let red = (255, 0, 0);
+        let green = (0, 160, 0);
+        for cvd in [
+            ColorVisionDeficiency::Protanopia,
+            ColorVisionDeficiency::Deuteranopia,
+        ] {
+            let (r1, g1, _) = cvd.simulate(red);
+            let (r2, g2, _) = cvd.simulate(green);
+            assert!((r1 as i32 - r2 as i32).abs() < 100);
+            assert!((g1 as i32 - g2 as i32).abs() < 100);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Animated palette settings (hue cycling, breathing, per-column hue offsets)
+
+use super::ColorScheme;
+use serde::{Deserialize, Serialize};
+
+/// How trail colours change over time and across the screen
+///
+/// All zero (the default) leaves colours static.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PaletteAnimation {
+    /// Seconds for a full rainbow hue rotation (0.0 disables cycling)
+    pub hue_cycle_period: f32,
+    /// Hue offset in degrees between neighbouring columns (a moving spectrum when cycling)
+    pub column_hue_offset: f32,
+    /// Scheme to slowly breathe towards and back (None disables breathing)
+    pub breathe_with: Option<ColorScheme>,
+    /// Seconds for one full breath from the base scheme to `breathe_with` and back
+    pub breathe_period: f32,
+}
+
+impl PaletteAnimation {
+    /// Rainbow rotation every `period` seconds with a spectrum across the columns
+    pub fn rainbow(period: f32) -> Self {
+        Self {
+            hue_cycle_period: period,
+            column_hue_offset: 4.0,
+            ..Self::default()
+        }
+    }
+
+    /// Breathe between the base scheme and another over `period` seconds
+    pub fn breathing(scheme: ColorScheme, period: f32) -> Self {
+        Self {
+            breathe_with: Some(scheme),
+            breathe_period: period,
+            ..Self::default()
+        }
+    }
+
+    /// Check whether colours change over time or across columns
+    pub fn is_enabled(&self) -> bool {
+        self.hue_cycle_period > 0.0
+            || self.column_hue_offset != 0.0
+            || self.breathing_scheme().is_some()
+    }
+
+    /// Hue rotation in degrees (0-360) for a column at a point in time
+    pub fn hue_shift(&self, time: f32, column: usize) -> f32 {
+        let cycle = if self.hue_cycle_period > 0.0 {
+            360.0 * time / self.hue_cycle_period
+        } else {
+            0.0
+        };
+        (cycle + self.column_hue_offset * column as f32).rem_euclid(360.0)
+    }
+
+    /// How far towards `breathe_with` colours are at a point in time (0.0-1.0)
+    pub fn breathe_amount(&self, time: f32) -> f32 {
+        if self.breathing_scheme().is_none() {
+            return 0.0;
+        }
+        let phase = time / self.breathe_period * std::f32::consts::TAU;
+        (1.0 - phase.cos()) / 2.0
+    }
+
+    /// The scheme to breathe towards, if breathing is enabled
+    pub fn breathing_scheme(&self) -> Option<ColorScheme> {
+        self.breathe_with.filter(|_| self.breathe_period > 0.0)
+    }
+}
+
+/// Rotate the hue of a colour by `degrees`, keeping saturation and brightness
+/// Greys (such as the white leader) are unchanged.
+pub fn rotate_hue((r, g, b): (u8, u8, u8), degrees: f32) -> (u8, u8, u8) {
+    let max = r.max(g).max(b) as f32;
+    let min = r.min(g).min(b) as f32;
+    let chroma = max - min;
+    if chroma == 0.0 || degrees.rem_euclid(360.0) == 0.0 {
+        return (r, g, b);
+    }
+
+    let (rf, gf, bf) = (r as f32, g as f32, b as f32);
+    let hue = if max == rf {
+        60.0 * ((gf - bf) / chroma).rem_euclid(6.0)
+    } else if max == gf {
+        60.0 * ((bf - rf) / chroma + 2.0)
+    } else {
+        60.0 * ((rf - gf) / chroma + 4.0)
+    };
+
+    let sector = (hue + degrees).rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+    let (r1, g1, b1) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let channel = |v: f32| (v + min).round().clamp(0.0, 255.0) as u8;
+    (channel(r1), channel(g1), channel(b1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_static() {
+        let animation = PaletteAnimation::default();
+        assert!(!animation.is_enabled());
+        assert_eq!(animation.hue_shift(12.5, 40), 0.0);
+        assert_eq!(animation.breathe_amount(3.0), 0.0);
+    }
+
+    #[test]
+    fn test_hue_shift() {
+        let animation = PaletteAnimation::rainbow(10.0);
+        assert!(animation.is_enabled());
+        assert_eq!(animation.hue_shift(0.0, 0), 0.0);
+        assert!((animation.hue_shift(2.5, 0) - 90.0).abs() < 1e-3);
+        assert!((animation.hue_shift(10.0, 0)).abs() < 1e-3);
+        assert!((animation.hue_shift(0.0, 10) - 40.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_breathing() {
+        let animation = PaletteAnimation::breathing(ColorScheme::Purple, 8.0);
+        assert_eq!(animation.breathe_amount(0.0), 0.0);
+        assert!((animation.breathe_amount(4.0) - 1.0).abs() < 1e-5);
+        assert!((animation.breathe_amount(2.0) - 0.5).abs() < 1e-5);
+
+        // A zero period disables breathing
+        let animation = PaletteAnimation::breathing(ColorScheme::Purple, 0.0);
+        assert!(!animation.is_enabled());
+        assert_eq!(animation.breathe_amount(4.0), 0.0);
+    }
+
+    #[test]
+    fn test_rotate_hue() {
+        assert_eq!(rotate_hue((255, 0, 0), 120.0), (0, 255, 0));
+        assert_eq!(rotate_hue((255, 0, 0), 240.0), (0, 0, 255));
+        assert_eq!(rotate_hue((255, 0, 0), -120.0), (0, 0, 255));
+        assert_eq!(rotate_hue((0, 255, 70), 360.0), (0, 255, 70));
+        assert_eq!(rotate_hue((255, 255, 255), 90.0), (255, 255, 255));
+
+        // Brightness and saturation are kept
+        let (r, g, b) = rotate_hue((0, 255, 70), 100.0);
+        assert_eq!(r.max(g).max(b), 255);
+        assert_eq!(r.min(g).min(b), 0);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let animation = PaletteAnimation {
+            column_hue_offset: 2.0,
+            ..PaletteAnimation::breathing(ColorScheme::Cyan, 6.0)
+        };
+        let json = serde_json::to_string(&animation).unwrap();
+        let back: PaletteAnimation = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, animation);
+
+        let partial: PaletteAnimation =
+            serde_json::from_str(r#"{"hue_cycle_period":5.0}"#).unwrap();
+        assert_eq!(partial.hue_cycle_period, 5.0);
+        assert_eq!(partial.breathe_with, None);
+    }
+}
@@ -0,0 +1,168 @@
+//! Background settings (solid colour, gradient backdrop or transparent)
+
+use super::colors::{hex_rgb, mix_rgb};
+use super::ColorScheme;
+use crate::rendering::{Backdrop, Color};
+use serde::{Deserialize, Serialize};
+
+/// What fills the screen behind the rain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum BackgroundFill {
+    /// The color scheme's background (black for presets)
+    #[default]
+    Scheme,
+    /// A solid colour
+    Solid(#[serde(with = "hex_rgb")] (u8, u8, u8)),
+    /// Top-to-bottom gradient
+    Vertical {
+        /// Colour along the top edge
+        #[serde(with = "hex_rgb")]
+        top: (u8, u8, u8),
+        /// Colour along the bottom edge
+        #[serde(with = "hex_rgb")]
+        bottom: (u8, u8, u8),
+    },
+    /// Gradient from the screen centre out to the corners
+    Radial {
+        /// Colour at the centre
+        #[serde(with = "hex_rgb")]
+        center: (u8, u8, u8),
+        /// Colour at the corners
+        #[serde(with = "hex_rgb")]
+        edge: (u8, u8, u8),
+    },
+    /// Nothing is painted, for overlay use
+    Transparent,
+}
+
+/// Background settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct BackgroundConfig {
+    /// What fills the screen
+    pub fill: BackgroundFill,
+    /// How much of the scheme's primary colour tints the fill (0.0 = none, ~0.05 is subtle)
+    pub tint: f32,
+}
+
+impl BackgroundConfig {
+    /// A solid colour background
+    pub fn solid(rgb: (u8, u8, u8)) -> Self {
+        Self {
+            fill: BackgroundFill::Solid(rgb),
+            ..Self::default()
+        }
+    }
+
+    /// A transparent background for overlays
+    pub fn transparent() -> Self {
+        Self {
+            fill: BackgroundFill::Transparent,
+            ..Self::default()
+        }
+    }
+
+    /// Resolve to final colours for a color scheme
+    pub fn backdrop(&self, scheme: &ColorScheme) -> Backdrop {
+        let tint_amount = self.tint.clamp(0.0, 1.0);
+        let tint = |rgb: (u8, u8, u8)| {
+            Color::from_rgb_tuple(mix_rgb(rgb, scheme.get_primary_color(), tint_amount))
+        };
+
+        match self.fill {
+            BackgroundFill::Scheme => Backdrop::Solid(tint(scheme.get_background_color())),
+            BackgroundFill::Solid(rgb) => Backdrop::Solid(tint(rgb)),
+            BackgroundFill::Vertical { top, bottom } => Backdrop::Vertical {
+                top: tint(top),
+                bottom: tint(bottom),
+            },
+            BackgroundFill::Radial { center, edge } => Backdrop::Radial {
+                center: tint(center),
+                edge: tint(edge),
+            },
+            BackgroundFill::Transparent => Backdrop::Transparent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CustomColors;
+
+    #[test]
+    fn test_default_follows_scheme() {
+        let background = BackgroundConfig::default();
+        assert_eq!(
+            background.backdrop(&ColorScheme::MatrixGreen),
+            Backdrop::Solid(Color::BLACK)
+        );
+
+        let mut colors = CustomColors::from_primary((255, 102, 0));
+        colors.background = (20, 10, 0);
+        assert_eq!(
+            background.backdrop(&ColorScheme::Custom(colors)),
+            Backdrop::Solid(Color::rgb(20, 10, 0))
+        );
+    }
+
+    #[test]
+    fn test_tint_from_scheme() {
+        let background = BackgroundConfig {
+            tint: 0.1,
+            ..Default::default()
+        };
+        let Backdrop::Solid(color) = background.backdrop(&ColorScheme::MatrixGreen) else {
+            panic!("expected a solid backdrop");
+        };
+        assert_eq!((color.r, color.g, color.b), (0, 26, 7));
+
+        // Transparent backgrounds are never tinted
+        let background = BackgroundConfig {
+            tint: 0.5,
+            ..BackgroundConfig::transparent()
+        };
+        assert!(background
+            .backdrop(&ColorScheme::MatrixGreen)
+            .is_transparent());
+    }
+
+    #[test]
+    fn test_gradients_resolve() {
+        let background = BackgroundConfig {
+            fill: BackgroundFill::Vertical {
+                top: (0, 0, 0),
+                bottom: (0, 40, 0),
+            },
+            tint: 0.0,
+        };
+        assert_eq!(
+            background.backdrop(&ColorScheme::Red),
+            Backdrop::Vertical {
+                top: Color::BLACK,
+                bottom: Color::rgb(0, 40, 0),
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialization() {
+        let background = BackgroundConfig {
+            fill: BackgroundFill::Radial {
+                center: (0, 30, 10),
+                edge: (0, 0, 0),
+            },
+            tint: 0.05,
+        };
+        let json = serde_json::to_string(&background).unwrap();
+        assert!(json.contains("\"center\":\"#001e0a\""));
+        assert_eq!(
+            serde_json::from_str::<BackgroundConfig>(&json).unwrap(),
+            background
+        );
+
+        let solid: BackgroundConfig =
+            serde_json::from_str(r##"{"fill":{"Solid":"#102030"}}"##).unwrap();
+        assert_eq!(solid, BackgroundConfig::solid((0x10, 0x20, 0x30)));
+    }
+}
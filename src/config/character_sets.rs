@@ -1,5 +1,7 @@
 //! Character sets for different languages/scripts
 
+use super::info::{CharacterSetInfo, WritingDirection};
+use crate::rendering::Glyph;
 use serde::{Deserialize, Serialize};
 
 /// Available character sets for the Matrix rain effect
@@ -173,6 +175,165 @@ impl CharacterSet {
         }
     }
 
+    /// Multi-codepoint clusters drawn alongside the single characters
+    /// (Devanagari conjuncts and precomposed Tamil syllables)
+    pub fn clusters(&self) -> &'static [&'static str] {
+        match self {
+            CharacterSet::Hindi => &[
+                "क्ष",
+                "त्र",
+                "ज्ञ",
+                "श्र",
+                "द्ध",
+                "द्व",
+                "प्र",
+                "क्र",
+                "स्त",
+                "न्द",
+                "कि",
+                "की",
+                "गु",
+                "मे",
+                "हो",
+                "सौ",
+            ],
+            CharacterSet::Tamil => &[
+                "கா",
+                "கி",
+                "கு",
+                "கெ",
+                "கொ",
+                "மா",
+                "மீ",
+                "னு",
+                "டோ",
+                "தை",
+                "ஷ்",
+                "க்ஷ",
+                "ஸ்ரீ",
+            ],
+            _ => &[],
+        }
+    }
+
+    /// Get every glyph of this character set: the single characters followed by its clusters
+    pub fn get_glyphs(&self) -> Vec<Glyph> {
+        let mut glyphs: Vec<Glyph> = self
+            .get_characters()
+            .into_iter()
+            .map(Glyph::from_char)
+            .collect();
+        glyphs.extend(
+            self.clusters()
+                .iter()
+                .filter_map(|cluster| Glyph::new(cluster)),
+        );
+        glyphs
+    }
+
+    /// Get all available character sets
+    pub fn all_sets() -> Vec<CharacterSet> {
+        vec![
+            CharacterSet::Japanese,
+            CharacterSet::Hindi,
+            CharacterSet::Tamil,
+            CharacterSet::Sinhala,
+            CharacterSet::Korean,
+            CharacterSet::Jawi,
+            CharacterSet::Arabic,
+            CharacterSet::Hebrew,
+            CharacterSet::Thai,
+            CharacterSet::Mixed,
+        ]
+    }
+
+    /// Get the character set at a stable index (the order of `all_sets`, used by FFI)
+    pub fn from_index(index: u8) -> Option<CharacterSet> {
+        Self::all_sets().get(index as usize).copied()
+    }
+
+    /// Get the stable index of this character set
+    pub fn index(&self) -> u8 {
+        Self::all_sets()
+            .iter()
+            .position(|set| set == self)
+            .unwrap_or(0) as u8
+    }
+
+    /// Look up a character set by its stable string ID (e.g. "japanese")
+    pub fn from_id(id: &str) -> Option<CharacterSet> {
+        Self::all_sets()
+            .into_iter()
+            .find(|set| set.info().id.eq_ignore_ascii_case(id))
+    }
+
+    /// Get display metadata for this character set
+    pub fn info(&self) -> CharacterSetInfo {
+        let (id, english_name, native_name, direction) = match self {
+            CharacterSet::Japanese => (
+                "japanese",
+                "Japanese",
+                "カタカナ",
+                WritingDirection::LeftToRight,
+            ),
+            CharacterSet::Hindi => ("hindi", "Hindi", "हिन्दी", WritingDirection::LeftToRight),
+            CharacterSet::Tamil => ("tamil", "Tamil", "தமிழ்", WritingDirection::LeftToRight),
+            CharacterSet::Sinhala => ("sinhala", "Sinhala", "සිංහල", WritingDirection::LeftToRight),
+            CharacterSet::Korean => ("korean", "Korean", "한글", WritingDirection::LeftToRight),
+            CharacterSet::Jawi => ("jawi", "Jawi", "جاوي", WritingDirection::RightToLeft),
+            CharacterSet::Arabic => ("arabic", "Arabic", "العربية", WritingDirection::RightToLeft),
+            CharacterSet::Hebrew => ("hebrew", "Hebrew", "עברית", WritingDirection::RightToLeft),
+            CharacterSet::Thai => ("thai", "Thai", "ไทย", WritingDirection::LeftToRight),
+            CharacterSet::Mixed => ("mixed", "Mixed", "Mixed", WritingDirection::Mixed),
+        };
+
+        CharacterSetInfo {
+            id,
+            english_name,
+            native_name,
+            direction,
+            glyph_count: self.get_glyphs().len(),
+            recommended_fonts: self.recommended_fonts(),
+            preview: self.preview(),
+        }
+    }
+
+    /// Fonts known to cover this character set, most preferred first
+    pub fn recommended_fonts(&self) -> &'static [&'static str] {
+        match self {
+            CharacterSet::Japanese => &["Hiragino Sans", "Osaka", "Noto Sans CJK JP"],
+            CharacterSet::Hindi => &[
+                "Kohinoor Devanagari",
+                "Devanagari Sangam MN",
+                "Noto Sans Devanagari",
+            ],
+            CharacterSet::Tamil => &["Tamil Sangam MN", "InaiMathi", "Noto Sans Tamil"],
+            CharacterSet::Sinhala => &["Sinhala Sangam MN", "Noto Sans Sinhala"],
+            CharacterSet::Korean => &["Apple SD Gothic Neo", "Noto Sans CJK KR"],
+            CharacterSet::Jawi => &["Geeza Pro", "Noto Naskh Arabic"],
+            CharacterSet::Arabic => &["Geeza Pro", "Noto Naskh Arabic", "Noto Sans Arabic"],
+            CharacterSet::Hebrew => &["Arial Hebrew", "Noto Sans Hebrew"],
+            CharacterSet::Thai => &["Thonburi", "Ayuthaya", "Noto Sans Thai"],
+            CharacterSet::Mixed => &["Arial Unicode MS", "Noto Sans Mono"],
+        }
+    }
+
+    /// Short sample string for previews
+    pub fn preview(&self) -> &'static str {
+        match self {
+            CharacterSet::Japanese => "ハミヒーウシナモニサワツオリアホテマケメ",
+            CharacterSet::Hindi => "अआइईउऊऋएऐओऔकखगघङचछजझ",
+            CharacterSet::Tamil => "அஆஇஈஉஊஎஏஐஒஓஔகஙசஞடணதந",
+            CharacterSet::Sinhala => "අආඇඈඉඊඋඌඍඎඏඐඑඒඓඔඕඖ",
+            CharacterSet::Korean => "가나다라마바사아자차카타파하",
+            CharacterSet::Jawi => "ابتثجحخدذرزسشصضطظعغفقكلمنهوي",
+            CharacterSet::Arabic => "ابتثجحخدذرزسشصضطظعغفقكلمنهوي",
+            CharacterSet::Hebrew => "אבגדהוזחטיכלמנסעפצקרשת",
+            CharacterSet::Thai => "กขคงจฉชซญดตถทนบปผพฟมยรลวสหอฮ",
+            CharacterSet::Mixed => "ハミヒअआகஙසි가나ابאבกข",
+        }
+    }
+
     /// Get a random character from this character set
     pub fn random_character(&self, rng: &mut impl rand::Rng) -> char {
         let chars = self.get_characters();
@@ -217,6 +378,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_all_sets_listed() {
+        let sets = CharacterSet::all_sets();
+        assert_eq!(sets.len(), 10);
+        assert_eq!(sets[0], CharacterSet::default());
+    }
+
+    #[test]
+    fn test_index_round_trip() {
+        for set in CharacterSet::all_sets() {
+            assert_eq!(CharacterSet::from_index(set.index()), Some(set));
+        }
+        assert_eq!(CharacterSet::from_index(9), Some(CharacterSet::Mixed));
+        assert_eq!(CharacterSet::from_index(200), None);
+    }
+
+    #[test]
+    fn test_info_ids_are_unique_and_resolvable() {
+        let sets = CharacterSet::all_sets();
+        for set in &sets {
+            let info = set.info();
+            assert_eq!(CharacterSet::from_id(info.id), Some(*set));
+            assert_eq!(sets.iter().filter(|s| s.info().id == info.id).count(), 1);
+        }
+        assert_eq!(CharacterSet::from_id("Klingon"), None);
+    }
+
+    #[test]
+    fn test_info_matches_data() {
+        for set in CharacterSet::all_sets() {
+            let info = set.info();
+            assert_eq!(info.glyph_count, set.get_glyphs().len());
+            assert!(!info.recommended_fonts.is_empty());
+            assert!(!info.preview.is_empty());
+        }
+        assert_eq!(
+            CharacterSet::Hebrew.info().direction,
+            WritingDirection::RightToLeft
+        );
+    }
+
+    #[test]
+    fn test_glyphs_include_clusters() {
+        let glyphs = CharacterSet::Hindi.get_glyphs();
+        assert_eq!(
+            glyphs.len(),
+            CharacterSet::Hindi.get_characters().len() + CharacterSet::Hindi.clusters().len()
+        );
+        assert!(glyphs.contains(&Glyph::new("क्ष").unwrap()));
+
+        // Every cluster segments as a single glyph
+        for set in CharacterSet::all_sets() {
+            for cluster in set.clusters() {
+                assert_eq!(Glyph::segment(cluster).len(), 1, "{cluster}");
+            }
+        }
+    }
+
     #[test]
     fn test_random_character() {
         let mut rng = thread_rng();
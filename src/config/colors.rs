@@ -1,7 +1,160 @@
 //! Color schemes for the Matrix rain effect
 
+use super::gradient::{Gradient, GradientStop, Interpolation};
+use super::info::ColorSchemeInfo;
+use super::leader::LEADER_TRAIL_FRACTION;
 use serde::{Deserialize, Serialize};
 
+/// Explicit colours for a custom scheme (serialized as `#rrggbb` strings)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CustomColors {
+    /// Leading (newest) character
+    #[serde(with = "hex_rgb")]
+    pub leader: (u8, u8, u8),
+    /// Characters right behind the leader
+    #[serde(with = "hex_rgb")]
+    pub primary: (u8, u8, u8),
+    /// Mid-trail characters
+    #[serde(with = "hex_rgb")]
+    pub secondary: (u8, u8, u8),
+    /// Oldest/fading characters
+    #[serde(with = "hex_rgb")]
+    pub tertiary: (u8, u8, u8),
+    /// Screen background
+    #[serde(with = "hex_rgb")]
+    pub background: (u8, u8, u8),
+}
+
+impl CustomColors {
+    /// Derive a full palette from one primary colour the way the presets do
+    /// (secondary 60%, tertiary 30%, white leader, black background)
+    pub fn from_primary(primary: (u8, u8, u8)) -> Self {
+        Self {
+            leader: (255, 255, 255),
+            primary,
+            secondary: scale_rgb(primary, 0.6),
+            tertiary: scale_rgb(primary, 0.3),
+            background: (0, 0, 0),
+        }
+    }
+
+    /// Build a palette from `#rrggbb` strings (None if any fails to parse)
+    pub fn from_hex(
+        leader: &str,
+        primary: &str,
+        secondary: &str,
+        tertiary: &str,
+        background: &str,
+    ) -> Option<Self> {
+        Some(Self {
+            leader: parse_hex_rgb(leader)?,
+            primary: parse_hex_rgb(primary)?,
+            secondary: parse_hex_rgb(secondary)?,
+            tertiary: parse_hex_rgb(tertiary)?,
+            background: parse_hex_rgb(background)?,
+        })
+    }
+}
+
+/// Parse a `#rrggbb` or `rrggbb` colour (also accepts the `#rgb` shorthand)
+pub fn parse_hex_rgb(hex: &str) -> Option<(u8, u8, u8)> {
+    let digits = hex.trim().trim_start_matches('#');
+    if !digits.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize, len: usize| u8::from_str_radix(&digits[i * len..(i + 1) * len], 16);
+    match digits.len() {
+        6 => Some((
+            channel(0, 2).ok()?,
+            channel(1, 2).ok()?,
+            channel(2, 2).ok()?,
+        )),
+        3 => {
+            let (r, g, b) = (
+                channel(0, 1).ok()?,
+                channel(1, 1).ok()?,
+                channel(2, 1).ok()?,
+            );
+            Some((r * 17, g * 17, b * 17))
+        }
+        _ => None,
+    }
+}
+
+/// Format a colour as a lowercase `#rrggbb` string
+pub fn format_hex_rgb((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Serde helpers storing RGB tuples as `#rrggbb` strings
+pub(super) mod hex_rgb {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(rgb: &(u8, u8, u8), serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::format_hex_rgb(*rgb))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<(u8, u8, u8), D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        super::parse_hex_rgb(&hex)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid hex colour: {hex}")))
+    }
+}
+
+/// Serde helpers storing optional RGB tuples as `#rrggbb` strings or null
+pub(super) mod hex_rgb_option {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        rgb: &Option<(u8, u8, u8)>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match rgb {
+            Some(rgb) => super::hex_rgb::serialize(rgb, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<(u8, u8, u8)>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(hex) => super::parse_hex_rgb(&hex)
+                .map(Some)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid hex colour: {hex}"))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Opacity along the trail (0.0 = head, 1.0 = tail)
+/// Leading characters are fully opaque, then fade smoothly to transparent
+pub fn trail_alpha(position_in_trail: f32) -> f32 {
+    if position_in_trail < 0.1 {
+        1.0
+    } else {
+        (1.0 - (position_in_trail - 0.1) / 0.9).clamp(0.0, 1.0)
+    }
+}
+
+/// Blend two colours (0.0 = `a`, 1.0 = `b`)
+pub fn mix_rgb(a: (u8, u8, u8), b: (u8, u8, u8), amount: f32) -> (u8, u8, u8) {
+    let t = amount.clamp(0.0, 1.0);
+    let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+    (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+/// Scale each channel of a colour by a factor
+fn scale_rgb((r, g, b): (u8, u8, u8), factor: f32) -> (u8, u8, u8) {
+    (
+        (r as f32 * factor) as u8,
+        (g as f32 * factor) as u8,
+        (b as f32 * factor) as u8,
+    )
+}
+
 /// Available color schemes
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum ColorScheme {
@@ -28,6 +181,8 @@ pub enum ColorScheme {
     LimeGreen,
     /// Teal
     Teal,
+    /// Explicit colours, e.g. to match brand colours exactly
+    Custom(CustomColors),
 }
 
 impl ColorScheme {
@@ -46,45 +201,71 @@ impl ColorScheme {
             ColorScheme::White => (255, 255, 255),
             ColorScheme::LimeGreen => (50, 255, 50),
             ColorScheme::Teal => (0, 200, 200),
+            ColorScheme::Custom(colors) => colors.primary,
         }
     }
 
     /// Get the secondary color RGB values (0-255)
     /// Returns (r, g, b) for mid-trail characters
     pub fn get_secondary_color(&self) -> (u8, u8, u8) {
-        let (r, g, b) = self.get_primary_color();
-        // Darken by about 40%
-        (
-            (r as f32 * 0.6) as u8,
-            (g as f32 * 0.6) as u8,
-            (b as f32 * 0.6) as u8,
-        )
+        match self {
+            ColorScheme::Custom(colors) => colors.secondary,
+            // Darken by about 40%
+            _ => scale_rgb(self.get_primary_color(), 0.6),
+        }
     }
 
     /// Get the tertiary color RGB values (0-255)
     /// Returns (r, g, b) for oldest/fading characters
     pub fn get_tertiary_color(&self) -> (u8, u8, u8) {
-        let (r, g, b) = self.get_primary_color();
-        // Darken by about 70%
-        (
-            (r as f32 * 0.3) as u8,
-            (g as f32 * 0.3) as u8,
-            (b as f32 * 0.3) as u8,
-        )
+        match self {
+            ColorScheme::Custom(colors) => colors.tertiary,
+            // Darken by about 70%
+            _ => scale_rgb(self.get_primary_color(), 0.3),
+        }
+    }
+
+    /// Get the leading character color RGB values (0-255)
+    /// White for the presets, for that classic Matrix look
+    pub fn get_leader_color(&self) -> (u8, u8, u8) {
+        match self {
+            ColorScheme::Custom(colors) => colors.leader,
+            _ => (255, 255, 255),
+        }
+    }
+
+    /// Get the background color RGB values (0-255)
+    /// Black for the presets
+    pub fn get_background_color(&self) -> (u8, u8, u8) {
+        match self {
+            ColorScheme::Custom(colors) => colors.background,
+            _ => (0, 0, 0),
+        }
+    }
+
+    /// Get the full palette of this scheme (presets expand to their derived colours)
+    pub fn custom_colors(&self) -> CustomColors {
+        CustomColors {
+            leader: self.get_leader_color(),
+            primary: self.get_primary_color(),
+            secondary: self.get_secondary_color(),
+            tertiary: self.get_tertiary_color(),
+            background: self.get_background_color(),
+        }
     }
 
     /// Get color with alpha transparency (0.0 = transparent, 1.0 = opaque)
     /// Returns (r, g, b, a) with RGB in 0-255 range and alpha in 0.0-1.0 range
     ///
     /// Classic Matrix effect:
-    /// - Leading character (position 0.0): Bright white
+    /// - Leading character (position 0.0): Leader color (bright white for presets)
     /// - Next few characters (0.0-0.15): Bright primary color
     /// - Mid trail (0.15-0.5): Medium brightness
     /// - Tail (0.5-1.0): Fading to black
     pub fn get_color_with_alpha(&self, position_in_trail: f32) -> (u8, u8, u8, f32) {
-        let (r, g, b) = if position_in_trail < 0.05 {
-            // Leading character is bright white for that classic Matrix look
-            (255, 255, 255)
+        let (r, g, b) = if position_in_trail < LEADER_TRAIL_FRACTION {
+            // Leading character in the scheme's leader colour (white for the classic look)
+            self.get_leader_color()
         } else if position_in_trail < 0.15 {
             // Very bright primary color right behind the leader
             self.get_primary_color()
@@ -96,18 +277,82 @@ impl ColorScheme {
             self.get_tertiary_color()
         };
 
-        // Alpha decreases more gradually for a longer visible trail
-        let alpha = if position_in_trail < 0.1 {
-            1.0 // Leading characters fully opaque
+        (r, g, b, trail_alpha(position_in_trail))
+    }
+
+    /// Get the trail color without the leader (0.0 = head, 1.0 = tail)
+    /// Primary near the head, then secondary and tertiary, as `get_color_with_alpha`
+    pub fn get_body_color(&self, position_in_trail: f32) -> (u8, u8, u8) {
+        if position_in_trail < 0.15 {
+            self.get_primary_color()
+        } else if position_in_trail < 0.5 {
+            self.get_secondary_color()
         } else {
-            // Smooth fade from 1.0 to 0.0
-            (1.0 - (position_in_trail - 0.1) / 0.9).clamp(0.0, 1.0)
+            self.get_tertiary_color()
+        }
+    }
+
+    /// Express this scheme's trail body as a gradient
+    /// Step interpolation at the classic thresholds reproduces `get_body_color` exactly;
+    /// switch the interpolation to smooth out the banding on long trails. Leaders are
+    /// coloured separately and are not part of the gradient.
+    pub fn to_gradient(&self) -> Gradient {
+        let stops = vec![
+            GradientStop::new(0.0, self.get_primary_color()),
+            GradientStop::new(0.15, self.get_secondary_color()),
+            GradientStop::new(0.5, self.get_tertiary_color()),
+        ];
+        Gradient::new(stops, Interpolation::Step).expect("scheme gradients have stops")
+    }
+
+    /// Get the color scheme at a stable index (the order of `all_schemes`, used by FFI)
+    pub fn from_index(index: u8) -> Option<ColorScheme> {
+        Self::all_schemes().get(index as usize).copied()
+    }
+
+    /// Get the stable index of this color scheme
+    /// Custom schemes have no preset index and report the preset count
+    pub fn index(&self) -> u8 {
+        Self::all_schemes()
+            .iter()
+            .position(|scheme| scheme == self)
+            .unwrap_or(Self::all_schemes().len()) as u8
+    }
+
+    /// Look up a color scheme by its stable string ID (e.g. "matrix-green")
+    pub fn from_id(id: &str) -> Option<ColorScheme> {
+        Self::all_schemes()
+            .into_iter()
+            .find(|scheme| scheme.info().id.eq_ignore_ascii_case(id))
+    }
+
+    /// Get display metadata for this color scheme
+    pub fn info(&self) -> ColorSchemeInfo {
+        let (id, name) = match self {
+            ColorScheme::MatrixGreen => ("matrix-green", "Matrix Green"),
+            ColorScheme::DarkBlue => ("dark-blue", "Dark Blue"),
+            ColorScheme::Purple => ("purple", "Purple"),
+            ColorScheme::Orange => ("orange", "Orange"),
+            ColorScheme::Red => ("red", "Red"),
+            ColorScheme::Cyan => ("cyan", "Cyan"),
+            ColorScheme::Yellow => ("yellow", "Yellow"),
+            ColorScheme::Pink => ("pink", "Pink"),
+            ColorScheme::White => ("white", "White"),
+            ColorScheme::LimeGreen => ("lime-green", "Lime Green"),
+            ColorScheme::Teal => ("teal", "Teal"),
+            ColorScheme::Custom(_) => ("custom", "Custom"),
         };
+        let primary = self.get_primary_color();
 
-        (r, g, b, alpha)
+        ColorSchemeInfo {
+            id,
+            name,
+            primary,
+            primary_hex: format_hex_rgb(primary),
+        }
     }
 
-    /// Get all available color schemes
+    /// Get all preset color schemes (custom schemes are not listed)
     pub fn all_schemes() -> Vec<ColorScheme> {
         vec![
             ColorScheme::MatrixGreen,
@@ -171,6 +416,81 @@ mod tests {
         assert!(a < 0.2);
     }
 
+    #[test]
+    fn test_scheme_info() {
+        let info = ColorScheme::MatrixGreen.info();
+        assert_eq!(info.id, "matrix-green");
+        assert_eq!(info.name, "Matrix Green");
+        assert_eq!(info.primary_hex, "#00ff46");
+
+        for scheme in ColorScheme::all_schemes() {
+            assert_eq!(ColorScheme::from_id(scheme.info().id), Some(scheme));
+            assert_eq!(ColorScheme::from_index(scheme.index()), Some(scheme));
+        }
+        assert_eq!(ColorScheme::from_index(11), None);
+    }
+
+    #[test]
+    fn test_presets_expand_to_custom_colors() {
+        for scheme in ColorScheme::all_schemes() {
+            let custom = ColorScheme::Custom(scheme.custom_colors());
+            for pos in [0.0, 0.1, 0.3, 0.8] {
+                assert_eq!(
+                    custom.get_color_with_alpha(pos),
+                    scheme.get_color_with_alpha(pos)
+                );
+            }
+        }
+        assert_eq!(
+            CustomColors::from_primary((0, 255, 70)),
+            ColorScheme::MatrixGreen.custom_colors()
+        );
+    }
+
+    #[test]
+    fn test_custom_colors() {
+        let colors =
+            CustomColors::from_hex("#fff", "#ff6600", "#993d00", "#4d1f00", "#101010").unwrap();
+        let scheme = ColorScheme::Custom(colors);
+
+        assert_eq!(scheme.get_leader_color(), (255, 255, 255));
+        assert_eq!(scheme.get_primary_color(), (255, 102, 0));
+        assert_eq!(scheme.get_secondary_color(), (0x99, 0x3d, 0x00));
+        assert_eq!(scheme.get_background_color(), (16, 16, 16));
+        assert_eq!(scheme.info().id, "custom");
+        assert_eq!(scheme.index() as usize, ColorScheme::all_schemes().len());
+        assert_eq!(ColorScheme::from_id("custom"), None);
+    }
+
+    #[test]
+    fn test_mix_rgb() {
+        assert_eq!(mix_rgb((0, 0, 0), (200, 100, 50), 0.5), (100, 50, 25));
+        assert_eq!(mix_rgb((0, 0, 0), (200, 100, 50), 2.0), (200, 100, 50));
+    }
+
+    #[test]
+    fn test_hex_parsing() {
+        assert_eq!(parse_hex_rgb("#00ff46"), Some((0, 255, 70)));
+        assert_eq!(parse_hex_rgb("00FF46"), Some((0, 255, 70)));
+        assert_eq!(parse_hex_rgb("#0f4"), Some((0, 255, 68)));
+        assert_eq!(parse_hex_rgb("#00ff4"), None);
+        assert_eq!(parse_hex_rgb("#gg0000"), None);
+        assert_eq!(parse_hex_rgb("#ffé"), None);
+        assert_eq!(format_hex_rgb((0, 255, 70)), "#00ff46");
+    }
+
+    #[test]
+    fn test_custom_serializes_as_hex() {
+        let scheme = ColorScheme::Custom(CustomColors::from_primary((255, 102, 0)));
+        let json = serde_json::to_string(&scheme).unwrap();
+        assert!(json.contains("\"primary\":\"#ff6600\""));
+        let back: ColorScheme = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, scheme);
+
+        let bad = json.replace("#ff6600", "orange");
+        assert!(serde_json::from_str::<ColorScheme>(&bad).is_err());
+    }
+
     #[test]
     fn test_default_color_scheme() {
         assert_eq!(ColorScheme::default(), ColorScheme::MatrixGreen);
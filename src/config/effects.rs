@@ -0,0 +1,155 @@
+//! Post-processing effects applied to rendered frames
+
+use serde::{Deserialize, Serialize};
+
+/// Bloom: bright pixels bleed light into their surroundings, giving heads a glow
+///
+/// Applied by framebuffer renderers after the glyphs are drawn; hosts that draw
+/// glow themselves can ignore it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BloomConfig {
+    /// Apply bloom at all
+    pub enabled: bool,
+    /// Linear brightness (0.0-1.0) above which pixels start to glow
+    pub threshold: f32,
+    /// Strength of the added glow (0.0 = none, 1.0 = as bright as the source)
+    pub intensity: f32,
+    /// Blur radius of the finest scale in pixels; each further scale doubles it
+    pub radius: f32,
+    /// Number of blur scales combined (1-6); more gives a wider, softer falloff
+    pub scales: u32,
+}
+
+impl Default for BloomConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 0.6,
+            intensity: 0.8,
+            radius: 8.0,
+            scales: 3,
+        }
+    }
+}
+
+impl BloomConfig {
+    /// Bloom with the default look, switched on
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            ..Self::default()
+        }
+    }
+
+    /// Check whether applying bloom would change a frame
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.intensity > 0.0 && self.scales > 0
+    }
+}
+
+/// Retro CRT look: phosphor trails, scanlines and a curved, slightly misconverged tube
+///
+/// Each effect is off at zero and can be used on its own. Like bloom, they are
+/// applied by framebuffer renderers when a frame is presented.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrtConfig {
+    /// Fraction of the previous frame's brightness kept each frame (0.0-0.99), leaving
+    /// trails behind moving glyphs; being per frame, trails last longer at lower fps
+    pub persistence: f32,
+    /// How far scanline gaps are darkened (0.0 = none, 1.0 = black)
+    pub scanlines: f32,
+    /// Distance between scanlines in pixels
+    pub scanline_spacing: f32,
+    /// Barrel distortion (0.0 = flat, 0.1 = slight bulge, corners fall off the tube)
+    pub curvature: f32,
+    /// Red/blue fringe offset at the corners in pixels
+    pub chromatic_aberration: f32,
+}
+
+impl Default for CrtConfig {
+    fn default() -> Self {
+        Self {
+            persistence: 0.0,
+            scanlines: 0.0,
+            scanline_spacing: 3.0,
+            curvature: 0.0,
+            chromatic_aberration: 0.0,
+        }
+    }
+}
+
+impl CrtConfig {
+    /// All four effects at a subtle strength
+    pub fn retro() -> Self {
+        Self {
+            persistence: 0.75,
+            scanlines: 0.35,
+            scanline_spacing: 3.0,
+            curvature: 0.08,
+            chromatic_aberration: 1.5,
+        }
+    }
+
+    /// Check whether any effect would change a frame
+    pub fn is_active(&self) -> bool {
+        self.has_persistence() || self.has_scanlines() || self.distorts()
+    }
+
+    /// Check whether frames leave decaying trails
+    pub fn has_persistence(&self) -> bool {
+        self.persistence > 0.0
+    }
+
+    /// Check whether scanlines are drawn
+    pub fn has_scanlines(&self) -> bool {
+        self.scanlines > 0.0 && self.scanline_spacing >= 1.0
+    }
+
+    /// Check whether pixels are moved (curvature or chromatic aberration)
+    pub fn distorts(&self) -> bool {
+        self.curvature != 0.0 || self.chromatic_aberration != 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_off() {
+        assert!(!BloomConfig::default().is_active());
+        assert!(BloomConfig::enabled().is_active());
+        assert!(!BloomConfig {
+            intensity: 0.0,
+            ..BloomConfig::enabled()
+        }
+        .is_active());
+    }
+
+    #[test]
+    fn test_json_defaults() {
+        let bloom: BloomConfig = serde_json::from_str(r#"{"enabled": true, "radius": 4}"#).unwrap();
+        assert_eq!(bloom.radius, 4.0);
+        assert_eq!(bloom.threshold, BloomConfig::default().threshold);
+        assert!(bloom.is_active());
+    }
+
+    #[test]
+    fn test_crt_effects_are_independent() {
+        assert!(!CrtConfig::default().is_active());
+        assert!(CrtConfig::retro().is_active());
+
+        let scanlines = CrtConfig {
+            scanlines: 0.5,
+            ..CrtConfig::default()
+        };
+        assert!(scanlines.is_active() && scanlines.has_scanlines());
+        assert!(!scanlines.has_persistence() && !scanlines.distorts());
+
+        let fringe: CrtConfig = serde_json::from_str(r#"{"chromatic_aberration": 2}"#).unwrap();
+        assert!(fringe.distorts() && !fringe.has_scanlines());
+        assert_eq!(fringe.scanline_spacing, 3.0);
+    }
+}
@@ -0,0 +1,267 @@
+//! Multi-stop gradient palettes mapping trail position to colour
+
+use super::colors::{hex_rgb, mix_rgb};
+use crate::rendering::{linear_to_srgb, srgb_to_linear};
+use serde::{Deserialize, Serialize};
+
+/// How colours are blended between gradient stops
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum Interpolation {
+    /// Straight blend of the sRGB channel values
+    Linear,
+    /// Perceptually even blend in the OKLab colour space
+    #[default]
+    OkLab,
+    /// No blending: each stop's colour holds until the next stop
+    Step,
+}
+
+/// A colour at a position along the trail (0.0 = head, 1.0 = tail)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GradientStop {
+    /// Position along the trail (0.0-1.0)
+    pub position: f32,
+    /// Colour at this position
+    #[serde(with = "hex_rgb")]
+    pub color: (u8, u8, u8),
+}
+
+impl GradientStop {
+    /// Create a stop (position is clamped to 0.0-1.0)
+    pub fn new(position: f32, color: (u8, u8, u8)) -> Self {
+        Self {
+            position: position.clamp(0.0, 1.0),
+            color,
+        }
+    }
+}
+
+/// A palette with any number of colour stops along the trail
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "GradientData")]
+pub struct Gradient {
+    /// Colour stops, sorted by position
+    stops: Vec<GradientStop>,
+    /// How to blend between stops
+    #[serde(default)]
+    pub interpolation: Interpolation,
+}
+
+/// Unvalidated gradient as read from JSON
+#[derive(Deserialize)]
+struct GradientData {
+    stops: Vec<GradientStop>,
+    #[serde(default)]
+    interpolation: Interpolation,
+}
+
+impl TryFrom<GradientData> for Gradient {
+    type Error = &'static str;
+
+    fn try_from(data: GradientData) -> Result<Self, Self::Error> {
+        Gradient::new(data.stops, data.interpolation).ok_or("gradient needs at least one stop")
+    }
+}
+
+impl Gradient {
+    /// Create a gradient from stops in any order (None if there are no stops)
+    pub fn new(stops: Vec<GradientStop>, interpolation: Interpolation) -> Option<Self> {
+        if stops.is_empty() {
+            return None;
+        }
+        let mut stops: Vec<GradientStop> = stops
+            .into_iter()
+            .map(|stop| GradientStop::new(stop.position, stop.color))
+            .collect();
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        Some(Self {
+            stops,
+            interpolation,
+        })
+    }
+
+    /// Create a gradient with colours spread evenly from head to tail
+    pub fn evenly_spaced(colors: &[(u8, u8, u8)], interpolation: Interpolation) -> Option<Self> {
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        let stops = colors
+            .iter()
+            .enumerate()
+            .map(|(i, &color)| GradientStop::new(i as f32 / last, color))
+            .collect();
+        Self::new(stops, interpolation)
+    }
+
+    /// The colour stops, sorted by position
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops
+    }
+
+    /// Set the interpolation mode
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Get the colour at a trail position (0.0 = head, 1.0 = tail)
+    pub fn color_at(&self, position: f32) -> (u8, u8, u8) {
+        let position = position.clamp(0.0, 1.0);
+
+        // Index of the first stop past the position
+        let next = self.stops.partition_point(|stop| stop.position <= position);
+        if next == 0 {
+            return self.stops[0].color;
+        }
+        let from = self.stops[next - 1];
+        let Some(&to) = self.stops.get(next) else {
+            return from.color;
+        };
+
+        let span = to.position - from.position;
+        let t = if span > 0.0 {
+            (position - from.position) / span
+        } else {
+            0.0
+        };
+
+        match self.interpolation {
+            Interpolation::Step => from.color,
+            Interpolation::Linear => mix_rgb(from.color, to.color, t),
+            Interpolation::OkLab => {
+                let (a, b) = (to_oklab(from.color), to_oklab(to.color));
+                from_oklab([
+                    a[0] + (b[0] - a[0]) * t,
+                    a[1] + (b[1] - a[1]) * t,
+                    a[2] + (b[2] - a[2]) * t,
+                ])
+            }
+        }
+    }
+}
+
+/// Convert an sRGB colour to OKLab (L, a, b)
+fn to_oklab((r, g, b): (u8, u8, u8)) -> [f32; 3] {
+    let (r, g, b) = (srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b));
+
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+/// Convert an OKLab colour back to sRGB (out-of-gamut values are clipped)
+fn from_oklab([lightness, a, b]: [f32; 3]) -> (u8, u8, u8) {
+    let l = (lightness + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+    let m = (lightness - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+    let s = (lightness - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+
+    (
+        linear_to_srgb(4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s),
+        linear_to_srgb(-1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s),
+        linear_to_srgb(-0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ColorScheme;
+
+    #[test]
+    fn test_requires_stops() {
+        assert!(Gradient::new(Vec::new(), Interpolation::Linear).is_none());
+        assert!(Gradient::evenly_spaced(&[], Interpolation::Linear).is_none());
+    }
+
+    #[test]
+    fn test_stops_are_sorted_and_clamped() {
+        let gradient = Gradient::new(
+            vec![
+                GradientStop::new(1.5, (0, 0, 0)),
+                GradientStop::new(-1.0, (255, 255, 255)),
+            ],
+            Interpolation::Linear,
+        )
+        .unwrap();
+        let positions: Vec<f32> = gradient.stops().iter().map(|s| s.position).collect();
+        assert_eq!(positions, vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_linear_interpolation() {
+        let gradient =
+            Gradient::evenly_spaced(&[(0, 0, 0), (200, 100, 50)], Interpolation::Linear).unwrap();
+        assert_eq!(gradient.color_at(0.0), (0, 0, 0));
+        assert_eq!(gradient.color_at(0.5), (100, 50, 25));
+        assert_eq!(gradient.color_at(1.0), (200, 100, 50));
+        assert_eq!(gradient.color_at(2.0), (200, 100, 50));
+    }
+
+    #[test]
+    fn test_step_interpolation() {
+        let gradient =
+            Gradient::evenly_spaced(&[(255, 0, 0), (0, 255, 0)], Interpolation::Step).unwrap();
+        assert_eq!(gradient.color_at(0.99), (255, 0, 0));
+        assert_eq!(gradient.color_at(1.0), (0, 255, 0));
+    }
+
+    #[test]
+    fn test_oklab_round_trip_and_midpoint() {
+        for color in [(0, 0, 0), (255, 255, 255), (0, 255, 70), (200, 100, 255)] {
+            assert_eq!(from_oklab(to_oklab(color)), color);
+        }
+
+        // The perceptual midpoint of black and white is mid-grey (OKLab L = 0.5)
+        let gradient =
+            Gradient::evenly_spaced(&[(0, 0, 0), (255, 255, 255)], Interpolation::OkLab).unwrap();
+        let (r, g, b) = gradient.color_at(0.5);
+        assert_eq!((r, r), (g, b));
+        assert!(r > 90 && r < 140);
+    }
+
+    #[test]
+    fn test_single_stop() {
+        let gradient = Gradient::evenly_spaced(&[(1, 2, 3)], Interpolation::OkLab).unwrap();
+        assert_eq!(gradient.color_at(0.0), (1, 2, 3));
+        assert_eq!(gradient.color_at(0.7), (1, 2, 3));
+    }
+
+    #[test]
+    fn test_schemes_reproduce_classic_look() {
+        for scheme in ColorScheme::all_schemes() {
+            let gradient = scheme.to_gradient();
+            for i in 0..=100 {
+                let pos = i as f32 / 100.0;
+                assert_eq!(
+                    gradient.color_at(pos),
+                    scheme.get_body_color(pos),
+                    "{scheme:?} at {pos}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_serialization() {
+        let gradient =
+            Gradient::evenly_spaced(&[(0, 255, 70), (0, 40, 10)], Interpolation::Linear).unwrap();
+        let json = serde_json::to_string(&gradient).unwrap();
+        assert!(json.contains("#00ff46"));
+        let back: Gradient = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, gradient);
+
+        // Interpolation defaults to OKLab and stops are sorted on load
+        let back: Gradient = serde_json::from_str(
+            r##"{"stops":[{"position":1.0,"color":"#000000"},{"position":0.0,"color":"#ffffff"}]}"##,
+        )
+        .unwrap();
+        assert_eq!(back.interpolation, Interpolation::OkLab);
+        assert_eq!(back.color_at(0.0), (255, 255, 255));
+
+        assert!(serde_json::from_str::<Gradient>(r#"{"stops":[]}"#).is_err());
+    }
+}
@@ -0,0 +1,679 @@
+//! Palette importers: GIMP `.gpl`, base16 YAML schemes and PPM/PNG images
+//!
+//! Every importer produces an `ImportedPalette`, whose custom colours can be
+//! used as a `ColorScheme` or saved as a theme.
+
+use super::accessibility::{contrast_ratio, relative_luminance};
+use super::{mix_rgb, parse_hex_rgb, ColorScheme, CustomColors, Theme};
+use std::fmt;
+use std::path::Path;
+
+/// Number of dominant colours extracted from images
+pub const IMAGE_PALETTE_SIZE: usize = 8;
+
+/// Most pixels sampled when extracting colours from an image
+const MAX_SAMPLED_PIXELS: usize = 1 << 16;
+
+/// Errors that can occur while importing a palette
+#[derive(Debug)]
+pub enum PaletteImportError {
+    /// The file could not be read
+    Io(std::io::Error),
+    /// The data is not in the expected format
+    Malformed(String),
+    /// The file format is not supported (or its feature is disabled)
+    UnsupportedFormat(String),
+    /// The palette or image has no usable colours
+    NoColors,
+    /// The PNG could not be decoded
+    #[cfg(feature = "png")]
+    Png(png::DecodingError),
+}
+
+impl fmt::Display for PaletteImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteImportError::Io(err) => write!(f, "failed to read palette: {}", err),
+            PaletteImportError::Malformed(reason) => write!(f, "malformed palette: {}", reason),
+            PaletteImportError::UnsupportedFormat(format) => {
+                write!(f, "unsupported palette format: {}", format)
+            }
+            PaletteImportError::NoColors => write!(f, "palette has no colours"),
+            #[cfg(feature = "png")]
+            PaletteImportError::Png(err) => write!(f, "failed to decode PNG: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PaletteImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PaletteImportError::Io(err) => Some(err),
+            #[cfg(feature = "png")]
+            PaletteImportError::Png(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PaletteImportError {
+    fn from(err: std::io::Error) -> Self {
+        PaletteImportError::Io(err)
+    }
+}
+
+#[cfg(feature = "png")]
+impl From<png::DecodingError> for PaletteImportError {
+    fn from(err: png::DecodingError) -> Self {
+        PaletteImportError::Png(err)
+    }
+}
+
+fn malformed(reason: impl Into<String>) -> PaletteImportError {
+    PaletteImportError::Malformed(reason.into())
+}
+
+/// A palette read from a file, mapped onto the rain's colour roles
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedPalette {
+    /// Palette name from the file (empty if it has none)
+    pub name: String,
+    /// Palette author from the file (empty if it has none)
+    pub author: String,
+    /// The source colours, most important first
+    pub swatches: Vec<(u8, u8, u8)>,
+    /// Colours assigned to the rain's roles
+    pub colors: CustomColors,
+}
+
+impl ImportedPalette {
+    /// Parse a GIMP palette (`.gpl`)
+    pub fn from_gpl(text: &str) -> Result<Self, PaletteImportError> {
+        let mut lines = text.lines();
+        if lines.next().map(str::trim) != Some("GIMP Palette") {
+            return Err(malformed("missing \"GIMP Palette\" header"));
+        }
+
+        let mut name = String::new();
+        let mut swatches = Vec::new();
+        for line in lines {
+            let line = line.trim();
+            if let Some(value) = line.strip_prefix("Name:") {
+                name = value.trim().to_string();
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+                continue;
+            }
+
+            let mut channels = line.split_whitespace().map(str::parse::<u8>);
+            match (channels.next(), channels.next(), channels.next()) {
+                (Some(Ok(r)), Some(Ok(g)), Some(Ok(b))) => swatches.push((r, g, b)),
+                _ => return Err(malformed(format!("bad colour line {:?}", line))),
+            }
+        }
+
+        Self::from_swatches(name, String::new(), swatches)
+    }
+
+    /// Parse a base16 scheme (YAML with `base00`-`base0F` hex colours)
+    ///
+    /// The background is `base00`, the leader `base07` and the trail `base0B`
+    /// (the scheme's green accent), fading towards the background.
+    pub fn from_base16(text: &str) -> Result<Self, PaletteImportError> {
+        let mut name = String::new();
+        let mut author = String::new();
+        let mut base = [None; 16];
+
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = yaml_scalar(value);
+
+            match key.trim() {
+                "scheme" | "name" => name = value.to_string(),
+                "author" => author = value.to_string(),
+                key => {
+                    let Some(index) = key
+                        .strip_prefix("base")
+                        .filter(|digits| digits.len() == 2)
+                        .and_then(|digits| usize::from_str_radix(digits, 16).ok())
+                    else {
+                        continue;
+                    };
+                    // Values may be written with or without a leading '#'
+                    let hex = format!("#{}", value.trim_start_matches('#'));
+                    base[index] = Some(
+                        parse_hex_rgb(&hex)
+                            .ok_or_else(|| malformed(format!("bad colour for {}", key)))?,
+                    );
+                }
+            }
+        }
+
+        let (Some(background), Some(leader), Some(primary)) = (base[0x00], base[0x07], base[0x0B])
+        else {
+            return Err(malformed("base00, base07 and base0B are required"));
+        };
+        Ok(Self {
+            name,
+            author,
+            swatches: base.iter().flatten().copied().collect(),
+            colors: trail_colors(leader, primary, background),
+        })
+    }
+
+    /// Extract the dominant colours of a PPM image (binary `P6` or ASCII `P3`)
+    pub fn from_ppm(data: &[u8]) -> Result<Self, PaletteImportError> {
+        let pixels = decode_ppm(data)?;
+        Self::from_pixels(&pixels)
+    }
+
+    /// Extract the dominant colours of a PNG image
+    #[cfg(feature = "png")]
+    pub fn from_png(data: &[u8]) -> Result<Self, PaletteImportError> {
+        let pixels = decode_png(data)?;
+        Self::from_pixels(&pixels)
+    }
+
+    /// Extract the dominant colours from pixels with median cut
+    pub fn from_pixels(pixels: &[(u8, u8, u8)]) -> Result<Self, PaletteImportError> {
+        let swatches = dominant_colors(pixels, IMAGE_PALETTE_SIZE);
+        Self::from_swatches(String::new(), String::new(), swatches)
+    }
+
+    /// Load a palette file, choosing the importer by extension
+    /// (`.gpl`, `.yaml`/`.yml`, `.ppm`/`.pnm` and, with the `png` feature, `.png`)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PaletteImportError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        let mut palette = match extension.as_str() {
+            "gpl" => Self::from_gpl(&std::fs::read_to_string(path)?)?,
+            "yaml" | "yml" => Self::from_base16(&std::fs::read_to_string(path)?)?,
+            "ppm" | "pnm" => Self::from_ppm(&std::fs::read(path)?)?,
+            #[cfg(feature = "png")]
+            "png" => Self::from_png(&std::fs::read(path)?)?,
+            _ => return Err(PaletteImportError::UnsupportedFormat(extension)),
+        };
+
+        // Images and unnamed palettes are named after the file
+        if palette.name.is_empty() {
+            if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                palette.name = stem.to_string();
+            }
+        }
+        Ok(palette)
+    }
+
+    /// Map arbitrary colours onto the rain's roles
+    ///
+    /// The darkest colour becomes the background and the lightest the leader.
+    /// The trail uses the most vivid remaining colour that stands out against
+    /// the background, fading towards the background.
+    fn from_swatches(
+        name: String,
+        author: String,
+        swatches: Vec<(u8, u8, u8)>,
+    ) -> Result<Self, PaletteImportError> {
+        let luminance = |rgb: &&(u8, u8, u8)| relative_luminance(**rgb);
+        let darkest = *swatches
+            .iter()
+            .min_by(|a, b| luminance(a).total_cmp(&luminance(b)))
+            .ok_or(PaletteImportError::NoColors)?;
+        let lightest = *swatches
+            .iter()
+            .max_by(|a, b| luminance(a).total_cmp(&luminance(b)))
+            .ok_or(PaletteImportError::NoColors)?;
+
+        // A single colour (or only dark colours) keeps the classic black background
+        let background = if contrast_ratio(lightest, darkest) < 3.0 {
+            (0, 0, 0)
+        } else {
+            darkest
+        };
+
+        let vividness = |rgb: (u8, u8, u8)| {
+            let (max, min) = (rgb.0.max(rgb.1).max(rgb.2), rgb.0.min(rgb.1).min(rgb.2));
+            let saturation = if max == 0 {
+                0.0
+            } else {
+                (max - min) as f32 / max as f32
+            };
+            // Greys still count a little so monochrome palettes pick their brightest
+            (saturation + 0.1) * contrast_ratio(rgb, background)
+        };
+        let primary = swatches
+            .iter()
+            .copied()
+            .filter(|&rgb| rgb != background)
+            .max_by(|&a, &b| vividness(a).total_cmp(&vividness(b)))
+            .unwrap_or(lightest);
+        let leader = if lightest == primary {
+            (255, 255, 255)
+        } else {
+            lightest
+        };
+
+        Ok(Self {
+            name,
+            author,
+            colors: trail_colors(leader, primary, background),
+            swatches,
+        })
+    }
+
+    /// The palette as a custom colour scheme
+    pub fn scheme(&self) -> ColorScheme {
+        ColorScheme::Custom(self.colors)
+    }
+
+    /// A theme using the palette, ready to save to a theme library
+    /// Everything but the colours comes from the default configuration.
+    pub fn to_theme(&self) -> Theme {
+        let name = if self.name.is_empty() {
+            "Imported"
+        } else {
+            &self.name
+        };
+        let config = super::ScreenSaverConfig {
+            color_scheme: self.scheme(),
+            ..Default::default()
+        };
+        Theme::from_config(name, &config).with_author(self.author.clone())
+    }
+}
+
+/// The value of a simple YAML `key: value` line, without quotes or a trailing comment
+fn yaml_scalar(value: &str) -> &str {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(rest) = value.strip_prefix(quote) {
+            return rest.split(quote).next().unwrap_or_default();
+        }
+    }
+    value.split(" #").next().unwrap_or_default().trim()
+}
+
+/// Trail colours fading from `primary` towards the background
+fn trail_colors(
+    leader: (u8, u8, u8),
+    primary: (u8, u8, u8),
+    background: (u8, u8, u8),
+) -> CustomColors {
+    CustomColors {
+        leader,
+        primary,
+        secondary: mix_rgb(primary, background, 0.35),
+        tertiary: mix_rgb(primary, background, 0.6),
+        background,
+    }
+}
+
+/// Read the RGB pixels of a binary (`P6`) or ASCII (`P3`) PPM image
+fn decode_ppm(data: &[u8]) -> Result<Vec<(u8, u8, u8)>, PaletteImportError> {
+    let mut pos = 0;
+    // Next whitespace-separated header token, skipping `#` comments
+    let mut token = || -> Option<&[u8]> {
+        loop {
+            while data.get(pos)?.is_ascii_whitespace() {
+                pos += 1;
+            }
+            if data[pos] == b'#' {
+                while *data.get(pos)? != b'\n' {
+                    pos += 1;
+                }
+                continue;
+            }
+            let start = pos;
+            while data.get(pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+                pos += 1;
+            }
+            return Some(&data[start..pos]);
+        }
+    };
+
+    let magic = token().ok_or_else(|| malformed("empty file"))?;
+    let binary = match magic {
+        b"P6" => true,
+        b"P3" => false,
+        _ => return Err(malformed("not a P3/P6 PPM image")),
+    };
+    let mut number = || -> Result<usize, PaletteImportError> {
+        token()
+            .and_then(|t| std::str::from_utf8(t).ok())
+            .and_then(|t| t.parse().ok())
+            .ok_or_else(|| malformed("bad PPM header"))
+    };
+    let (width, height, max_value) = (number()?, number()?, number()?);
+    if max_value == 0 || max_value > 65535 {
+        return Err(malformed("bad PPM maximum value"));
+    }
+    let sample_count = width
+        .checked_mul(height)
+        .and_then(|count| count.checked_mul(3))
+        .ok_or_else(|| malformed("PPM image too large"))?;
+    // Samples are checked against max_value before scaling, so this cannot overflow
+    let scale = |v: usize| {
+        if v > max_value {
+            return Err(malformed("PPM sample above the maximum value"));
+        }
+        Ok((v * 255 / max_value) as u8)
+    };
+
+    if binary {
+        // A single whitespace byte separates the header from the pixels
+        let sample_bytes = if max_value < 256 { 1 } else { 2 };
+        let body = sample_count
+            .checked_mul(sample_bytes)
+            .and_then(|len| data.get(pos + 1..)?.get(..len))
+            .ok_or_else(|| malformed("PPM pixel data is truncated"))?;
+        body.chunks_exact(3 * sample_bytes)
+            .map(|pixel| {
+                let sample = |i: usize| match sample_bytes {
+                    1 => scale(pixel[i] as usize),
+                    _ => scale(u16::from_be_bytes([pixel[2 * i], pixel[2 * i + 1]]) as usize),
+                };
+                Ok((sample(0)?, sample(1)?, sample(2)?))
+            })
+            .collect()
+    } else {
+        let mut sample = || -> Result<u8, PaletteImportError> {
+            let value = token()
+                .ok_or_else(|| malformed("PPM pixel data is truncated"))
+                .and_then(|t| {
+                    std::str::from_utf8(t)
+                        .ok()
+                        .and_then(|t| t.parse().ok())
+                        .ok_or_else(|| malformed("bad PPM sample"))
+                })?;
+            scale(value)
+        };
+        (0..sample_count / 3)
+            .map(|_| Ok((sample()?, sample()?, sample()?)))
+            .collect()
+    }
+}
+
+/// Read the RGB pixels of a PNG image (mostly transparent pixels are skipped)
+#[cfg(feature = "png")]
+fn decode_png(data: &[u8]) -> Result<Vec<(u8, u8, u8)>, PaletteImportError> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer)?;
+    let bytes = &buffer[..frame.buffer_size()];
+
+    let pixels = match frame.color_type {
+        png::ColorType::Rgb => bytes.chunks_exact(3).map(|p| (p[0], p[1], p[2])).collect(),
+        png::ColorType::Rgba => bytes
+            .chunks_exact(4)
+            .filter(|p| p[3] >= 128)
+            .map(|p| (p[0], p[1], p[2]))
+            .collect(),
+        png::ColorType::Grayscale => bytes.iter().map(|&v| (v, v, v)).collect(),
+        png::ColorType::GrayscaleAlpha => bytes
+            .chunks_exact(2)
+            .filter(|p| p[1] >= 128)
+            .map(|p| (p[0], p[0], p[0]))
+            .collect(),
+        png::ColorType::Indexed => {
+            return Err(PaletteImportError::UnsupportedFormat(
+                "unexpanded indexed PNG".to_string(),
+            ))
+        }
+    };
+    Ok(pixels)
+}
+
+/// The `count` dominant colours of an image by median cut, most common first
+pub fn dominant_colors(pixels: &[(u8, u8, u8)], count: usize) -> Vec<(u8, u8, u8)> {
+    if pixels.is_empty() || count == 0 {
+        return Vec::new();
+    }
+    let step = pixels.len().div_ceil(MAX_SAMPLED_PIXELS);
+    let sampled: Vec<[u8; 3]> = pixels
+        .iter()
+        .step_by(step)
+        .map(|&(r, g, b)| [r, g, b])
+        .collect();
+
+    let mut boxes = vec![sampled];
+    while boxes.len() < count {
+        // Split the box with the widest channel range at its median
+        let Some((index, channel)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, pixels)| pixels.len() > 1)
+            .map(|(index, pixels)| {
+                let (channel, range) = widest_channel(pixels);
+                (index, channel, range)
+            })
+            .filter(|&(_, _, range)| range > 0)
+            .max_by_key(|&(_, _, range)| range)
+            .map(|(index, channel, _)| (index, channel))
+        else {
+            break;
+        };
+
+        let mut pixels = boxes.swap_remove(index);
+        pixels.sort_unstable_by_key(|pixel| pixel[channel]);
+        // Cut where the channel value changes nearest the median, so equal colours stay together
+        let median = pixels[pixels.len() / 2][channel];
+        let below = pixels.partition_point(|pixel| pixel[channel] < median);
+        let through = pixels.partition_point(|pixel| pixel[channel] <= median);
+        let mid = pixels.len() / 2;
+        let cut = if below > 0 && (through == pixels.len() || mid - below <= through - mid) {
+            below
+        } else {
+            through
+        };
+        let upper = pixels.split_off(cut);
+        boxes.push(pixels);
+        boxes.push(upper);
+    }
+
+    boxes.sort_by_key(|pixels| std::cmp::Reverse(pixels.len()));
+    boxes.iter().map(|pixels| average(pixels)).collect()
+}
+
+/// The channel with the largest spread and its range
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let (min, max) = pixels.iter().fold((u8::MAX, u8::MIN), |(min, max), p| {
+                (min.min(p[channel]), max.max(p[channel]))
+            });
+            (channel, max - min)
+        })
+        .max_by_key(|&(_, range)| range)
+        .unwrap_or((0, 0))
+}
+
+fn average(pixels: &[[u8; 3]]) -> (u8, u8, u8) {
+    let mut sum = [0u64; 3];
+    for pixel in pixels {
+        for channel in 0..3 {
+            sum[channel] += pixel[channel] as u64;
+        }
+    }
+    let n = pixels.len().max(1) as u64;
+    let mean = |total: u64| ((total + n / 2) / n) as u8;
+    (mean(sum[0]), mean(sum[1]), mean(sum[2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GPL: &str = "GIMP Palette
+Name: Phosphor
+Columns: 4
+# A comment
+  0   0   0\tBlack
+ 20 200  60\tGreen
+ 40  80  50\tDull green
+230 255 230\tHighlight
+";
+
+    const BASE16: &str = r##"scheme: "Gruvbox dark"
+author: "Dawid Kurek"
+base00: "282828" # background
+base01: "3c3836"
+base07: "fbf1c7"
+base08: "fb4934"
+base0B: "#b8bb26"
+"##;
+
+    #[test]
+    fn test_gpl() {
+        let palette = ImportedPalette::from_gpl(GPL).unwrap();
+        assert_eq!(palette.name, "Phosphor");
+        assert_eq!(palette.swatches.len(), 4);
+        assert_eq!(palette.colors.background, (0, 0, 0));
+        assert_eq!(palette.colors.leader, (230, 255, 230));
+        assert_eq!(palette.colors.primary, (20, 200, 60));
+        assert_eq!(
+            palette.colors.secondary,
+            mix_rgb((20, 200, 60), (0, 0, 0), 0.35)
+        );
+
+        assert!(ImportedPalette::from_gpl("Not a palette").is_err());
+        assert!(ImportedPalette::from_gpl("GIMP Palette\n1 2\n").is_err());
+        assert!(matches!(
+            ImportedPalette::from_gpl("GIMP Palette\nName: Empty\n"),
+            Err(PaletteImportError::NoColors)
+        ));
+    }
+
+    #[test]
+    fn test_base16() {
+        let palette = ImportedPalette::from_base16(BASE16).unwrap();
+        assert_eq!(palette.name, "Gruvbox dark");
+        assert_eq!(palette.author, "Dawid Kurek");
+        assert_eq!(palette.colors.background, (0x28, 0x28, 0x28));
+        assert_eq!(palette.colors.leader, (0xfb, 0xf1, 0xc7));
+        assert_eq!(palette.colors.primary, (0xb8, 0xbb, 0x26));
+        assert_eq!(palette.swatches.len(), 5);
+
+        assert!(ImportedPalette::from_base16("base00: \"000000\"").is_err());
+        assert!(ImportedPalette::from_base16("base00: \"zz\"").is_err());
+    }
+
+    #[test]
+    fn test_ppm() {
+        // 3x1 image: two green pixels and one black one, in both encodings
+        let ascii = b"P3\n# comment\n3 1\n255\n0 200 70  0 200 70  0 0 0\n";
+        let mut binary = b"P6 3 1 255\n".to_vec();
+        binary.extend_from_slice(&[0, 200, 70, 0, 200, 70, 0, 0, 0]);
+
+        for data in [&ascii[..], &binary[..]] {
+            let palette = ImportedPalette::from_ppm(data).unwrap();
+            assert_eq!(palette.swatches, vec![(0, 200, 70), (0, 0, 0)]);
+            assert_eq!(palette.colors.primary, (0, 200, 70));
+            assert_eq!(palette.colors.background, (0, 0, 0));
+        }
+
+        // 16-bit samples are scaled down
+        let mut wide = b"P6 1 1 65535\n".to_vec();
+        wide.extend_from_slice(&[0xFF, 0xFF, 0x80, 0x00, 0x00, 0x00]);
+        assert_eq!(decode_ppm(&wide).unwrap(), vec![(255, 127, 0)]);
+
+        assert!(ImportedPalette::from_ppm(b"P6 2 2 255\n\0\0\0").is_err());
+        // Oversized headers and out-of-range samples are errors, not overflows
+        assert!(ImportedPalette::from_ppm(b"P6 2147483648 4294967296 255\n\0\0\0").is_err());
+        assert!(ImportedPalette::from_ppm(b"P3 1 1 255 9223372036854775807 0 0").is_err());
+        assert!(ImportedPalette::from_ppm(b"P3 1 1 100 101 0 0").is_err());
+        assert!(ImportedPalette::from_ppm(b"P6 1 1 100\n\x65\0\0").is_err());
+        // Only the image's own samples are read; trailing bytes are ignored
+        assert_eq!(
+            decode_ppm(b"P6 1 1 255\n\x10\x20\x30\xFF\xFF").unwrap(),
+            vec![(16, 32, 48)]
+        );
+        assert!(ImportedPalette::from_ppm(b"P5 1 1 255\n\0").is_err());
+    }
+
+    #[test]
+    fn test_dominant_colors() {
+        let mut pixels = vec![(250, 10, 10); 60];
+        pixels.extend(vec![(10, 10, 250); 30]);
+        pixels.extend(vec![(10, 250, 10); 10]);
+
+        let colors = dominant_colors(&pixels, 3);
+        assert_eq!(colors, vec![(250, 10, 10), (10, 10, 250), (10, 250, 10)]);
+
+        // Never more colours than there are distinct pixels
+        assert_eq!(dominant_colors(&[(1, 2, 3); 5], 8), vec![(1, 2, 3)]);
+        assert!(dominant_colors(&[], 8).is_empty());
+    }
+
+    #[test]
+    fn test_single_colour_keeps_black_background() {
+        let palette = ImportedPalette::from_pixels(&[(0, 255, 70); 4]).unwrap();
+        assert_eq!(palette.colors.background, (0, 0, 0));
+        assert_eq!(palette.colors.primary, (0, 255, 70));
+        assert_eq!(palette.colors.leader, (255, 255, 255));
+    }
+
+    #[test]
+    fn test_to_theme() {
+        let palette = ImportedPalette::from_base16(BASE16).unwrap();
+        let theme = palette.to_theme();
+        assert_eq!(theme.name, "Gruvbox dark");
+        assert_eq!(theme.author, "Dawid Kurek");
+        assert_eq!(theme.color_scheme, palette.scheme());
+        // Round-trips through JSON as a custom scheme
+        assert_eq!(Theme::from_json(&theme.to_json().unwrap()).unwrap(), theme);
+    }
+
+    #[test]
+    fn test_load_by_extension() {
+        let dir = std::env::temp_dir().join(format!("matrix-rain-import-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let gpl = dir.join("phosphor.gpl");
+        std::fs::write(&gpl, GPL).unwrap();
+        assert_eq!(ImportedPalette::load(&gpl).unwrap().name, "Phosphor");
+
+        let ppm = dir.join("wallpaper.ppm");
+        std::fs::write(&ppm, b"P3 1 1 255 90 10 200").unwrap();
+        assert_eq!(ImportedPalette::load(&ppm).unwrap().name, "wallpaper");
+
+        assert!(matches!(
+            ImportedPalette::load(dir.join("palette.ase")),
+            Err(PaletteImportError::UnsupportedFormat(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_png() {
+        let mut data = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut data, 2, 2);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer
+                .write_image_data(&[
+                    255, 0, 180, 255, //
+                    255, 0, 180, 255, //
+                    10, 0, 20, 255, //
+                    255, 255, 255, 0, // Transparent, ignored
+                ])
+                .unwrap();
+        }
+
+        let palette = ImportedPalette::from_png(&data).unwrap();
+        assert_eq!(palette.swatches, vec![(255, 0, 180), (10, 0, 20)]);
+        assert_eq!(palette.colors.background, (10, 0, 20));
+        assert_eq!(palette.colors.primary, (255, 0, 180));
+    }
+}
@@ -0,0 +1,93 @@
+//! Display metadata for character sets, color schemes and speeds
+//!
+//! Hosts build their menus from these instead of keeping their own name lists.
+
+use serde::Serialize;
+
+/// Direction a script is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum WritingDirection {
+    /// Left-to-right scripts (Latin, Devanagari, Kana...)
+    LeftToRight,
+    /// Right-to-left scripts (Arabic, Hebrew...)
+    RightToLeft,
+    /// A mix of both directions
+    Mixed,
+}
+
+impl WritingDirection {
+    /// Stable numeric code used over FFI (0 = LTR, 1 = RTL, 2 = mixed)
+    pub fn code(&self) -> u8 {
+        match self {
+            WritingDirection::LeftToRight => 0,
+            WritingDirection::RightToLeft => 1,
+            WritingDirection::Mixed => 2,
+        }
+    }
+}
+
+/// Metadata describing a character set
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CharacterSetInfo {
+    /// Stable identifier (lowercase, never localized)
+    pub id: &'static str,
+    /// Name in English
+    pub english_name: &'static str,
+    /// Name in the script itself
+    pub native_name: &'static str,
+    /// Writing direction of the script
+    pub direction: WritingDirection,
+    /// Number of glyphs in the set
+    pub glyph_count: usize,
+    /// Fonts known to cover the set, most preferred first
+    pub recommended_fonts: &'static [&'static str],
+    /// Short sample string for previews
+    pub preview: &'static str,
+}
+
+/// Metadata describing a color scheme
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ColorSchemeInfo {
+    /// Stable identifier (lowercase, never localized)
+    pub id: &'static str,
+    /// Display name in English
+    pub name: &'static str,
+    /// Primary color as RGB
+    pub primary: (u8, u8, u8),
+    /// Primary color as a `#rrggbb` string
+    pub primary_hex: String,
+}
+
+/// Metadata describing a speed setting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RainSpeedInfo {
+    /// Stable identifier (lowercase, never localized)
+    pub id: &'static str,
+    /// Display name in English
+    pub name: &'static str,
+    /// Update interval in milliseconds
+    pub update_interval_ms: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_direction_codes() {
+        assert_eq!(WritingDirection::LeftToRight.code(), 0);
+        assert_eq!(WritingDirection::RightToLeft.code(), 1);
+        assert_eq!(WritingDirection::Mixed.code(), 2);
+    }
+
+    #[test]
+    fn test_info_serializes() {
+        let info = RainSpeedInfo {
+            id: "medium",
+            name: "Medium",
+            update_interval_ms: 50,
+        };
+        let json = serde_json::to_string(&info).unwrap();
+        assert!(json.contains("\"id\":\"medium\""));
+    }
+}
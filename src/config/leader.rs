@@ -0,0 +1,104 @@
+//! Leader (head glyph) styling
+
+use super::colors::hex_rgb_option;
+use serde::{Deserialize, Serialize};
+
+/// Part of the trail, from the head, drawn in the leader colour by default
+pub(crate) const LEADER_TRAIL_FRACTION: f32 = 0.05;
+
+/// How the head of each foreground trail is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LeaderStyle {
+    /// Draw a bright leader (false: the head uses the trail colours, like the background layer)
+    pub enabled: bool,
+    /// Leader colour (None uses the scheme's leader colour, white for presets)
+    #[serde(with = "hex_rgb_option")]
+    pub color: Option<(u8, u8, u8)>,
+    /// Number of glyphs at the head drawn in the leader colour (None keeps the
+    /// classic rule: the first 5% of the trail, so long trails get two)
+    pub head_glyphs: Option<usize>,
+    /// Glow/halo intensity for leader glyphs (0.0 = none, 1.0 = strong), applied by renderers
+    pub glow: f32,
+    /// Leader brightness relative to SDR white on extended-range displays
+    /// (1.0 = no boost; SDR output is unchanged whatever the value)
+    pub brightness: f32,
+}
+
+impl Default for LeaderStyle {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            color: None,
+            head_glyphs: None,
+            glow: 0.0,
+            brightness: 1.0,
+        }
+    }
+}
+
+impl LeaderStyle {
+    /// No leader: heads take the trail colours
+    pub fn none() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    /// Check whether the glyph at `index` from the head, `trail_pos` along its
+    /// trail (0.0 = head, 1.0 = tail), is a leader glyph
+    pub fn is_leader(&self, index: usize, trail_pos: f32) -> bool {
+        self.enabled
+            && match self.head_glyphs {
+                Some(count) => index < count,
+                None => trail_pos < LEADER_TRAIL_FRACTION,
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_leader() {
+        let leader = LeaderStyle::default();
+        assert!(leader.is_leader(0, 0.0));
+        // Long trails keep a second bright glyph, short ones have just the head
+        assert!(leader.is_leader(1, 1.0 / 29.0));
+        assert!(!leader.is_leader(2, 2.0 / 29.0));
+        assert!(!leader.is_leader(1, 1.0 / 15.0));
+        assert_eq!(leader.glow, 0.0);
+        assert_eq!(leader.brightness, 1.0);
+    }
+
+    #[test]
+    fn test_head_glyphs_and_disabled() {
+        let leader = LeaderStyle {
+            head_glyphs: Some(3),
+            ..Default::default()
+        };
+        assert!(leader.is_leader(2, 0.5));
+        assert!(!leader.is_leader(3, 0.01));
+        assert!(!LeaderStyle::none().is_leader(0, 0.0));
+    }
+
+    #[test]
+    fn test_serialization() {
+        let leader = LeaderStyle {
+            color: Some((255, 240, 200)),
+            glow: 0.6,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&leader).unwrap();
+        assert!(json.contains("#fff0c8"));
+        assert_eq!(serde_json::from_str::<LeaderStyle>(&json).unwrap(), leader);
+
+        let partial: LeaderStyle = serde_json::from_str(r#"{"head_glyphs":2}"#).unwrap();
+        assert!(partial.enabled);
+        assert_eq!(partial.head_glyphs, Some(2));
+        assert_eq!(partial.color, None);
+        assert!(serde_json::from_str::<LeaderStyle>(r#"{"color":"white"}"#).is_err());
+    }
+}
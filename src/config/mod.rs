@@ -2,15 +2,41 @@
 //!
 //! Provides character sets, color schemes, speed settings, and overall configuration.
 
+mod accessibility;
+mod animation;
+mod background;
 mod character_sets;
 mod colors;
+mod effects;
+mod gradient;
+mod import;
+mod info;
+mod leader;
+mod sequence;
 mod speed;
+mod theme;
+mod transforms;
 
+pub use accessibility::{
+    contrast_ratio, relative_luminance, AccessiblePalette, ColorVisionDeficiency, ContrastReport,
+};
+pub use animation::{rotate_hue, PaletteAnimation};
+pub use background::{BackgroundConfig, BackgroundFill};
 pub use character_sets::CharacterSet;
-pub use colors::ColorScheme;
+pub use colors::{format_hex_rgb, mix_rgb, parse_hex_rgb, trail_alpha, ColorScheme, CustomColors};
+pub use effects::{BloomConfig, CrtConfig};
+pub use gradient::{Gradient, GradientStop, Interpolation};
+pub use import::{dominant_colors, ImportedPalette, PaletteImportError, IMAGE_PALETTE_SIZE};
+pub use info::{CharacterSetInfo, ColorSchemeInfo, RainSpeedInfo, WritingDirection};
+pub use leader::LeaderStyle;
+pub use sequence::{SequenceConfig, SequenceSource};
 pub use speed::RainSpeed;
+pub use theme::{Theme, ThemeEffects, ThemeEntry, ThemeError, ThemeLibrary, THEME_EXTENSION};
+pub use transforms::GlyphTransformConfig;
 
+use crate::rendering::{BlendMode, Glyph};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Main configuration for the Matrix Rain screensaver
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -27,6 +53,54 @@ pub struct ScreenSaverConfig {
     pub screen_height: u32,
     /// Enable background rain layer for depth effect
     pub enable_background_layer: bool,
+    /// How often idle columns start a new trail (1.0 = normal, 0.0 lets the rain die out)
+    #[serde(default = "default_density")]
+    pub density: f32,
+    /// Mirrored/rotated glyph probabilities
+    #[serde(default)]
+    pub glyph_transforms: GlyphTransformConfig,
+    /// Font file whose coverage limits the glyph pool (glyphs it lacks are never spawned)
+    #[serde(default)]
+    pub coverage_font: Option<PathBuf>,
+    /// Face index of `coverage_font` inside a .ttc collection
+    #[serde(default)]
+    pub coverage_font_index: u32,
+    /// Word/sequence mode: columns spell entries instead of random glyphs
+    #[serde(default)]
+    pub sequence: Option<SequenceConfig>,
+    /// Custom glyphs (characters or grapheme clusters such as emoji or flags)
+    /// drawn instead of the character set when non-empty
+    #[serde(default)]
+    pub custom_glyphs: Option<Vec<Glyph>>,
+    /// Gradient mapping trail position to colour, overriding the scheme's
+    /// stepped colours (the background still comes from the scheme)
+    #[serde(default)]
+    pub gradient: Option<Gradient>,
+    /// Hue cycling, breathing and per-column hue offsets
+    #[serde(default)]
+    pub palette_animation: PaletteAnimation,
+    /// Styling of the head glyphs of foreground trails
+    #[serde(default)]
+    pub leader: LeaderStyle,
+    /// What fills the screen behind the rain
+    #[serde(default)]
+    pub background: BackgroundConfig,
+    /// How glyphs combine with the backdrop and with overlapping glyphs
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+    /// Bake blending into opaque glyph colours for hosts that can't blend
+    #[serde(default)]
+    pub precomposite: bool,
+    /// Glow around bright glyphs, added by framebuffer renderers
+    #[serde(default)]
+    pub bloom: BloomConfig,
+    /// Phosphor trails, scanlines and tube distortion, added by framebuffer renderers
+    #[serde(default)]
+    pub crt: CrtConfig,
+}
+
+fn default_density() -> f32 {
+    1.0
 }
 
 impl Default for ScreenSaverConfig {
@@ -38,6 +112,20 @@ impl Default for ScreenSaverConfig {
             screen_width: 1920,
             screen_height: 1080,
             enable_background_layer: true,
+            density: 1.0,
+            glyph_transforms: GlyphTransformConfig::default(),
+            coverage_font: None,
+            coverage_font_index: 0,
+            sequence: None,
+            custom_glyphs: None,
+            gradient: None,
+            palette_animation: PaletteAnimation::default(),
+            leader: LeaderStyle::default(),
+            background: BackgroundConfig::default(),
+            blend_mode: BlendMode::default(),
+            precomposite: false,
+            bloom: BloomConfig::default(),
+            crt: CrtConfig::default(),
         }
     }
 }
@@ -57,7 +145,7 @@ impl ScreenSaverConfig {
             speed,
             screen_width,
             screen_height,
-            enable_background_layer: true,
+            ..Self::default()
         }
     }
 
@@ -77,6 +165,7 @@ impl ScreenSaverConfig {
             screen_width,
             screen_height,
             enable_background_layer,
+            ..Self::default()
         }
     }
 
@@ -124,4 +213,22 @@ mod tests {
         assert_eq!(config.color_scheme, ColorScheme::DarkBlue);
         assert_eq!(config.speed, RainSpeed::Fast);
     }
+
+    #[test]
+    fn test_config_without_transforms_field() {
+        // Configs saved before glyph transforms existed should still load
+        let json = r#"{
+            "character_set": "Japanese",
+            "color_scheme": "MatrixGreen",
+            "speed": "Medium",
+            "screen_width": 800,
+            "screen_height": 600,
+            "enable_background_layer": false
+        }"#;
+        let config = ScreenSaverConfig::from_json(json).unwrap();
+        assert_eq!(config.glyph_transforms, GlyphTransformConfig::default());
+        assert_eq!(config.coverage_font, None);
+        assert_eq!(config.coverage_font_index, 0);
+        assert_eq!(config.sequence, None);
+    }
 }
@@ -0,0 +1,111 @@
+//! Word/sequence mode settings (columns spell readable text)
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Where sequence mode takes its text from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SequenceSource {
+    /// A list of words or names, one entry each
+    Words(Vec<String>),
+    /// Free text, one entry per non-empty line
+    Text(String),
+    /// A text file such as source code, one entry per non-empty line
+    File(PathBuf),
+}
+
+impl SequenceSource {
+    /// Load the entries columns spell out
+    /// Blank entries are dropped and tabs/control characters become spaces
+    pub fn entries(&self) -> std::io::Result<Vec<String>> {
+        let raw: Vec<String> = match self {
+            SequenceSource::Words(words) => words.clone(),
+            SequenceSource::Text(text) => text.lines().map(str::to_string).collect(),
+            SequenceSource::File(path) => std::fs::read_to_string(path)?
+                .lines()
+                .map(str::to_string)
+                .collect(),
+        };
+
+        Ok(raw
+            .iter()
+            .map(|entry| {
+                entry
+                    .trim()
+                    .chars()
+                    .map(|ch| if ch.is_control() { ' ' } else { ch })
+                    .collect::<String>()
+            })
+            .filter(|entry| !entry.is_empty())
+            .collect())
+    }
+}
+
+/// Settings for word/sequence mode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SequenceConfig {
+    /// Text the columns spell
+    pub source: SequenceSource,
+    /// Chance per update that a glyph already in a trail is swapped for a
+    /// random one (0.0 keeps the text fully readable)
+    #[serde(default)]
+    pub mutation_rate: f32,
+}
+
+impl SequenceConfig {
+    /// Spell the given words with no mutation
+    pub fn words<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            source: SequenceSource::Words(words.into_iter().map(Into::into).collect()),
+            mutation_rate: 0.0,
+        }
+    }
+
+    /// Set the mutation rate
+    pub fn with_mutation_rate(mut self, mutation_rate: f32) -> Self {
+        self.mutation_rate = mutation_rate;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_entries() {
+        let config = SequenceConfig::words(["NEO", "  TRINITY ", ""]);
+        assert_eq!(
+            config.source.entries().unwrap(),
+            vec!["NEO".to_string(), "TRINITY".to_string()]
+        );
+        assert_eq!(config.mutation_rate, 0.0);
+    }
+
+    #[test]
+    fn test_text_entries_split_lines() {
+        let source = SequenceSource::Text("fn main() {\n\n\tprintln!(\"hi\");\n}".to_string());
+        assert_eq!(
+            source.entries().unwrap(),
+            vec!["fn main() {", "println!(\"hi\");", "}"]
+        );
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let source = SequenceSource::File("/nonexistent/words.txt".into());
+        assert!(source.entries().is_err());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let config = SequenceConfig::words(["MORPHEUS"]).with_mutation_rate(0.1);
+        let json = serde_json::to_string(&config).unwrap();
+        let back: SequenceConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(config, back);
+    }
+}
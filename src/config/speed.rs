@@ -1,5 +1,6 @@
 //! Rain speed settings
 
+use super::info::RainSpeedInfo;
 use serde::{Deserialize, Serialize};
 
 /// Available speed settings for the rain animation
@@ -55,6 +56,43 @@ impl RainSpeed {
         }
     }
 
+    /// Get the speed at a stable index (the order of `all_speeds`, used by FFI)
+    pub fn from_index(index: u8) -> Option<RainSpeed> {
+        Self::all_speeds().get(index as usize).copied()
+    }
+
+    /// Get the stable index of this speed
+    pub fn index(&self) -> u8 {
+        Self::all_speeds()
+            .iter()
+            .position(|speed| speed == self)
+            .unwrap_or(0) as u8
+    }
+
+    /// Look up a speed by its stable string ID (e.g. "very-fast")
+    pub fn from_id(id: &str) -> Option<RainSpeed> {
+        Self::all_speeds()
+            .into_iter()
+            .find(|speed| speed.info().id.eq_ignore_ascii_case(id))
+    }
+
+    /// Get display metadata for this speed
+    pub fn info(&self) -> RainSpeedInfo {
+        let (id, name) = match self {
+            RainSpeed::VerySlow => ("very-slow", "Very Slow"),
+            RainSpeed::Slow => ("slow", "Slow"),
+            RainSpeed::Medium => ("medium", "Medium"),
+            RainSpeed::Fast => ("fast", "Fast"),
+            RainSpeed::VeryFast => ("very-fast", "Very Fast"),
+        };
+
+        RainSpeedInfo {
+            id,
+            name,
+            update_interval_ms: self.update_interval_ms(),
+        }
+    }
+
     /// Get all available speed settings
     pub fn all_speeds() -> Vec<RainSpeed> {
         vec![
@@ -122,6 +160,17 @@ mod tests {
         assert_eq!(speeds.len(), 5);
     }
 
+    #[test]
+    fn test_speed_info() {
+        assert_eq!(RainSpeed::VeryFast.info().id, "very-fast");
+        assert_eq!(RainSpeed::Medium.info().update_interval_ms, 50);
+        for speed in RainSpeed::all_speeds() {
+            assert_eq!(RainSpeed::from_id(speed.info().id), Some(speed));
+            assert_eq!(RainSpeed::from_index(speed.index()), Some(speed));
+        }
+        assert_eq!(RainSpeed::from_index(2), Some(RainSpeed::Medium));
+    }
+
     #[test]
     fn test_default_speed() {
         assert_eq!(RainSpeed::default(), RainSpeed::Medium);
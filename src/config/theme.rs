@@ -0,0 +1,421 @@
+//! Shareable theme files bundling a complete look, and a directory of themes
+
+use super::{
+    BackgroundConfig, BloomConfig, CharacterSet, ColorScheme, CrtConfig, GlyphTransformConfig,
+    Gradient, LeaderStyle, PaletteAnimation, RainSpeed, ScreenSaverConfig,
+};
+use crate::rendering::{BlendMode, Glyph};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// File extension of theme files
+pub const THEME_EXTENSION: &str = "json";
+
+/// Errors that can occur while loading or saving themes
+#[derive(Debug)]
+pub enum ThemeError {
+    /// A theme file or directory could not be read or written
+    Io(std::io::Error),
+    /// A theme file is not valid theme JSON
+    Parse(serde_json::Error),
+    /// No theme with the requested name exists in the library
+    NotFound(String),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(err) => write!(f, "failed to access theme: {}", err),
+            ThemeError::Parse(err) => write!(f, "invalid theme file: {}", err),
+            ThemeError::NotFound(name) => write!(f, "no theme named {:?}", name),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ThemeError::Io(err) => Some(err),
+            ThemeError::Parse(err) => Some(err),
+            ThemeError::NotFound(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ThemeError {
+    fn from(err: std::io::Error) -> Self {
+        ThemeError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ThemeError {
+    fn from(err: serde_json::Error) -> Self {
+        ThemeError::Parse(err)
+    }
+}
+
+/// Effects that are part of a theme's look
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ThemeEffects {
+    /// Mirrored/rotated glyph probabilities
+    pub glyph_transforms: GlyphTransformConfig,
+    /// Hue cycling and breathing
+    pub palette_animation: PaletteAnimation,
+    /// Head glyph styling
+    pub leader: LeaderStyle,
+    /// How glyphs blend with the backdrop and each other
+    pub blend_mode: BlendMode,
+    /// Glow around bright glyphs
+    pub bloom: BloomConfig,
+    /// Retro CRT output: phosphor trails, scanlines, curvature, colour fringes
+    pub crt: CrtConfig,
+}
+
+/// A complete, shareable look: glyphs, palette, motion, layers and effects
+///
+/// Screen size, font coverage and sequence text are machine or session
+/// specific, so they are not part of a theme.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    /// Display name, also used to look the theme up in a library
+    pub name: String,
+    /// Who made the theme
+    #[serde(default)]
+    pub author: String,
+    /// Version of the theme itself (free-form, e.g. "1.2")
+    #[serde(default = "default_version")]
+    pub version: String,
+    /// Short description
+    #[serde(default)]
+    pub description: String,
+    /// Character set used when there are no custom glyphs
+    #[serde(default)]
+    pub character_set: CharacterSet,
+    /// Custom glyphs drawn instead of the character set
+    #[serde(default)]
+    pub custom_glyphs: Option<Vec<Glyph>>,
+    /// Colour scheme (presets by name or custom colours)
+    #[serde(default)]
+    pub color_scheme: ColorScheme,
+    /// Gradient overriding the scheme's trail colours
+    #[serde(default)]
+    pub gradient: Option<Gradient>,
+    /// What fills the screen behind the rain
+    #[serde(default)]
+    pub background: BackgroundConfig,
+    /// Rain speed
+    #[serde(default)]
+    pub speed: RainSpeed,
+    /// How often idle columns start a new trail (1.0 = normal)
+    #[serde(default = "default_density")]
+    pub density: f32,
+    /// Draw the dim background rain layer
+    #[serde(default = "default_background_layer")]
+    pub background_layer: bool,
+    /// Glyph transforms, palette animation, leader, blending, bloom and CRT
+    #[serde(default)]
+    pub effects: ThemeEffects,
+}
+
+fn default_version() -> String {
+    "1.0".to_string()
+}
+
+fn default_density() -> f32 {
+    1.0
+}
+
+fn default_background_layer() -> bool {
+    true
+}
+
+impl Theme {
+    /// Capture the look of a configuration as a named theme
+    pub fn from_config(name: impl Into<String>, config: &ScreenSaverConfig) -> Self {
+        Self {
+            name: name.into(),
+            author: String::new(),
+            version: default_version(),
+            description: String::new(),
+            character_set: config.character_set,
+            custom_glyphs: config.custom_glyphs.clone(),
+            color_scheme: config.color_scheme,
+            gradient: config.gradient.clone(),
+            background: config.background,
+            speed: config.speed,
+            density: config.density,
+            background_layer: config.enable_background_layer,
+            effects: ThemeEffects {
+                glyph_transforms: config.glyph_transforms,
+                palette_animation: config.palette_animation,
+                leader: config.leader,
+                blend_mode: config.blend_mode,
+                bloom: config.bloom,
+                crt: config.crt,
+            },
+        }
+    }
+
+    /// Set the author
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = author.into();
+        self
+    }
+
+    /// Apply the theme to a configuration, keeping its screen size, font and sequence
+    pub fn apply(&self, config: &mut ScreenSaverConfig) {
+        config.character_set = self.character_set;
+        config.custom_glyphs = self.custom_glyphs.clone();
+        config.color_scheme = self.color_scheme;
+        config.gradient = self.gradient.clone();
+        config.background = self.background;
+        config.speed = self.speed;
+        config.density = self.density;
+        config.enable_background_layer = self.background_layer;
+        config.glyph_transforms = self.effects.glyph_transforms;
+        config.palette_animation = self.effects.palette_animation;
+        config.leader = self.effects.leader;
+        config.blend_mode = self.effects.blend_mode;
+        config.bloom = self.effects.bloom;
+        config.crt = self.effects.crt;
+    }
+
+    /// A copy of a configuration with the theme applied
+    pub fn applied_to(&self, config: &ScreenSaverConfig) -> ScreenSaverConfig {
+        let mut config = config.clone();
+        self.apply(&mut config);
+        config
+    }
+
+    /// Load a theme from a JSON string
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Save the theme to a JSON string
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Load a theme file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ThemeError> {
+        Ok(Self::from_json(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Write the theme to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ThemeError> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// File name the theme is stored under in a library ("Neon Nights" -> "neon-nights.json")
+    pub fn file_name(&self) -> String {
+        let mut slug = String::new();
+        for ch in self.name.trim().chars() {
+            if ch.is_alphanumeric() {
+                slug.extend(ch.to_lowercase());
+            } else if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        let slug = slug.trim_end_matches('-');
+        let slug = if slug.is_empty() { "theme" } else { slug };
+        format!("{}.{}", slug, THEME_EXTENSION)
+    }
+}
+
+/// A theme found in a library
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeEntry {
+    /// Theme file
+    pub path: PathBuf,
+    /// The theme's contents
+    pub theme: Theme,
+}
+
+/// A directory of theme files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThemeLibrary {
+    dir: PathBuf,
+}
+
+impl ThemeLibrary {
+    /// Use a directory of themes (it is created on the first save)
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The themes directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// All readable themes, sorted by name
+    /// Files that aren't valid themes are skipped; a missing directory is empty.
+    pub fn list(&self) -> Result<Vec<ThemeEntry>, ThemeError> {
+        let read_dir = match std::fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut entries = Vec::new();
+        for entry in read_dir {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(THEME_EXTENSION) {
+                continue;
+            }
+            if let Ok(theme) = Theme::load(&path) {
+                entries.push(ThemeEntry { path, theme });
+            }
+        }
+        entries.sort_by_key(|entry| entry.theme.name.to_lowercase());
+        Ok(entries)
+    }
+
+    /// Load a theme by name (case-insensitive) or by file name without extension
+    pub fn load(&self, name: &str) -> Result<Theme, ThemeError> {
+        self.list()?
+            .into_iter()
+            .find(|entry| {
+                entry.theme.name.eq_ignore_ascii_case(name)
+                    || entry.path.file_stem().and_then(|stem| stem.to_str()) == Some(name)
+            })
+            .map(|entry| entry.theme)
+            .ok_or_else(|| ThemeError::NotFound(name.to_string()))
+    }
+
+    /// Save a theme into the library, replacing any theme with the same file name
+    /// Returns the path written.
+    pub fn save(&self, theme: &Theme) -> Result<PathBuf, ThemeError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(theme.file_name());
+        theme.save(&path)?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BackgroundFill, CustomColors};
+
+    /// A fresh, empty directory under the system temp dir
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "matrix-rain-themes-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn neon_theme() -> Theme {
+        let config = ScreenSaverConfig {
+            character_set: CharacterSet::Hebrew,
+            color_scheme: ColorScheme::Custom(CustomColors::from_primary((255, 0, 200))),
+            speed: RainSpeed::Fast,
+            density: 1.5,
+            enable_background_layer: false,
+            background: BackgroundConfig::solid((10, 0, 20)),
+            blend_mode: BlendMode::Additive,
+            bloom: BloomConfig::enabled(),
+            crt: CrtConfig::retro(),
+            ..Default::default()
+        };
+        Theme::from_config("Neon Nights", &config).with_author("Trinity")
+    }
+
+    #[test]
+    fn test_apply_round_trip() {
+        let theme = neon_theme();
+        let base = ScreenSaverConfig {
+            screen_width: 800,
+            screen_height: 600,
+            ..Default::default()
+        };
+        let config = theme.applied_to(&base);
+
+        // The look comes from the theme; the screen stays
+        assert_eq!(config.character_set, CharacterSet::Hebrew);
+        assert_eq!(config.density, 1.5);
+        assert!(!config.enable_background_layer);
+        assert_eq!(config.blend_mode, BlendMode::Additive);
+        assert!(config.bloom.is_active());
+        assert_eq!(config.crt, CrtConfig::retro());
+        assert_eq!((config.screen_width, config.screen_height), (800, 600));
+        assert_eq!(
+            Theme::from_config("Neon Nights", &config).with_author("Trinity"),
+            theme
+        );
+    }
+
+    #[test]
+    fn test_json_defaults() {
+        let theme = Theme::from_json(r#"{"name":"Minimal"}"#).unwrap();
+        assert_eq!(theme.version, "1.0");
+        assert_eq!(theme.density, 1.0);
+        assert!(theme.background_layer);
+        assert_eq!(theme.color_scheme, ColorScheme::MatrixGreen);
+        assert_eq!(theme.background.fill, BackgroundFill::Scheme);
+
+        let json = neon_theme().to_json().unwrap();
+        assert!(json.contains("\"author\": \"Trinity\""));
+        assert_eq!(Theme::from_json(&json).unwrap(), neon_theme());
+
+        assert!(Theme::from_json(r#"{"author":"no name"}"#).is_err());
+    }
+
+    #[test]
+    fn test_file_name() {
+        let mut theme = neon_theme();
+        assert_eq!(theme.file_name(), "neon-nights.json");
+        theme.name = "  Café -- Blue!! ".to_string();
+        assert_eq!(theme.file_name(), "café-blue.json");
+        theme.name = "../..".to_string();
+        assert_eq!(theme.file_name(), "theme.json");
+    }
+
+    #[test]
+    fn test_library() {
+        let dir = scratch_dir("library");
+        let library = ThemeLibrary::new(&dir);
+        assert!(library.list().unwrap().is_empty());
+        assert!(matches!(
+            library.load("Neon Nights"),
+            Err(ThemeError::NotFound(_))
+        ));
+
+        let path = library.save(&neon_theme()).unwrap();
+        assert_eq!(path, dir.join("neon-nights.json"));
+        library
+            .save(&Theme::from_config("Amber", &ScreenSaverConfig::default()))
+            .unwrap();
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a theme").unwrap();
+
+        let names: Vec<String> = library
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.theme.name)
+            .collect();
+        assert_eq!(names, vec!["Amber", "Neon Nights"]);
+
+        assert_eq!(library.load("neon nights").unwrap(), neon_theme());
+        assert_eq!(library.load("neon-nights").unwrap(), neon_theme());
+        assert!(matches!(
+            Theme::load(dir.join("broken.json")),
+            Err(ThemeError::Parse(_))
+        ));
+        assert!(matches!(
+            Theme::load(dir.join("missing.json")),
+            Err(ThemeError::Io(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,105 @@
+//! Glyph transform settings (mirrored and rotated characters)
+
+use crate::rendering::GlyphTransform;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Probabilities for applying each transform to a newly spawned glyph
+///
+/// Each value is an independent chance in the range 0.0-1.0, so a glyph can
+/// end up both mirrored and rotated. All zero (the default) disables transforms.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct GlyphTransformConfig {
+    /// Chance of mirroring a glyph horizontally
+    pub mirror_x: f32,
+    /// Chance of mirroring a glyph vertically
+    pub mirror_y: f32,
+    /// Chance of rotating a glyph by 90 degrees
+    pub rotate_90: f32,
+    /// Chance of rotating a glyph by 180 degrees
+    pub rotate_180: f32,
+}
+
+impl GlyphTransformConfig {
+    /// Classic film look: roughly half of the glyphs are mirrored horizontally
+    pub fn classic() -> Self {
+        Self {
+            mirror_x: 0.5,
+            ..Self::default()
+        }
+    }
+
+    /// Check whether any transform can be produced
+    pub fn is_enabled(&self) -> bool {
+        self.mirror_x > 0.0 || self.mirror_y > 0.0 || self.rotate_90 > 0.0 || self.rotate_180 > 0.0
+    }
+
+    /// Pick a random transform according to the configured probabilities
+    pub fn sample(&self, rng: &mut impl Rng) -> GlyphTransform {
+        let mut transform = GlyphTransform::NONE;
+        if !self.is_enabled() {
+            return transform;
+        }
+
+        for (flag, chance) in [
+            (GlyphTransform::MIRROR_X, self.mirror_x),
+            (GlyphTransform::MIRROR_Y, self.mirror_y),
+            (GlyphTransform::ROTATE_90, self.rotate_90),
+            (GlyphTransform::ROTATE_180, self.rotate_180),
+        ] {
+            if chance > 0.0 && rng.gen_bool(chance.clamp(0.0, 1.0) as f64) {
+                transform |= flag;
+            }
+        }
+        transform
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_default_disabled() {
+        let config = GlyphTransformConfig::default();
+        let mut rng = thread_rng();
+        assert!(!config.is_enabled());
+        for _ in 0..100 {
+            assert!(config.sample(&mut rng).is_none());
+        }
+    }
+
+    #[test]
+    fn test_always_mirror() {
+        let config = GlyphTransformConfig {
+            mirror_x: 1.0,
+            ..Default::default()
+        };
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            assert_eq!(config.sample(&mut rng), GlyphTransform::MIRROR_X);
+        }
+    }
+
+    #[test]
+    fn test_classic_mixes_mirrored_and_plain() {
+        let config = GlyphTransformConfig::classic();
+        let mut rng = thread_rng();
+        let mirrored = (0..1000)
+            .filter(|_| config.sample(&mut rng).contains(GlyphTransform::MIRROR_X))
+            .count();
+        assert!((300..=700).contains(&mirrored));
+    }
+
+    #[test]
+    fn test_out_of_range_probability_is_clamped() {
+        let config = GlyphTransformConfig {
+            rotate_180: 5.0,
+            ..Default::default()
+        };
+        let mut rng = thread_rng();
+        assert_eq!(config.sample(&mut rng), GlyphTransform::ROTATE_180);
+    }
+}
@@ -1,6 +1,8 @@
 //! Individual rain column implementation
 
-use crate::config::CharacterSet;
+use super::{GlyphSource, Sequence, SequenceCursor};
+use crate::config::GlyphTransformConfig;
+use crate::rendering::{Glyph, GlyphTransform};
 use rand::Rng;
 
 /// A single column of falling characters
@@ -11,13 +13,19 @@ pub struct RainColumn {
     /// Current Y position of the head of the rain (in character units)
     pub y: f32,
     /// The trail of characters in this column
-    pub characters: Vec<char>,
+    pub characters: Vec<Glyph>,
+    /// Transform for each character in the trail (same length as `characters`)
+    pub transforms: Vec<GlyphTransform>,
+    /// Probabilities used to pick transforms for new characters
+    pub glyph_transforms: GlyphTransformConfig,
     /// Speed multiplier for this specific column
     pub speed: f32,
     /// Maximum length of the trail
     pub max_length: usize,
     /// Whether this column is currently active
     pub active: bool,
+    /// Position in the sequence entries when spelling words (None in random mode)
+    pub cursor: Option<SequenceCursor>,
 }
 
 impl RainColumn {
@@ -33,14 +41,23 @@ impl RainColumn {
             x,
             y,
             characters: Vec::with_capacity(max_length),
+            transforms: Vec::with_capacity(max_length),
+            glyph_transforms: GlyphTransformConfig::default(),
             speed,
             max_length: rng.gen_range(max_length / 2..=max_length),
             active: true,
+            cursor: None,
         }
     }
 
+    /// Set the transform probabilities for characters spawned by this column
+    pub fn with_glyph_transforms(mut self, glyph_transforms: GlyphTransformConfig) -> Self {
+        self.glyph_transforms = glyph_transforms;
+        self
+    }
+
     /// Update the column's position
-    pub fn update(&mut self, char_set: &CharacterSet, rng: &mut impl Rng) {
+    pub fn update(&mut self, glyphs: &impl GlyphSource, rng: &mut impl Rng) {
         if !self.active {
             return;
         }
@@ -50,13 +67,53 @@ impl RainColumn {
 
         // Add new characters to the trail
         if self.characters.len() < self.max_length && rng.gen_bool(0.8) {
-            self.characters.push(char_set.random_character(rng));
+            self.characters.push(glyphs.random_glyph(rng));
+            self.transforms.push(self.glyph_transforms.sample(rng));
         }
 
         // Occasionally change a character in the trail for the "glitch" effect
         if !self.characters.is_empty() && rng.gen_bool(0.05) {
             let idx = rng.gen_range(0..self.characters.len());
-            self.characters[idx] = char_set.random_character(rng);
+            self.characters[idx] = glyphs.random_glyph(rng);
+            self.transforms[idx] = self.glyph_transforms.sample(rng);
+        }
+    }
+
+    /// Update the column in sequence mode
+    ///
+    /// The head writes the next glyph of its entry each time it enters a new
+    /// row, so the newest glyph sits at the head and entries read top-to-bottom.
+    /// Glyphs only change afterwards if the sequence allows mutation.
+    pub fn update_sequence(
+        &mut self,
+        sequence: &Sequence,
+        glyphs: &impl GlyphSource,
+        rng: &mut impl Rng,
+    ) {
+        if !self.active {
+            return;
+        }
+
+        let previous_row = self.y.floor();
+        self.y += self.speed;
+        let new_rows = (self.y.floor() - previous_row).max(0.0) as usize;
+
+        let cursor = self
+            .cursor
+            .get_or_insert_with(|| sequence.start_cursor(rng));
+        for _ in 0..new_rows {
+            self.characters.insert(0, sequence.next_glyph(cursor, rng));
+            self.transforms.insert(0, self.glyph_transforms.sample(rng));
+        }
+        self.characters.truncate(self.max_length);
+        self.transforms.truncate(self.max_length);
+
+        // Optional mutation so the text slowly dissolves into rain
+        let mutation_rate = sequence.mutation_rate();
+        if !self.characters.is_empty() && mutation_rate > 0.0 && rng.gen_bool(mutation_rate as f64)
+        {
+            let idx = rng.gen_range(0..self.characters.len());
+            self.characters[idx] = glyphs.random_glyph(rng);
         }
     }
 
@@ -70,24 +127,34 @@ impl RainColumn {
     pub fn reset(&mut self, rng: &mut impl Rng) {
         self.y = -(rng.gen_range(5..=20) as f32);
         self.characters.clear();
+        self.transforms.clear();
+        self.cursor = None;
         self.active = true;
     }
 
     /// Get the position of each character in the trail
-    /// Returns Vec<(character, y_position, position_in_trail)>
+    /// Returns Vec<(glyph, y_position, position_in_trail, transform)>
     /// position_in_trail is 0.0 at the head, 1.0 at the tail
-    pub fn get_trail_positions(&self) -> Vec<(char, f32, f32)> {
+    pub fn get_trail_positions(&self) -> Vec<(Glyph, f32, f32, GlyphTransform)> {
+        // Sequence glyphs stay on their rows so the text doesn't jitter
+        let head_y = if self.cursor.is_some() {
+            self.y.floor()
+        } else {
+            self.y
+        };
+
         self.characters
             .iter()
+            .zip(&self.transforms)
             .enumerate()
-            .map(|(i, &ch)| {
-                let y_pos = self.y - i as f32;
+            .map(|(i, (&ch, &transform))| {
+                let y_pos = head_y - i as f32;
                 let trail_pos = if self.characters.len() <= 1 {
                     0.0
                 } else {
                     i as f32 / (self.characters.len() - 1) as f32
                 };
-                (ch, y_pos, trail_pos)
+                (ch, y_pos, trail_pos, transform)
             })
             .collect()
     }
@@ -96,6 +163,7 @@ impl RainColumn {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::CharacterSet;
     use rand::thread_rng;
 
     #[test]
@@ -198,4 +266,83 @@ mod tests {
             assert!(positions[last_idx].2 > 0.9);
         }
     }
+
+    #[test]
+    fn test_sequence_reads_top_to_bottom() {
+        let mut rng = thread_rng();
+        let sequence = Sequence::new(["MATRIX"], 0.0).unwrap();
+        let mut column = RainColumn::new(0, 30, 1.0, &mut rng);
+        column.speed = 1.0;
+        column.max_length = 30;
+
+        for _ in 0..40 {
+            column.update_sequence(&sequence, &CharacterSet::Japanese, &mut rng);
+        }
+
+        // Trail is stored head-first; reversed it reads in emission order
+        let top_to_bottom: String = column.characters.iter().rev().map(Glyph::as_str).collect();
+        assert!(top_to_bottom.contains("MATRIX MATRIX"));
+        assert_eq!(column.transforms.len(), column.characters.len());
+
+        // Glyphs are aligned to whole rows
+        for (_, y_pos, _, _) in column.get_trail_positions() {
+            assert_eq!(y_pos, y_pos.floor());
+        }
+
+        column.reset(&mut rng);
+        assert!(column.cursor.is_none());
+    }
+
+    #[test]
+    fn test_sequence_keeps_clusters_together() {
+        let mut rng = thread_rng();
+        let sequence = Sequence::new(["क्षत्रिय"], 0.0).unwrap();
+        let mut column = RainColumn::new(0, 30, 1.0, &mut rng);
+        column.speed = 1.0;
+        column.max_length = 30;
+
+        for _ in 0..20 {
+            column.update_sequence(&sequence, &CharacterSet::Hindi, &mut rng);
+        }
+
+        assert!(column.characters.contains(&Glyph::new("क्ष").unwrap()));
+        assert!(column.characters.contains(&Glyph::new("त्रि").unwrap()));
+    }
+
+    #[test]
+    fn test_sequence_mutation() {
+        let mut rng = thread_rng();
+        let sequence = Sequence::new(["AAAA"], 1.0).unwrap();
+        let mut column = RainColumn::new(0, 20, 1.0, &mut rng);
+        column.speed = 1.0;
+
+        for _ in 0..40 {
+            column.update_sequence(&sequence, &CharacterSet::Japanese, &mut rng);
+        }
+
+        assert!(column.characters.iter().any(|&ch| ch != 'A' && ch != ' '));
+    }
+
+    #[test]
+    fn test_transforms_follow_characters() {
+        let mut rng = thread_rng();
+        let char_set = CharacterSet::Japanese;
+        let mut column =
+            RainColumn::new(5, 20, 1.0, &mut rng).with_glyph_transforms(GlyphTransformConfig {
+                mirror_x: 1.0,
+                ..Default::default()
+            });
+
+        for _ in 0..50 {
+            column.update(&char_set, &mut rng);
+        }
+
+        assert_eq!(column.transforms.len(), column.characters.len());
+        for (_, _, _, transform) in column.get_trail_positions() {
+            assert_eq!(transform, GlyphTransform::MIRROR_X);
+        }
+
+        column.reset(&mut rng);
+        assert!(column.transforms.is_empty());
+    }
 }
@@ -0,0 +1,468 @@
+//! Frame deltas: which cells changed since the last frame, and where to redraw
+//!
+//! Hosts that repaint the whole screen every frame can instead keep the last
+//! frame on screen and repaint only the dirty rectangles. Whenever a delta
+//! can't be trusted (first frame, resize, new backdrop) or would cover most of
+//! the screen anyway, it asks for a full redraw instead.
+
+use super::MatrixRain;
+use crate::rendering::{Backdrop, RenderChar, CELL_HEIGHT_RATIO, CELL_WIDTH_RATIO};
+use std::collections::HashMap;
+
+/// Dirty area, as a fraction of the screen, above which a full redraw is cheaper
+const FULL_FRAME_AREA: f32 = 0.5;
+
+/// Most dirty rectangles a delta reports before asking for a full redraw instead
+pub const MAX_DIRTY_RECTS: usize = 512;
+
+/// Rain layer a glyph is drawn in (background glyphs are drawn first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Layer {
+    Background = 0,
+    Foreground = 1,
+}
+
+impl Layer {
+    /// Stable index used by FFI
+    pub fn index(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// Identifies a cell of the character grid within a layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CellKey {
+    pub layer: Layer,
+    /// Column index (columns are one cell width apart)
+    pub column: u32,
+    /// Row index, the row a glyph falling between rows mostly covers
+    pub row: i32,
+}
+
+impl CellKey {
+    /// Key for a glyph at a fractional row position
+    pub(super) fn new(layer: Layer, column: usize, y: f32) -> Self {
+        Self {
+            layer,
+            column: column as u32,
+            row: y.round() as i32,
+        }
+    }
+}
+
+/// A glyph to draw together with the cell it occupies
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderCell {
+    pub key: CellKey,
+    pub render_char: RenderChar,
+}
+
+/// How a cell differs from the previous frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellChange {
+    /// A glyph appeared in an empty cell
+    Added = 0,
+    /// The cell's glyph disappeared
+    Removed = 1,
+    /// Same glyph, different colour or brightness
+    Recolored = 2,
+    /// A different glyph (its colour may have changed too)
+    Reglyphed = 3,
+    /// Same glyph and colour, drawn at a different position or size, or with a
+    /// different transform or glow
+    Restyled = 4,
+}
+
+impl CellChange {
+    /// Stable index used by FFI
+    pub fn index(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Compare a cell's glyph in two frames (None if nothing changed)
+    fn between(previous: &RenderChar, current: &RenderChar) -> Option<Self> {
+        if previous == current {
+            None
+        } else if previous.character != current.character {
+            Some(CellChange::Reglyphed)
+        } else if previous.color != current.color || previous.intensity != current.intensity {
+            Some(CellChange::Recolored)
+        } else {
+            Some(CellChange::Restyled)
+        }
+    }
+}
+
+/// One changed cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellDelta {
+    pub key: CellKey,
+    pub change: CellChange,
+    /// The cell's glyph this frame, or for removals the glyph it last had
+    pub render_char: RenderChar,
+}
+
+/// A screen region in pixels, in the same coordinates as `RenderChar` positions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirtyRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl DirtyRect {
+    /// A rectangle from its top-left corner and size
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The area a glyph may paint: its cell, turned if the glyph is rotated a
+    /// quarter turn, grown by its glow
+    pub fn around(render_char: &RenderChar) -> Self {
+        let (mut width, mut height) = (
+            render_char.font_size * CELL_WIDTH_RATIO,
+            render_char.font_size * CELL_HEIGHT_RATIO,
+        );
+        let (mut x, mut y) = (render_char.x, render_char.y);
+        if render_char.transform.rotation_degrees() % 180.0 != 0.0 {
+            // Rotated about the cell centre, the cell covers a square of its longer side
+            let side = width.max(height);
+            x -= (side - width) / 2.0;
+            y -= (side - height) / 2.0;
+            (width, height) = (side, side);
+        }
+        let glow = render_char.font_size * render_char.glow.max(0.0);
+        Self::new(x - glow, y - glow, width + 2.0 * glow, height + 2.0 * glow)
+    }
+
+    /// Area in square pixels
+    pub fn area(&self) -> f32 {
+        self.width * self.height
+    }
+
+    /// Check whether two rectangles overlap or share an edge
+    pub fn touches(&self, other: &DirtyRect) -> bool {
+        self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+
+    /// The smallest rectangle covering both
+    pub fn union(&self, other: &DirtyRect) -> Self {
+        let (left, top) = (self.x.min(other.x), self.y.min(other.y));
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Self::new(left, top, right - left, bottom - top)
+    }
+
+    /// The part inside a `width`×`height` screen (None if nothing is left)
+    fn clipped(&self, width: f32, height: f32) -> Option<Self> {
+        let (left, top) = (self.x.max(0.0), self.y.max(0.0));
+        let right = (self.x + self.width).min(width);
+        let bottom = (self.y + self.height).min(height);
+        (right > left && bottom > top).then(|| Self::new(left, top, right - left, bottom - top))
+    }
+}
+
+/// What changed between two frames
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FrameDelta {
+    /// The whole screen must be redrawn; `dirty_rects` then holds just the screen
+    pub full_frame: bool,
+    /// Changed cells, ordered by layer, column and row
+    pub changes: Vec<CellDelta>,
+    /// Regions to redraw, covering every changed glyph's old and new area
+    pub dirty_rects: Vec<DirtyRect>,
+}
+
+impl FrameDelta {
+    /// Check whether nothing needs redrawing
+    pub fn is_empty(&self) -> bool {
+        !self.full_frame && self.dirty_rects.is_empty()
+    }
+}
+
+/// Remembers the last frame of an engine so the next can be reported as a delta
+///
+/// Redrawing a dirty rectangle means repainting the backdrop there and every
+/// glyph of the current frame that overlaps it, not only the changed ones.
+#[derive(Debug, Clone, Default)]
+pub struct DeltaTracker {
+    previous: HashMap<CellKey, RenderChar>,
+    /// Backdrop and screen size of the previous frame (None before the first)
+    previous_screen: Option<(Backdrop, u32, u32)>,
+}
+
+impl DeltaTracker {
+    /// Create a tracker whose first delta is a full frame
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make the next delta a full frame, e.g. after the host lost its drawing
+    pub fn invalidate(&mut self) {
+        self.previous.clear();
+        self.previous_screen = None;
+    }
+
+    /// Report what changed in the engine's current frame since the last call
+    pub fn diff(&mut self, engine: &MatrixRain) -> FrameDelta {
+        let config = engine.config();
+        let screen = (engine.backdrop(), config.screen_width, config.screen_height);
+        let (width, height) = (config.screen_width as f32, config.screen_height as f32);
+        let mut full_frame = self.previous_screen != Some(screen);
+
+        let mut changes = Vec::new();
+        let mut dirty = Vec::new();
+        let mut current = HashMap::with_capacity(self.previous.len());
+        for RenderCell { key, render_char } in engine.get_render_cells() {
+            let previous = self.previous.remove(&key);
+            let change = match &previous {
+                Some(previous) => CellChange::between(previous, &render_char),
+                None => Some(CellChange::Added),
+            };
+            if let Some(change) = change {
+                let mut area = DirtyRect::around(&render_char);
+                if let Some(previous) = &previous {
+                    area = area.union(&DirtyRect::around(previous));
+                }
+                dirty.push(area);
+                changes.push(CellDelta {
+                    key,
+                    change,
+                    render_char: render_char.clone(),
+                });
+            }
+            current.insert(key, render_char);
+        }
+        for (key, render_char) in self.previous.drain() {
+            dirty.push(DirtyRect::around(&render_char));
+            changes.push(CellDelta {
+                key,
+                change: CellChange::Removed,
+                render_char,
+            });
+        }
+        changes.sort_by_key(|delta| delta.key);
+
+        let mut dirty_rects = merge(dirty, width, height);
+        let dirty_area: f32 = dirty_rects.iter().map(DirtyRect::area).sum();
+        full_frame |=
+            dirty_rects.len() > MAX_DIRTY_RECTS || dirty_area > FULL_FRAME_AREA * width * height;
+        if full_frame {
+            dirty_rects = vec![DirtyRect::new(0.0, 0.0, width, height)];
+        }
+
+        self.previous = current;
+        self.previous_screen = Some(screen);
+        FrameDelta {
+            full_frame,
+            changes,
+            dirty_rects,
+        }
+    }
+}
+
+/// Clip rectangles to the screen and merge those that touch
+///
+/// Sorting by position first lines up each column's rectangles, so runs of
+/// glyphs down a column collapse into one rectangle.
+fn merge(mut rects: Vec<DirtyRect>, width: f32, height: f32) -> Vec<DirtyRect> {
+    rects.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    let mut merged: Vec<DirtyRect> = Vec::with_capacity(rects.len());
+    for rect in rects.iter().filter_map(|rect| rect.clipped(width, height)) {
+        match merged.last_mut() {
+            Some(last) if last.touches(&rect) => *last = last.union(&rect),
+            _ => merged.push(rect),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::{Color, GlyphTransform};
+    use crate::ScreenSaverConfig;
+
+    fn render_char(ch: char, x: f32, y: f32) -> RenderChar {
+        RenderChar {
+            character: ch.into(),
+            glyph_index: None,
+            x,
+            y,
+            color: Color::MATRIX_GREEN,
+            font_size: 10.0,
+            transform: GlyphTransform::NONE,
+            leader: false,
+            glow: 0.0,
+            intensity: 1.0,
+        }
+    }
+
+    fn engine() -> MatrixRain {
+        let config = ScreenSaverConfig {
+            screen_width: 320,
+            screen_height: 240,
+            ..Default::default()
+        };
+        let mut engine = MatrixRain::with_seed(config, 7);
+        for _ in 0..40 {
+            engine.update();
+        }
+        engine
+    }
+
+    #[test]
+    fn test_classifies_changes() {
+        let before = render_char('a', 0.0, 0.0);
+        let mut after = before.clone();
+        assert_eq!(CellChange::between(&before, &after), None);
+
+        after.x = 1.0;
+        assert_eq!(
+            CellChange::between(&before, &after),
+            Some(CellChange::Restyled)
+        );
+        after.color = Color::WHITE;
+        assert_eq!(
+            CellChange::between(&before, &after),
+            Some(CellChange::Recolored)
+        );
+        after.character = 'b'.into();
+        assert_eq!(
+            CellChange::between(&before, &after),
+            Some(CellChange::Reglyphed)
+        );
+    }
+
+    #[test]
+    fn test_glyph_areas() {
+        let plain = DirtyRect::around(&render_char('a', 10.0, 20.0));
+        assert_eq!(plain, DirtyRect::new(10.0, 20.0, 6.0, 12.0));
+
+        let mut turned = render_char('a', 10.0, 20.0);
+        turned.transform = GlyphTransform::ROTATE_90;
+        turned.glow = 0.5;
+        // A 12×12 square around the cell centre, plus 5 pixels of glow
+        assert_eq!(
+            DirtyRect::around(&turned),
+            DirtyRect::new(2.0, 15.0, 22.0, 22.0)
+        );
+    }
+
+    #[test]
+    fn test_merges_runs_and_clips() {
+        let rects = vec![
+            DirtyRect::new(0.0, 24.0, 6.0, 12.0),
+            DirtyRect::new(0.0, 0.0, 6.0, 12.0),
+            DirtyRect::new(0.0, 12.0, 6.0, 12.0),
+            DirtyRect::new(30.0, 0.0, 6.0, 12.0),
+            DirtyRect::new(-3.0, 95.0, 6.0, 12.0),
+            DirtyRect::new(200.0, 0.0, 6.0, 12.0),
+        ];
+        assert_eq!(
+            merge(rects, 100.0, 100.0),
+            vec![
+                DirtyRect::new(0.0, 95.0, 3.0, 5.0),
+                DirtyRect::new(0.0, 0.0, 6.0, 36.0),
+                DirtyRect::new(30.0, 0.0, 6.0, 12.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_first_frame_is_full_then_deltas_replay_the_frame() {
+        let mut engine = engine();
+        let mut tracker = DeltaTracker::new();
+        let first = tracker.diff(&engine);
+        assert!(first.full_frame);
+        assert_eq!(
+            first.dirty_rects,
+            vec![DirtyRect::new(0.0, 0.0, 320.0, 240.0)]
+        );
+        assert!(first
+            .changes
+            .iter()
+            .all(|delta| delta.change == CellChange::Added));
+
+        // Nothing moves without an update
+        assert!(tracker.diff(&engine).is_empty());
+
+        // Applying each delta to the previous frame rebuilds the current one
+        let mut frame: HashMap<CellKey, RenderChar> = engine
+            .get_render_cells()
+            .into_iter()
+            .map(|cell| (cell.key, cell.render_char))
+            .collect();
+        for _ in 0..5 {
+            engine.update();
+            let delta = tracker.diff(&engine);
+            assert!(!delta.changes.is_empty());
+            for change in delta.changes {
+                match change.change {
+                    CellChange::Removed => assert!(frame.remove(&change.key).is_some()),
+                    CellChange::Added => {
+                        assert!(frame.insert(change.key, change.render_char).is_none())
+                    }
+                    _ => assert!(frame.insert(change.key, change.render_char).is_some()),
+                }
+            }
+            let expected: HashMap<CellKey, RenderChar> = engine
+                .get_render_cells()
+                .into_iter()
+                .map(|cell| (cell.key, cell.render_char))
+                .collect();
+            assert_eq!(frame, expected);
+        }
+    }
+
+    #[test]
+    fn test_dirty_rects_cover_changes() {
+        let mut engine = engine();
+        let mut tracker = DeltaTracker::new();
+        tracker.diff(&engine);
+        engine.update();
+
+        // One update of the seeded engine stays under the full-frame fallbacks
+        let delta = tracker.diff(&engine);
+        assert!(!delta.full_frame);
+        assert!(!delta.changes.is_empty());
+        for change in &delta.changes {
+            let area = DirtyRect::around(&change.render_char)
+                .clipped(320.0, 240.0)
+                .unwrap();
+            assert!(delta
+                .dirty_rects
+                .iter()
+                .any(|rect| rect.union(&area) == *rect));
+        }
+    }
+
+    #[test]
+    fn test_full_frame_fallbacks() {
+        let mut engine = engine();
+        let mut tracker = DeltaTracker::new();
+        tracker.diff(&engine);
+
+        tracker.invalidate();
+        assert!(tracker.diff(&engine).full_frame);
+
+        // A new backdrop or screen size repaints everything
+        let mut config = engine.config().clone();
+        config.background = crate::config::BackgroundConfig::solid((40, 0, 0));
+        engine.set_config(config.clone());
+        assert!(tracker.diff(&engine).full_frame);
+        config.screen_width = 640;
+        engine.set_config(config);
+        assert!(tracker.diff(&engine).full_frame);
+        assert!(!tracker.diff(&engine).full_frame);
+    }
+}
@@ -0,0 +1,195 @@
+//! Cached pool of glyphs that columns draw from
+
+use crate::config::CharacterSet;
+use crate::fonts::FontCoverage;
+use crate::rendering::Glyph;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Anything columns can pick random glyphs from
+pub trait GlyphSource {
+    /// Pick a random glyph
+    fn random_glyph(&self, rng: &mut impl Rng) -> Glyph;
+}
+
+impl GlyphSource for CharacterSet {
+    fn random_glyph(&self, rng: &mut impl Rng) -> Glyph {
+        let glyphs = self.get_glyphs();
+        glyphs[rng.gen_range(0..glyphs.len())]
+    }
+}
+
+/// The glyphs the engine spawns, built once from a character set
+///
+/// Optionally restricted to the glyphs a font can display, so fonts with
+/// partial coverage never show missing-glyph boxes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphPool {
+    /// Glyphs available for spawning
+    glyphs: Vec<Glyph>,
+    /// Number of glyphs before any font filtering
+    unfiltered_len: usize,
+    /// Position of each glyph in `glyphs`
+    indices: HashMap<Glyph, u32>,
+}
+
+impl GlyphPool {
+    /// Build a pool containing every glyph of a character set
+    pub fn from_character_set(character_set: CharacterSet) -> Self {
+        let glyphs = character_set.get_glyphs();
+        Self {
+            unfiltered_len: glyphs.len(),
+            indices: index_glyphs(&glyphs),
+            glyphs,
+        }
+    }
+
+    /// Build a pool from custom glyphs (single characters or grapheme clusters)
+    /// Returns None if no glyphs are given
+    pub fn from_glyphs(glyphs: Vec<Glyph>) -> Option<Self> {
+        if glyphs.is_empty() {
+            return None;
+        }
+        Some(Self {
+            unfiltered_len: glyphs.len(),
+            indices: index_glyphs(&glyphs),
+            glyphs,
+        })
+    }
+
+    /// Build a pool limited to the glyphs covered by a font
+    ///
+    /// Falls back to the full set if the font covers none of it,
+    /// since an empty pool would leave nothing to draw.
+    pub fn with_coverage(character_set: CharacterSet, coverage: &FontCoverage) -> Self {
+        Self::from_character_set(character_set).filtered(coverage)
+    }
+
+    /// Restrict the pool to glyphs whose every codepoint the font covers
+    /// Keeps the pool unchanged if the font covers none of it.
+    pub fn filtered(mut self, coverage: &FontCoverage) -> Self {
+        let covered: Vec<Glyph> = self
+            .glyphs
+            .iter()
+            .copied()
+            .filter(|glyph| glyph.chars().all(|ch| coverage.contains(ch)))
+            .collect();
+        if !covered.is_empty() {
+            self.indices = index_glyphs(&covered);
+            self.glyphs = covered;
+        }
+        self
+    }
+
+    /// The glyphs in this pool
+    pub fn glyphs(&self) -> &[Glyph] {
+        &self.glyphs
+    }
+
+    /// Position of a glyph in the pool, which is also its index in glyph atlases
+    /// built from the pool; None for glyphs the pool doesn't contain
+    pub fn index_of(&self, glyph: Glyph) -> Option<u32> {
+        self.indices.get(&glyph).copied()
+    }
+
+    /// Number of glyphs in the pool
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    /// Check whether the pool is empty
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Number of glyphs removed by font filtering
+    pub fn filtered_out(&self) -> usize {
+        self.unfiltered_len - self.glyphs.len()
+    }
+}
+
+/// Map glyphs to their positions, keeping the first of any duplicates
+fn index_glyphs(glyphs: &[Glyph]) -> HashMap<Glyph, u32> {
+    let mut indices = HashMap::with_capacity(glyphs.len());
+    for (index, &glyph) in glyphs.iter().enumerate() {
+        indices.entry(glyph).or_insert(index as u32);
+    }
+    indices
+}
+
+impl GlyphSource for GlyphPool {
+    fn random_glyph(&self, rng: &mut impl Rng) -> Glyph {
+        self.glyphs[rng.gen_range(0..self.glyphs.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fonts::coverage_test_font;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_pool_matches_character_set() {
+        let pool = GlyphPool::from_character_set(CharacterSet::Thai);
+        assert_eq!(pool.glyphs(), CharacterSet::Thai.get_glyphs().as_slice());
+        assert_eq!(pool.filtered_out(), 0);
+    }
+
+    #[test]
+    fn test_random_glyph_from_pool() {
+        let mut rng = thread_rng();
+        let pool = GlyphPool::from_character_set(CharacterSet::Korean);
+        for _ in 0..100 {
+            assert!(pool.glyphs().contains(&pool.random_glyph(&mut rng)));
+        }
+    }
+
+    #[test]
+    fn test_filter_by_coverage() {
+        let coverage =
+            FontCoverage::from_bytes(&coverage_test_font(&[(0x30A0, 0x30FF)]), 0).unwrap();
+        let pool = GlyphPool::with_coverage(CharacterSet::Japanese, &coverage);
+
+        assert_eq!(pool.len(), 96);
+        assert!(pool.filtered_out() > 0);
+        assert!(pool
+            .glyphs()
+            .iter()
+            .all(|glyph| coverage.contains(glyph.first_char())));
+    }
+
+    #[test]
+    fn test_filter_without_coverage_keeps_full_set() {
+        let coverage = FontCoverage::from_bytes(&coverage_test_font(&[(0x41, 0x5A)]), 0).unwrap();
+        let pool = GlyphPool::with_coverage(CharacterSet::Hebrew, &coverage);
+        assert_eq!(pool.len(), CharacterSet::Hebrew.get_glyphs().len());
+    }
+
+    #[test]
+    fn test_custom_glyph_pool() {
+        let glyphs: Vec<Glyph> = ["🇯🇵", "👩‍💻", "A"]
+            .iter()
+            .filter_map(|text| Glyph::new(text))
+            .collect();
+        let pool = GlyphPool::from_glyphs(glyphs.clone()).unwrap();
+        assert_eq!(pool.glyphs(), glyphs.as_slice());
+        assert_eq!(pool.index_of(glyphs[1]), Some(1));
+        assert!(GlyphPool::from_glyphs(Vec::new()).is_none());
+
+        // Clusters are kept only if the font covers every codepoint
+        let coverage = FontCoverage::from_bytes(&coverage_test_font(&[(0x41, 0x5A)]), 0).unwrap();
+        let pool = pool.filtered(&coverage);
+        assert_eq!(pool.glyphs(), &[Glyph::from('A')]);
+        assert_eq!(pool.filtered_out(), 2);
+        // Indices follow the filtered pool
+        assert_eq!(pool.index_of('A'.into()), Some(0));
+        assert_eq!(pool.index_of(glyphs[0]), None);
+    }
+
+    #[test]
+    fn test_pool_keeps_clusters() {
+        let pool = GlyphPool::from_character_set(CharacterSet::Tamil);
+        assert!(pool.glyphs().iter().any(|glyph| !glyph.is_single_char()));
+    }
+}
@@ -1,10 +1,15 @@
 //! Main Matrix Rain engine
 
-use crate::config::ScreenSaverConfig;
-use crate::rendering::{Color, RenderChar, Renderer};
+use crate::config::{mix_rgb, rotate_hue, trail_alpha, ScreenSaverConfig};
+use crate::fonts::{FontCoverage, FontError};
+use crate::rendering::{
+    batch_render_chars, Backdrop, Color, FrameStats, RenderBatch, RenderChar, Renderer,
+};
 use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::HashMap;
+use std::path::PathBuf;
 
-use super::RainColumn;
+use super::{CellKey, GlyphPool, Layer, RainColumn, RenderCell, Sequence};
 
 /// The main Matrix Rain engine
 pub struct MatrixRain {
@@ -14,6 +19,14 @@ pub struct MatrixRain {
     columns: Vec<RainColumn>,
     /// Background rain columns (subtle depth layer)
     background_columns: Vec<RainColumn>,
+    /// Glyphs that columns spawn
+    glyph_pool: GlyphPool,
+    /// Coverage of the configured font, used to filter the glyph pool
+    font_coverage: Option<FontCoverage>,
+    /// Why the configured coverage font could not be loaded, if it failed
+    coverage_error: Option<FontError>,
+    /// Loaded entries for word/sequence mode (None in random mode)
+    sequence: Option<Sequence>,
     /// Random number generator
     rng: StdRng,
     /// Character width in pixels
@@ -22,13 +35,24 @@ pub struct MatrixRain {
     char_height: f32,
     /// Font size
     font_size: f32,
+    /// Animation time in seconds, advanced by one update interval per update
+    time: f32,
+    /// Simulated time passed to `advance` that has not yet filled an update interval
+    pending_time: f32,
 }
 
 impl MatrixRain {
     /// Create a new Matrix Rain engine
     pub fn new(config: ScreenSaverConfig) -> Self {
-        let mut rng = StdRng::from_entropy();
+        Self::with_rng(config, StdRng::from_entropy())
+    }
+
+    /// Create an engine whose animation is reproducible for a given seed
+    pub fn with_seed(config: ScreenSaverConfig, seed: u64) -> Self {
+        Self::with_rng(config, StdRng::seed_from_u64(seed))
+    }
 
+    fn with_rng(config: ScreenSaverConfig, mut rng: StdRng) -> Self {
         // Calculate character dimensions
         let font_size = 16.0;
         let char_width = font_size * 0.6; // Monospace font ratio
@@ -40,10 +64,12 @@ impl MatrixRain {
         // Create foreground columns with staggered start times
         let max_length = config.speed.max_trail_length();
         let base_speed = config.speed.speed_multiplier();
+        let glyph_transforms = config.glyph_transforms;
 
         let mut columns = Vec::with_capacity(num_columns);
         for x in 0..num_columns {
-            let column = RainColumn::new(x, max_length, base_speed, &mut rng);
+            let column = RainColumn::new(x, max_length, base_speed, &mut rng)
+                .with_glyph_transforms(glyph_transforms);
             columns.push(column);
         }
 
@@ -56,35 +82,58 @@ impl MatrixRain {
                 let bg_speed = base_speed * 0.6;
                 // Shorter trails for background
                 let bg_max_length = max_length / 2;
-                let column = RainColumn::new(x, bg_max_length, bg_speed, &mut rng);
+                let column = RainColumn::new(x, bg_max_length, bg_speed, &mut rng)
+                    .with_glyph_transforms(glyph_transforms);
                 background_columns.push(column);
             }
         }
 
+        // Fonts that fail to load leave the pool unfiltered
+        let (font_coverage, coverage_error) = load_font_coverage(&config);
+        let glyph_pool = build_glyph_pool(&config, font_coverage.as_ref());
+
+        // Unreadable or empty sources fall back to random glyphs
+        let sequence = config.sequence.as_ref().and_then(Sequence::from_config);
+
         Self {
             config,
             columns,
             background_columns,
+            glyph_pool,
+            font_coverage,
+            coverage_error,
+            sequence,
             rng,
             char_width,
             char_height,
             font_size,
+            time: 0.0,
+            pending_time: 0.0,
         }
     }
 
     /// Update the animation state
     pub fn update(&mut self) {
-        let char_set = self.config.character_set;
+        self.time += self.config.speed.update_interval_ms() as f32 / 1000.0;
+
+        let glyph_pool = &self.glyph_pool;
+        let sequence = self.sequence.as_ref();
         let screen_height = self.config.screen_height as f32;
+        // Chance an idle column starts a new trail, scaled by the configured density
+        let density = self.config.density.max(0.0) as f64;
+        let spawn_chance = |base: f64| (base * density).min(1.0);
 
-        // Update foreground columns
+        // Update foreground columns (only the foreground spells sequences)
         for column in &mut self.columns {
-            column.update(&char_set, &mut self.rng);
+            match sequence {
+                Some(sequence) => column.update_sequence(sequence, glyph_pool, &mut self.rng),
+                None => column.update(glyph_pool, &mut self.rng),
+            }
 
             // Reset columns that have moved off screen
             if column.is_off_screen(screen_height, self.char_height) {
                 // Random chance to start a new column or wait
-                if self.rng.gen_bool(0.1) {
+                if self.rng.gen_bool(spawn_chance(0.1)) {
                     column.reset(&mut self.rng);
                 } else {
                     column.active = false;
@@ -94,7 +143,7 @@ impl MatrixRain {
 
         // Randomly activate inactive foreground columns
         for column in &mut self.columns {
-            if !column.active && self.rng.gen_bool(0.01) {
+            if !column.active && self.rng.gen_bool(spawn_chance(0.01)) {
                 column.reset(&mut self.rng);
             }
         }
@@ -102,11 +151,11 @@ impl MatrixRain {
         // Update background columns (if enabled)
         if self.config.enable_background_layer {
             for column in &mut self.background_columns {
-                column.update(&char_set, &mut self.rng);
+                column.update(glyph_pool, &mut self.rng);
 
                 // Reset background columns with lower frequency
                 if column.is_off_screen(screen_height, self.char_height) {
-                    if self.rng.gen_bool(0.05) {
+                    if self.rng.gen_bool(spawn_chance(0.05)) {
                         column.reset(&mut self.rng);
                     } else {
                         column.active = false;
@@ -116,95 +165,47 @@ impl MatrixRain {
 
             // Randomly activate inactive background columns (less frequent)
             for column in &mut self.background_columns {
-                if !column.active && self.rng.gen_bool(0.005) {
+                if !column.active && self.rng.gen_bool(spawn_chance(0.005)) {
                     column.reset(&mut self.rng);
                 }
             }
         }
     }
 
-    /// Render the current state
-    pub fn render(&self, renderer: &mut impl Renderer) {
-        // Clear screen with black
-        renderer.clear(Color::BLACK);
-
-        // Collect all characters to render
-        let mut render_chars = Vec::new();
-
-        // Render background layer first (if enabled)
-        if self.config.enable_background_layer {
-            for column in &self.background_columns {
-                if !column.active {
-                    continue;
-                }
-
-                let x_pixel = column.x as f32 * self.char_width;
-
-                for (ch, y_pos, trail_pos) in column.get_trail_positions() {
-                    if y_pos < 0.0 {
-                        continue;
-                    }
-
-                    let y_pixel = y_pos * self.char_height;
-
-                    if y_pixel > self.config.screen_height as f32 {
-                        continue;
-                    }
-
-                    // Background characters are much dimmer (30% opacity, no white leader)
-                    let rgba = self.config.color_scheme.get_color_with_alpha(trail_pos);
-                    let mut color = Color::from_rgba_tuple(rgba);
-                    color.a *= 0.3; // Reduce alpha to 30% for subtle background effect
-
-                    render_chars.push(RenderChar {
-                        character: ch,
-                        x: x_pixel,
-                        y: y_pixel,
-                        color,
-                        font_size: self.font_size * 0.9, // Slightly smaller font for depth
-                    });
-                }
-            }
+    /// Advance by a span of simulated time, running as many updates as fit
+    /// Leftover time carries over to the next call, so any frame rate plays back at
+    /// the speed's own update rate.
+    pub fn advance(&mut self, seconds: f32) {
+        let interval = self.config.speed.update_interval_ms() as f32 / 1000.0;
+        self.pending_time += seconds.max(0.0);
+        while self.pending_time >= interval {
+            self.pending_time -= interval;
+            self.update();
         }
+    }
 
-        // Render foreground layer (main rain)
-        for column in &self.columns {
-            if !column.active {
-                continue;
-            }
-
-            let x_pixel = column.x as f32 * self.char_width;
-
-            for (ch, y_pos, trail_pos) in column.get_trail_positions() {
-                // Skip characters above screen
-                if y_pos < 0.0 {
-                    continue;
-                }
-
-                let y_pixel = y_pos * self.char_height;
+    /// Render the current state, reporting how many glyphs and batches were drawn
+    pub fn render(&self, renderer: &mut impl Renderer) -> FrameStats {
+        // Paint the configured background (the scheme's, black for presets, by default)
+        renderer.clear_backdrop(&self.backdrop());
 
-                // Skip characters below screen
-                if y_pixel > self.config.screen_height as f32 {
-                    continue;
-                }
-
-                // Get color based on position in trail
-                let rgba = self.config.color_scheme.get_color_with_alpha(trail_pos);
-                let color = Color::from_rgba_tuple(rgba);
-
-                render_chars.push(RenderChar {
-                    character: ch,
-                    x: x_pixel,
-                    y: y_pixel,
-                    color,
-                    font_size: self.font_size,
-                });
-            }
+        // Render all characters in batches (background layer first, then foreground)
+        let batches = self.get_render_batches();
+        for batch in &batches {
+            renderer.draw_batch(batch);
         }
-
-        // Render all characters
-        renderer.draw_chars(&render_chars);
         renderer.present();
+        FrameStats::of(&batches)
+    }
+
+    /// Get the backdrop to paint behind the rain, resolved for the current color scheme
+    pub fn backdrop(&self) -> Backdrop {
+        self.config.background.backdrop(&self.config.color_scheme)
+    }
+
+    /// Get a single background colour (the backdrop's base colour)
+    pub fn background_color(&self) -> Color {
+        self.backdrop().base_color()
     }
 
     /// Get the current configuration
@@ -221,17 +222,47 @@ impl MatrixRain {
         // If speed changed, update column speeds and max lengths
         let speed_changed = config.speed != self.config.speed;
 
+        let transforms_changed = config.glyph_transforms != self.config.glyph_transforms;
+
+        let font_changed = config.coverage_font != self.config.coverage_font
+            || config.coverage_font_index != self.config.coverage_font_index;
+        let glyphs_changed = font_changed
+            || config.character_set != self.config.character_set
+            || config.custom_glyphs != self.config.custom_glyphs;
+
+        let sequence_changed = config.sequence != self.config.sequence;
+
         self.config = config;
 
+        if font_changed {
+            (self.font_coverage, self.coverage_error) = load_font_coverage(&self.config);
+        }
+        if glyphs_changed {
+            self.glyph_pool = build_glyph_pool(&self.config, self.font_coverage.as_ref());
+        }
+
+        if sequence_changed {
+            self.sequence = self
+                .config
+                .sequence
+                .as_ref()
+                .and_then(Sequence::from_config);
+            for column in &mut self.columns {
+                column.reset(&mut self.rng);
+            }
+        }
+
         if dimensions_changed {
             // Recalculate foreground columns
             let num_columns = (self.config.screen_width as f32 / self.char_width).ceil() as usize;
             let max_length = self.config.speed.max_trail_length();
             let base_speed = self.config.speed.speed_multiplier();
+            let glyph_transforms = self.config.glyph_transforms;
 
             self.columns.clear();
             for x in 0..num_columns {
-                let column = RainColumn::new(x, max_length, base_speed, &mut self.rng);
+                let column = RainColumn::new(x, max_length, base_speed, &mut self.rng)
+                    .with_glyph_transforms(glyph_transforms);
                 self.columns.push(column);
             }
 
@@ -241,7 +272,8 @@ impl MatrixRain {
                 for x in (0..num_columns).step_by(3) {
                     let bg_speed = base_speed * 0.6;
                     let bg_max_length = max_length / 2;
-                    let column = RainColumn::new(x, bg_max_length, bg_speed, &mut self.rng);
+                    let column = RainColumn::new(x, bg_max_length, bg_speed, &mut self.rng)
+                        .with_glyph_transforms(glyph_transforms);
                     self.background_columns.push(column);
                 }
             }
@@ -261,6 +293,59 @@ impl MatrixRain {
                 column.max_length = self.rng.gen_range(max_length / 4..=max_length / 2);
             }
         }
+
+        if transforms_changed && !dimensions_changed {
+            // Existing glyphs keep their transform; new glyphs use the new odds
+            for column in self.columns.iter_mut().chain(&mut self.background_columns) {
+                column.glyph_transforms = self.config.glyph_transforms;
+            }
+        }
+    }
+
+    /// Restrict spawned glyphs to those covered by a font (None removes the filter)
+    ///
+    /// Returns the number of glyphs left in the pool.
+    pub fn set_font_coverage(&mut self, coverage: Option<FontCoverage>) -> usize {
+        self.font_coverage = coverage;
+        self.glyph_pool = build_glyph_pool(&self.config, self.font_coverage.as_ref());
+        self.glyph_pool.len()
+    }
+
+    /// Restrict spawned glyphs to those covered by a font file (None removes the filter)
+    ///
+    /// Unlike `set_font_coverage`, this records the font in the config, so later
+    /// `set_config` calls keep the filter. The font's face is the config's
+    /// `coverage_font_index`. On error nothing changes.
+    /// Returns the number of glyphs left in the pool.
+    pub fn set_coverage_font(&mut self, path: Option<PathBuf>) -> Result<usize, FontError> {
+        self.font_coverage = match &path {
+            Some(path) => Some(FontCoverage::from_file_index(
+                path,
+                self.config.coverage_font_index,
+            )?),
+            None => None,
+        };
+        self.config.coverage_font = path;
+        self.coverage_error = None;
+        self.glyph_pool = build_glyph_pool(&self.config, self.font_coverage.as_ref());
+        Ok(self.glyph_pool.len())
+    }
+
+    /// Why the config's coverage font could not be loaded (None if it loaded or none is set)
+    ///
+    /// A font that fails to load leaves the glyph pool unfiltered.
+    pub fn coverage_font_error(&self) -> Option<&FontError> {
+        self.coverage_error.as_ref()
+    }
+
+    /// Get the glyphs columns currently spawn
+    pub fn glyph_pool(&self) -> &GlyphPool {
+        &self.glyph_pool
+    }
+
+    /// Check whether columns are spelling sequence entries
+    pub fn is_sequence_mode(&self) -> bool {
+        self.sequence.is_some()
     }
 
     /// Get the number of active columns
@@ -273,9 +358,75 @@ impl MatrixRain {
         self.columns.len()
     }
 
+    /// Width and height of a character cell in pixels (columns are one cell apart)
+    pub fn cell_size(&self) -> (f32, f32) {
+        (self.char_width, self.char_height)
+    }
+
+    /// Animation time in seconds
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Set the animation time in seconds (e.g. to sync with a host clock)
+    pub fn set_time(&mut self, seconds: f32) {
+        self.time = seconds;
+    }
+
+    /// Get the colour for a trail position in a column at the current time
+    /// Leader glyphs take the leader colour; the rest use the gradient if one is set,
+    /// otherwise the scheme. Palette animation is applied on top.
+    fn trail_color(&self, column: usize, trail_pos: f32, leader: bool) -> Color {
+        let scheme = self.config.color_scheme;
+        let animation = &self.config.palette_animation;
+
+        let mut rgb = if leader {
+            self.config
+                .leader
+                .color
+                .unwrap_or_else(|| scheme.get_leader_color())
+        } else {
+            let mut rgb = match &self.config.gradient {
+                Some(gradient) => gradient.color_at(trail_pos),
+                None => scheme.get_body_color(trail_pos),
+            };
+            if let Some(other) = animation.breathing_scheme() {
+                let amount = animation.breathe_amount(self.time);
+                rgb = mix_rgb(rgb, other.get_body_color(trail_pos), amount);
+            }
+            rgb
+        };
+        rgb = rotate_hue(rgb, animation.hue_shift(self.time, column));
+
+        Color::rgba(rgb.0, rgb.1, rgb.2, trail_alpha(trail_pos))
+    }
+
     /// Get render data without actually rendering (useful for FFI)
     pub fn get_render_data(&self) -> Vec<RenderChar> {
-        let mut render_chars = Vec::new();
+        self.get_render_cells()
+            .into_iter()
+            .map(|cell| cell.render_char)
+            .collect()
+    }
+
+    /// Get render data grouped into batches of glyphs that share a drawing style
+    /// The background layer's batches come first, so it stays behind the foreground.
+    pub fn get_render_batches(&self) -> Vec<RenderBatch> {
+        let (background, foreground): (Vec<RenderCell>, Vec<RenderCell>) = self
+            .get_render_cells()
+            .into_iter()
+            .partition(|cell| cell.key.layer == Layer::Background);
+        let mut batches = batch_render_chars(background.into_iter().map(|cell| cell.render_char));
+        batches.extend(batch_render_chars(
+            foreground.into_iter().map(|cell| cell.render_char),
+        ));
+        batches
+    }
+
+    /// Get render data with the cell each glyph occupies, in drawing order
+    /// Hosts diffing frames (see `DeltaTracker`) match glyphs up by cell.
+    pub fn get_render_cells(&self) -> Vec<RenderCell> {
+        let mut render_cells = Vec::new();
 
         // Add background layer first (if enabled)
         if self.config.enable_background_layer {
@@ -286,7 +437,7 @@ impl MatrixRain {
 
                 let x_pixel = column.x as f32 * self.char_width;
 
-                for (ch, y_pos, trail_pos) in column.get_trail_positions() {
+                for (ch, y_pos, trail_pos, transform) in column.get_trail_positions() {
                     if y_pos < 0.0 {
                         continue;
                     }
@@ -297,17 +448,24 @@ impl MatrixRain {
                         continue;
                     }
 
-                    // Background characters are much dimmer
-                    let rgba = self.config.color_scheme.get_color_with_alpha(trail_pos);
-                    let mut color = Color::from_rgba_tuple(rgba);
+                    // Background characters are much dimmer and have no leader
+                    let mut color = self.trail_color(column.x, trail_pos, false);
                     color.a *= 0.3; // 30% opacity for subtle effect
 
-                    render_chars.push(RenderChar {
-                        character: ch,
-                        x: x_pixel,
-                        y: y_pixel,
-                        color,
-                        font_size: self.font_size * 0.9,
+                    render_cells.push(RenderCell {
+                        key: CellKey::new(Layer::Background, column.x, y_pos),
+                        render_char: RenderChar {
+                            character: ch,
+                            glyph_index: self.glyph_pool.index_of(ch),
+                            x: x_pixel,
+                            y: y_pixel,
+                            color,
+                            font_size: self.font_size * 0.9,
+                            transform,
+                            leader: false,
+                            glow: 0.0,
+                            intensity: 1.0,
+                        },
                     });
                 }
             }
@@ -321,7 +479,9 @@ impl MatrixRain {
 
             let x_pixel = column.x as f32 * self.char_width;
 
-            for (ch, y_pos, trail_pos) in column.get_trail_positions() {
+            for (index, (ch, y_pos, trail_pos, transform)) in
+                column.get_trail_positions().into_iter().enumerate()
+            {
                 // Skip characters above screen
                 if y_pos < 0.0 {
                     continue;
@@ -335,33 +495,105 @@ impl MatrixRain {
                 }
 
                 // Get color based on position in trail
-                let rgba = self.config.color_scheme.get_color_with_alpha(trail_pos);
-                let color = Color::from_rgba_tuple(rgba);
-
-                render_chars.push(RenderChar {
-                    character: ch,
-                    x: x_pixel,
-                    y: y_pixel,
-                    color,
-                    font_size: self.font_size,
+                let leader = self.config.leader.is_leader(index, trail_pos);
+                let color = self.trail_color(column.x, trail_pos, leader);
+
+                render_cells.push(RenderCell {
+                    key: CellKey::new(Layer::Foreground, column.x, y_pos),
+                    render_char: RenderChar {
+                        character: ch,
+                        glyph_index: self.glyph_pool.index_of(ch),
+                        x: x_pixel,
+                        y: y_pixel,
+                        color,
+                        font_size: self.font_size,
+                        transform,
+                        leader,
+                        glow: if leader { self.config.leader.glow } else { 0.0 },
+                        intensity: if leader {
+                            self.config.leader.brightness.max(0.0)
+                        } else {
+                            1.0
+                        },
+                    },
                 });
             }
         }
 
-        render_chars
+        if self.config.precomposite {
+            self.precomposite(&mut render_cells);
+        }
+
+        render_cells
+    }
+
+    /// Replace each glyph's colour with the result of blending it over the backdrop and
+    /// any glyphs already drawn in the same cell, so hosts can draw glyphs without blending
+    ///
+    /// Colours come out opaque unless the backdrop is transparent.
+    fn precomposite(&self, render_cells: &mut [RenderCell]) {
+        let backdrop = self.backdrop();
+        let width = self.config.screen_width.max(1) as f32;
+        let height = self.config.screen_height.max(1) as f32;
+        let mut cells: HashMap<(i64, i64), Color> = HashMap::new();
+
+        for RenderCell { render_char, .. } in render_cells {
+            let cell = (
+                (render_char.x / self.char_width).round() as i64,
+                (render_char.y / self.char_height).round() as i64,
+            );
+            let below = *cells.entry(cell).or_insert_with(|| {
+                backdrop.color_at(render_char.x / width, render_char.y / height)
+            });
+            let color = render_char.color.blend_over(below, self.config.blend_mode);
+            cells.insert(cell, color);
+            render_char.color = color;
+        }
+    }
+}
+
+/// Load the coverage of the config's font, or the error that prevented it
+fn load_font_coverage(config: &ScreenSaverConfig) -> (Option<FontCoverage>, Option<FontError>) {
+    match &config.coverage_font {
+        Some(path) => match FontCoverage::from_file_index(path, config.coverage_font_index) {
+            Ok(coverage) => (Some(coverage), None),
+            Err(err) => (None, Some(err)),
+        },
+        None => (None, None),
+    }
+}
+
+/// Build the glyph pool for a config, filtered by font coverage if available
+/// Custom glyphs replace the character set when any are configured
+fn build_glyph_pool(config: &ScreenSaverConfig, coverage: Option<&FontCoverage>) -> GlyphPool {
+    let pool = config
+        .custom_glyphs
+        .clone()
+        .and_then(GlyphPool::from_glyphs)
+        .unwrap_or_else(|| GlyphPool::from_character_set(config.character_set));
+    match coverage {
+        Some(coverage) => pool.filtered(coverage),
+        None => pool,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{CharacterSet, ColorScheme, RainSpeed};
+    use crate::config::{
+        BackgroundConfig, BackgroundFill, CharacterSet, ColorScheme, CustomColors,
+        GlyphTransformConfig, Gradient, Interpolation, LeaderStyle, PaletteAnimation, RainSpeed,
+        SequenceConfig, SequenceSource,
+    };
+    use crate::fonts::coverage_test_font;
+    use crate::rendering::{BlendMode, Glyph, GlyphTransform};
 
     // Mock renderer for testing
     struct MockRenderer {
         width: u32,
         height: u32,
         chars_drawn: Vec<RenderChar>,
+        clear_color: Option<Color>,
     }
 
     impl MockRenderer {
@@ -370,12 +602,14 @@ mod tests {
                 width,
                 height,
                 chars_drawn: Vec::new(),
+                clear_color: None,
             }
         }
     }
 
     impl Renderer for MockRenderer {
-        fn clear(&mut self, _color: Color) {
+        fn clear(&mut self, color: Color) {
+            self.clear_color = Some(color);
             self.chars_drawn.clear();
         }
 
@@ -435,6 +669,47 @@ mod tests {
         assert!(!renderer.chars_drawn.is_empty());
     }
 
+    #[test]
+    fn test_render_batches() {
+        let mut matrix = MatrixRain::with_seed(ScreenSaverConfig::default(), 3);
+        for _ in 0..80 {
+            matrix.update();
+        }
+
+        // Every glyph lands in a batch, and the background layer is drawn first
+        let batches = matrix.get_render_batches();
+        let render_data = matrix.get_render_data();
+        let background_size = matrix.font_size * 0.9;
+        let first_foreground = batches
+            .iter()
+            .position(|batch| batch.font_size != background_size)
+            .unwrap();
+        assert!(batches[first_foreground..]
+            .iter()
+            .all(|batch| batch.font_size != background_size));
+        assert!(batches.len() < render_data.len() / 4);
+
+        let mut renderer = MockRenderer::new(1920, 1080);
+        let stats = matrix.render(&mut renderer);
+        assert_eq!(stats.glyphs, render_data.len());
+        assert_eq!(stats.batches, batches.len());
+        assert_eq!(renderer.chars_drawn.len(), render_data.len());
+    }
+
+    #[test]
+    fn test_render_chars_reference_glyph_pool() {
+        let mut matrix = MatrixRain::with_seed(ScreenSaverConfig::default(), 5);
+        for _ in 0..60 {
+            matrix.update();
+        }
+        let render_data = matrix.get_render_data();
+        assert!(!render_data.is_empty());
+        for rc in &render_data {
+            let index = rc.glyph_index.expect("random glyphs come from the pool");
+            assert_eq!(matrix.glyph_pool().glyphs()[index as usize], rc.character);
+        }
+    }
+
     #[test]
     fn test_config_update() {
         let config = ScreenSaverConfig::default();
@@ -484,4 +759,551 @@ mod tests {
                 > fast_matrix.config().speed.max_trail_length()
         );
     }
+
+    #[test]
+    fn test_glyph_transforms_in_render_data() {
+        let config = ScreenSaverConfig {
+            glyph_transforms: GlyphTransformConfig {
+                rotate_180: 1.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::new(config);
+
+        for _ in 0..50 {
+            matrix.update();
+        }
+
+        let render_data = matrix.get_render_data();
+        assert!(!render_data.is_empty());
+        assert!(render_data
+            .iter()
+            .all(|rc| rc.transform == GlyphTransform::ROTATE_180));
+    }
+
+    #[test]
+    fn test_set_config_updates_transforms() {
+        let mut matrix = MatrixRain::new(ScreenSaverConfig::default());
+        for _ in 0..50 {
+            matrix.update();
+        }
+
+        let mut config = matrix.config().clone();
+        config.glyph_transforms = GlyphTransformConfig {
+            mirror_y: 1.0,
+            ..Default::default()
+        };
+        matrix.set_config(config);
+
+        assert!(matrix
+            .columns
+            .iter()
+            .chain(&matrix.background_columns)
+            .all(|c| c.glyph_transforms.mirror_y == 1.0));
+    }
+
+    #[test]
+    fn test_font_coverage_filters_spawned_glyphs() {
+        let mut matrix = MatrixRain::new(ScreenSaverConfig::default());
+        let full = matrix.glyph_pool().len();
+
+        let coverage =
+            FontCoverage::from_bytes(&coverage_test_font(&[(0x30A0, 0x30FF)]), 0).unwrap();
+        let remaining = matrix.set_font_coverage(Some(coverage.clone()));
+        assert!(remaining < full);
+
+        for _ in 0..50 {
+            matrix.update();
+        }
+        assert!(matrix
+            .columns
+            .iter()
+            .flat_map(|c| &c.characters)
+            .all(|glyph| glyph.chars().all(|ch| coverage.contains(ch))));
+
+        // Switching character set keeps the filter
+        let mut config = matrix.config().clone();
+        config.character_set = CharacterSet::Thai;
+        matrix.set_config(config);
+        assert_eq!(matrix.glyph_pool().filtered_out(), 0);
+
+        assert_eq!(matrix.set_font_coverage(None), matrix.glyph_pool().len());
+    }
+
+    #[test]
+    fn test_coverage_font_is_kept_in_config() {
+        let path =
+            std::env::temp_dir().join(format!("matrix-rain-coverage-{}.ttf", std::process::id()));
+        std::fs::write(&path, coverage_test_font(&[(0x30A0, 0x30FF)])).unwrap();
+        let mut matrix = MatrixRain::new(ScreenSaverConfig::default());
+
+        let remaining = matrix.set_coverage_font(Some(path.clone())).unwrap();
+        assert_eq!(remaining, 96);
+        assert_eq!(matrix.config().coverage_font.as_ref(), Some(&path));
+
+        // Later config changes keep the filter
+        let config = ScreenSaverConfig {
+            speed: crate::RainSpeed::Fast,
+            ..matrix.config().clone()
+        };
+        matrix.set_config(config);
+        assert_eq!(matrix.glyph_pool().len(), 96);
+
+        // The face index is honoured: this single-face font has no face 1
+        let config = ScreenSaverConfig {
+            coverage_font_index: 1,
+            ..matrix.config().clone()
+        };
+        matrix.set_config(config);
+        assert!(matches!(
+            matrix.coverage_font_error(),
+            Some(FontError::FaceIndexOutOfRange(1))
+        ));
+        let config = ScreenSaverConfig {
+            coverage_font_index: 0,
+            ..matrix.config().clone()
+        };
+        matrix.set_config(config);
+        assert!(matrix.coverage_font_error().is_none());
+        assert_eq!(matrix.glyph_pool().len(), 96);
+
+        // Errors leave everything as it was
+        assert!(matrix
+            .set_coverage_font(Some("/nonexistent/font.ttf".into()))
+            .is_err());
+        assert_eq!(matrix.config().coverage_font.as_ref(), Some(&path));
+        assert_eq!(matrix.glyph_pool().len(), 96);
+
+        assert!(matrix.set_coverage_font(None).unwrap() > 96);
+        assert_eq!(matrix.config().coverage_font, None);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_coverage_font_is_ignored() {
+        let config = ScreenSaverConfig {
+            coverage_font: Some("/nonexistent/font.ttf".into()),
+            ..Default::default()
+        };
+        let matrix = MatrixRain::new(config);
+        assert_eq!(matrix.glyph_pool().filtered_out(), 0);
+        assert!(matches!(
+            matrix.coverage_font_error(),
+            Some(FontError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_sequence_mode_from_config() {
+        let config = ScreenSaverConfig {
+            sequence: Some(SequenceConfig::words(["NEBUCHADNEZZAR"])),
+            enable_background_layer: false,
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::new(config);
+        assert!(matrix.is_sequence_mode());
+
+        for _ in 0..60 {
+            matrix.update();
+        }
+
+        let render_data = matrix.get_render_data();
+        assert!(!render_data.is_empty());
+        assert!(render_data
+            .iter()
+            .all(|rc| "NEBUCHADNEZZAR ".contains(rc.character.as_str())));
+
+        // Turning sequence mode off returns to random glyphs
+        let mut config = matrix.config().clone();
+        config.sequence = None;
+        matrix.set_config(config);
+        assert!(!matrix.is_sequence_mode());
+    }
+
+    #[test]
+    fn test_custom_cluster_glyphs() {
+        let flags: Vec<Glyph> = Glyph::segment("🇯🇵🇰🇷🇮🇳");
+        let config = ScreenSaverConfig {
+            custom_glyphs: Some(flags.clone()),
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::new(config);
+        assert_eq!(matrix.glyph_pool().glyphs(), flags.as_slice());
+
+        for _ in 0..50 {
+            matrix.update();
+        }
+        let render_data = matrix.get_render_data();
+        assert!(!render_data.is_empty());
+        assert!(render_data.iter().all(|rc| flags.contains(&rc.character)));
+
+        // An empty list falls back to the character set
+        let mut config = matrix.config().clone();
+        config.custom_glyphs = Some(Vec::new());
+        matrix.set_config(config);
+        assert_eq!(
+            matrix.glyph_pool().len(),
+            CharacterSet::Japanese.get_glyphs().len()
+        );
+    }
+
+    #[test]
+    fn test_custom_color_scheme() {
+        let colors = CustomColors {
+            leader: (255, 240, 200),
+            primary: (255, 102, 0),
+            secondary: (153, 61, 0),
+            tertiary: (77, 31, 0),
+            background: (10, 0, 20),
+        };
+        let config = ScreenSaverConfig {
+            color_scheme: ColorScheme::Custom(colors),
+            enable_background_layer: false,
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::new(config);
+        for _ in 0..50 {
+            matrix.update();
+        }
+
+        let mut renderer = MockRenderer::new(1920, 1080);
+        matrix.render(&mut renderer);
+        assert_eq!(renderer.clear_color, Some(Color::rgb(10, 0, 20)));
+
+        let palette = [
+            colors.leader,
+            colors.primary,
+            colors.secondary,
+            colors.tertiary,
+        ];
+        assert!(renderer
+            .chars_drawn
+            .iter()
+            .all(|rc| palette.contains(&(rc.color.r, rc.color.g, rc.color.b))));
+    }
+
+    #[test]
+    fn test_gradient_overrides_scheme_colors() {
+        let gradient = Gradient::evenly_spaced(
+            &[(255, 255, 255), (0, 255, 70), (0, 40, 10)],
+            Interpolation::OkLab,
+        )
+        .unwrap();
+        let config = ScreenSaverConfig {
+            gradient: Some(gradient.clone()),
+            enable_background_layer: false,
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::new(config);
+        for _ in 0..50 {
+            matrix.update();
+        }
+
+        for column in &matrix.columns {
+            for (_, _, trail_pos, _) in column.get_trail_positions() {
+                let color = matrix.trail_color(column.x, trail_pos, false);
+                assert_eq!((color.r, color.g, color.b), gradient.color_at(trail_pos));
+                assert_eq!(color.a, trail_alpha(trail_pos));
+            }
+        }
+
+        // The scheme's stepped gradient matches rendering without one
+        let mut config = matrix.config().clone();
+        config.gradient = Some(config.color_scheme.to_gradient());
+        let stepped = MatrixRain::new(config);
+        let plain = MatrixRain::new(ScreenSaverConfig::default());
+        for pos in [0.0, 0.04, 0.1, 0.3, 0.9] {
+            assert_eq!(
+                stepped.trail_color(0, pos, false),
+                plain.trail_color(0, pos, false)
+            );
+        }
+        assert_eq!(
+            stepped.trail_color(0, 0.0, true),
+            plain.trail_color(0, 0.0, true)
+        );
+    }
+
+    #[test]
+    fn test_time_advances_per_update() {
+        let mut matrix = MatrixRain::new(ScreenSaverConfig::default());
+        assert_eq!(matrix.time(), 0.0);
+        for _ in 0..20 {
+            matrix.update();
+        }
+        // Medium speed updates every 50ms
+        assert!((matrix.time() - 1.0).abs() < 1e-4);
+
+        matrix.set_time(42.0);
+        assert_eq!(matrix.time(), 42.0);
+    }
+
+    #[test]
+    fn test_advance_by_simulated_time() {
+        let mut matrix = MatrixRain::new(ScreenSaverConfig::default());
+        // 60fps frames at 50ms per update: 3 updates per 10 frames
+        for _ in 0..10 {
+            matrix.advance(1.0 / 60.0);
+        }
+        assert!((matrix.time() - 0.15).abs() < 1e-4);
+        matrix.advance(0.35);
+        assert!((matrix.time() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_seeded_engines_match() {
+        let mut a = MatrixRain::with_seed(ScreenSaverConfig::default(), 7);
+        let mut b = MatrixRain::with_seed(ScreenSaverConfig::default(), 7);
+        for _ in 0..50 {
+            a.update();
+            b.update();
+        }
+        assert_eq!(a.get_render_data(), b.get_render_data());
+    }
+
+    #[test]
+    fn test_hue_cycling_and_column_offsets() {
+        let config = ScreenSaverConfig {
+            palette_animation: PaletteAnimation {
+                hue_cycle_period: 12.0,
+                column_hue_offset: 10.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::new(config);
+        let plain = MatrixRain::new(ScreenSaverConfig::default());
+
+        // Column 0 at time 0 is unshifted; the white leader never changes
+        assert_eq!(
+            matrix.trail_color(0, 0.3, false),
+            plain.trail_color(0, 0.3, false)
+        );
+        assert_eq!(
+            matrix.trail_color(7, 0.0, true),
+            plain.trail_color(7, 0.0, true)
+        );
+
+        // Neighbouring columns differ, and time moves the spectrum
+        assert_ne!(
+            matrix.trail_color(0, 0.3, false),
+            matrix.trail_color(6, 0.3, false)
+        );
+        matrix.set_time(3.0);
+        assert_ne!(
+            matrix.trail_color(0, 0.3, false),
+            plain.trail_color(0, 0.3, false)
+        );
+
+        // A full period later colours repeat
+        let before = matrix.trail_color(3, 0.3, false);
+        matrix.set_time(15.0);
+        assert_eq!(matrix.trail_color(3, 0.3, false), before);
+    }
+
+    #[test]
+    fn test_breathing_between_schemes() {
+        let config = ScreenSaverConfig {
+            palette_animation: PaletteAnimation::breathing(ColorScheme::Red, 10.0),
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::new(config);
+        let green = matrix.trail_color(0, 0.1, false);
+        assert_eq!((green.r, green.g, green.b), (0, 255, 70));
+
+        matrix.set_time(5.0);
+        let red = matrix.trail_color(0, 0.1, false);
+        assert_eq!((red.r, red.g, red.b), (255, 50, 50));
+        assert_eq!(red.a, green.a);
+    }
+
+    #[test]
+    fn test_leader_style() {
+        let config = ScreenSaverConfig {
+            leader: LeaderStyle {
+                color: Some((255, 200, 0)),
+                head_glyphs: Some(2),
+                glow: 0.8,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::new(config);
+        for _ in 0..60 {
+            matrix.update();
+        }
+
+        let render_data = matrix.get_render_data();
+        let leaders: Vec<&RenderChar> = render_data.iter().filter(|rc| rc.leader).collect();
+        assert!(!leaders.is_empty());
+        for rc in &leaders {
+            assert_eq!((rc.color.r, rc.color.g, rc.color.b), (255, 200, 0));
+            assert_eq!(rc.glow, 0.8);
+            assert_eq!(rc.font_size, 16.0); // Background glyphs are never leaders
+        }
+        assert!(render_data
+            .iter()
+            .filter(|rc| !rc.leader)
+            .all(|rc| rc.glow == 0.0 && (rc.color.r, rc.color.g, rc.color.b) != (255, 200, 0)));
+
+        // Each column has at most two leaders
+        let foreground = matrix.columns.len();
+        assert!(leaders.len() <= foreground * 2);
+    }
+
+    #[test]
+    fn test_default_leader_follows_trail_length() {
+        // Very slow trails run up to 30 glyphs; those over 21 lead with two
+        let config = ScreenSaverConfig {
+            speed: crate::RainSpeed::VerySlow,
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::with_seed(config, 7);
+        for _ in 0..200 {
+            matrix.update();
+        }
+
+        let mut leaders: HashMap<u32, usize> = HashMap::new();
+        for rc in matrix.get_render_data().iter().filter(|rc| rc.leader) {
+            *leaders.entry(rc.x.to_bits()).or_default() += 1;
+        }
+        assert!(leaders.values().any(|&count| count == 2));
+        assert!(leaders.values().all(|&count| count <= 2));
+    }
+
+    #[test]
+    fn test_disabled_leader_uses_trail_colors() {
+        let config = ScreenSaverConfig {
+            leader: LeaderStyle::none(),
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::new(config);
+        for _ in 0..60 {
+            matrix.update();
+        }
+
+        let render_data = matrix.get_render_data();
+        assert!(!render_data.is_empty());
+        assert!(render_data
+            .iter()
+            .all(|rc| !rc.leader && (rc.color.r, rc.color.g, rc.color.b) != (255, 255, 255)));
+    }
+
+    #[test]
+    fn test_background_config() {
+        let mut matrix = MatrixRain::new(ScreenSaverConfig::default());
+        assert_eq!(matrix.backdrop(), Backdrop::Solid(Color::BLACK));
+
+        let mut config = matrix.config().clone();
+        config.background = BackgroundConfig {
+            fill: BackgroundFill::Vertical {
+                top: (0, 0, 0),
+                bottom: (0, 60, 20),
+            },
+            tint: 0.0,
+        };
+        matrix.set_config(config);
+        let mut renderer = MockRenderer::new(1920, 1080);
+        matrix.render(&mut renderer);
+        // The mock only clears, so it receives the gradient's base colour
+        assert_eq!(renderer.clear_color, Some(Color::rgb(0, 30, 10)));
+
+        let mut config = matrix.config().clone();
+        config.background = BackgroundConfig::transparent();
+        matrix.set_config(config);
+        matrix.render(&mut renderer);
+        assert_eq!(renderer.clear_color.map(|c| c.a), Some(0.0));
+    }
+
+    #[test]
+    fn test_zero_density_lets_rain_die_out() {
+        let config = ScreenSaverConfig {
+            screen_height: 200,
+            density: 0.0,
+            speed: RainSpeed::VeryFast,
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::new(config);
+        for _ in 0..2000 {
+            matrix.update();
+        }
+        assert_eq!(matrix.active_columns(), 0);
+        assert!(matrix.get_render_data().is_empty());
+    }
+
+    #[test]
+    fn test_hdr_leader() {
+        let config = ScreenSaverConfig {
+            leader: LeaderStyle {
+                brightness: 3.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::new(config);
+        for _ in 0..60 {
+            matrix.update();
+        }
+
+        let render_data = matrix.get_render_data();
+        assert!(render_data.iter().any(|rc| rc.leader));
+        for rc in &render_data {
+            let extended = rc.extended_color();
+            if rc.leader {
+                // Heads go past SDR white while the SDR colour stays white
+                assert_eq!(rc.intensity, 3.0);
+                assert_eq!(extended.max_component(), 3.0);
+                assert_eq!((rc.color.r, rc.color.g, rc.color.b), (255, 255, 255));
+            } else {
+                assert_eq!(rc.intensity, 1.0);
+                assert!(!extended.is_extended());
+                assert_eq!(extended.to_color(), rc.color);
+            }
+        }
+    }
+
+    #[test]
+    fn test_precomposite() {
+        let mut matrix = MatrixRain::new(ScreenSaverConfig::default());
+        for _ in 0..100 {
+            matrix.update();
+        }
+        let raw = matrix.get_render_data();
+        assert!(raw.iter().any(|rc| rc.color.a < 1.0));
+
+        let mut config = matrix.config().clone();
+        config.precomposite = true;
+        matrix.set_config(config);
+        let composited = matrix.get_render_data();
+        assert_eq!(composited.len(), raw.len());
+        assert!(composited.iter().all(|rc| rc.color.a == 1.0));
+
+        // The first glyph in a cell is blended straight onto the black backdrop
+        assert_eq!(
+            composited[0].color,
+            raw[0].color.blend_over(Color::BLACK, BlendMode::Normal)
+        );
+
+        // Additive blending never comes out darker than normal blending over black
+        let mut config = matrix.config().clone();
+        config.blend_mode = BlendMode::Additive;
+        matrix.set_config(config);
+        let additive = matrix.get_render_data();
+        for (add, normal) in additive.iter().zip(&composited) {
+            assert!(add.color.g >= normal.color.g);
+        }
+    }
+
+    #[test]
+    fn test_unreadable_sequence_falls_back() {
+        let config = ScreenSaverConfig {
+            sequence: Some(SequenceConfig {
+                source: SequenceSource::File("/nonexistent/names.txt".into()),
+                mutation_rate: 0.0,
+            }),
+            ..Default::default()
+        };
+        assert!(!MatrixRain::new(config).is_sequence_mode());
+    }
 }
@@ -1,7 +1,16 @@
 //! Core engine for the Matrix rain effect
 
 mod column;
+mod delta;
+mod glyph_pool;
 mod matrix_rain;
+mod sequence;
 
 pub use column::RainColumn;
+pub use delta::{
+    CellChange, CellDelta, CellKey, DeltaTracker, DirtyRect, FrameDelta, Layer, RenderCell,
+    MAX_DIRTY_RECTS,
+};
+pub use glyph_pool::{GlyphPool, GlyphSource};
 pub use matrix_rain::MatrixRain;
+pub use sequence::{Sequence, SequenceCursor};
@@ -0,0 +1,123 @@
+//! Word/sequence mode: columns spell entries from a word list or text
+
+use crate::config::SequenceConfig;
+use crate::rendering::Glyph;
+use rand::Rng;
+
+/// Position of a column within the sequence entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceCursor {
+    /// Index of the entry being spelled
+    pub entry: usize,
+    /// Index of the next glyph within the entry
+    pub offset: usize,
+}
+
+/// Loaded sequence entries shared by all columns
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sequence {
+    /// Entries split into glyphs (grapheme clusters stay whole)
+    entries: Vec<Vec<Glyph>>,
+    /// Chance per update of a trail glyph mutating
+    mutation_rate: f32,
+}
+
+impl Sequence {
+    /// Create a sequence from entries (None if there is nothing to spell)
+    pub fn new<I, S>(entries: I, mutation_rate: f32) -> Option<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let entries: Vec<Vec<Glyph>> = entries
+            .into_iter()
+            .map(|entry| Glyph::segment(entry.as_ref()))
+            .filter(|entry| !entry.is_empty())
+            .collect();
+
+        if entries.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            entries,
+            mutation_rate: mutation_rate.clamp(0.0, 1.0),
+        })
+    }
+
+    /// Load a sequence from config (None if the source is empty or unreadable)
+    pub fn from_config(config: &SequenceConfig) -> Option<Self> {
+        let entries = config.source.entries().ok()?;
+        Self::new(entries, config.mutation_rate)
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether there are no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Chance per update of a trail glyph mutating
+    pub fn mutation_rate(&self) -> f32 {
+        self.mutation_rate
+    }
+
+    /// Start spelling a random entry
+    pub fn start_cursor(&self, rng: &mut impl Rng) -> SequenceCursor {
+        SequenceCursor {
+            entry: rng.gen_range(0..self.entries.len()),
+            offset: 0,
+        }
+    }
+
+    /// Get the next glyph for a cursor and advance it
+    /// A space separates entries; after it a new random entry begins
+    pub fn next_glyph(&self, cursor: &mut SequenceCursor, rng: &mut impl Rng) -> Glyph {
+        let entry = &self.entries[cursor.entry.min(self.entries.len() - 1)];
+        if cursor.offset < entry.len() {
+            let glyph = entry[cursor.offset];
+            cursor.offset += 1;
+            glyph
+        } else {
+            *cursor = self.start_cursor(rng);
+            Glyph::from_char(' ')
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SequenceConfig;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_empty_sequence() {
+        assert!(Sequence::new(Vec::<String>::new(), 0.0).is_none());
+        assert!(Sequence::new([""], 0.0).is_none());
+    }
+
+    #[test]
+    fn test_spells_entry_in_order() {
+        let mut rng = thread_rng();
+        let sequence = Sequence::new(["ZION"], 0.0).unwrap();
+        let mut cursor = sequence.start_cursor(&mut rng);
+
+        let spelled: String = (0..10)
+            .map(|_| sequence.next_glyph(&mut cursor, &mut rng).to_string())
+            .collect();
+        assert_eq!(spelled, "ZION ZION ");
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = SequenceConfig::words(["NEO"]).with_mutation_rate(2.0);
+        let sequence = Sequence::from_config(&config).unwrap();
+        assert_eq!(sequence.len(), 1);
+        assert_eq!(sequence.mutation_rate(), 1.0);
+    }
+}
@@ -0,0 +1,578 @@
+//! Looping animated previews as GIF (quantized to the scheme) or APNG (full colour)
+
+use super::{ExportError, FrameOptions, FrameSequence};
+use crate::config::{dominant_colors, rotate_hue};
+use crate::rendering::{Backdrop, Color, Framebuffer, GlyphRasterizer};
+use crate::{CharacterSet, ColorScheme, ScreenSaverConfig};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Most colours a GIF palette can hold
+pub const MAX_PALETTE_COLORS: usize = 256;
+
+/// Steps in the ramp between the ends of a gradient backdrop
+const BACKDROP_RAMP_STEPS: usize = 16;
+
+/// Hue steps (in degrees) added per colour when the palette animation cycles hue
+const HUE_CYCLE_STEP: f32 = 60.0;
+
+/// Animated image formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationFormat {
+    /// Animated GIF with a 256-colour palette
+    Gif,
+    /// Animated PNG with full 8-bit RGBA colour
+    Apng,
+}
+
+impl AnimationFormat {
+    /// File extension (without the dot)
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AnimationFormat::Gif => "gif",
+            AnimationFormat::Apng => "png",
+        }
+    }
+
+    /// Look up a format by name or extension ("gif", "apng" or "png")
+    pub fn from_id(id: &str) -> Option<AnimationFormat> {
+        match id.to_ascii_lowercase().as_str() {
+            "gif" => Some(AnimationFormat::Gif),
+            "apng" | "png" => Some(AnimationFormat::Apng),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from a file's extension
+    pub fn from_path(path: impl AsRef<Path>) -> Option<AnimationFormat> {
+        Self::from_id(path.as_ref().extension()?.to_str()?)
+    }
+}
+
+/// A fixed palette of up to 256 colours with nearest-colour lookup
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    colors: Vec<(u8, u8, u8)>,
+    /// Nearest palette index per colour already looked up
+    lookup: HashMap<(u8, u8, u8), u8>,
+}
+
+impl Palette {
+    /// Create a palette (duplicates are removed, extra colours past 256 are dropped)
+    /// An empty list gives a black-only palette.
+    pub fn new(colors: impl IntoIterator<Item = (u8, u8, u8)>) -> Self {
+        let mut unique = Vec::new();
+        for color in colors {
+            if !unique.contains(&color) {
+                unique.push(color);
+            }
+        }
+        unique.truncate(MAX_PALETTE_COLORS);
+        if unique.is_empty() {
+            unique.push((0, 0, 0));
+        }
+        Self {
+            colors: unique,
+            lookup: HashMap::new(),
+        }
+    }
+
+    /// Build a palette for the colours a config can produce
+    ///
+    /// Rain is drawn with partial alpha and anti-aliased edges over the backdrop, so
+    /// every colour the scheme uses gets a ramp from the backdrop up to full strength,
+    /// mixed in linear light as the renderer does.
+    pub fn for_config(config: &ScreenSaverConfig) -> Self {
+        let scheme = &config.color_scheme;
+        let backdrop = config.background.backdrop(scheme);
+        let (bases, base_ramp) = match backdrop {
+            Backdrop::Solid(color) => (vec![color], None),
+            Backdrop::Vertical { top, bottom } => (vec![top, bottom], Some((top, bottom))),
+            Backdrop::Radial { center, edge } => (vec![center, edge], Some((center, edge))),
+            Backdrop::Transparent => (vec![Color::BLACK], None),
+        };
+        let base = bases[0].mix(*bases.last().unwrap_or(&bases[0]), 0.5);
+
+        let mut keys = vec![config
+            .leader
+            .color
+            .unwrap_or_else(|| scheme.get_leader_color())];
+        let mut trail = match &config.gradient {
+            Some(gradient) => gradient.stops().iter().map(|stop| stop.color).collect(),
+            None => scheme_colors(scheme),
+        };
+        let animation = &config.palette_animation;
+        if let Some(other) = animation.breathing_scheme() {
+            trail.extend(scheme_colors(&other));
+        }
+        if animation.hue_cycle_period > 0.0 {
+            let shifted: Vec<_> = (1..(360.0 / HUE_CYCLE_STEP) as u32)
+                .flat_map(|step| {
+                    let degrees = step as f32 * HUE_CYCLE_STEP;
+                    trail.iter().map(move |&rgb| rotate_hue(rgb, degrees))
+                })
+                .collect();
+            trail.extend(shifted);
+        }
+        for color in trail {
+            if !keys.contains(&color) {
+                keys.push(color);
+            }
+        }
+
+        let mut colors: Vec<(u8, u8, u8)> = bases.iter().map(rgb).collect();
+        if let Some((from, to)) = base_ramp {
+            colors.extend((1..BACKDROP_RAMP_STEPS).map(|step| {
+                rgb(&mix_linear(
+                    from,
+                    to,
+                    step as f32 / BACKDROP_RAMP_STEPS as f32,
+                ))
+            }));
+        }
+        let steps = ((MAX_PALETTE_COLORS - colors.len()) / keys.len()).max(2);
+        for key in keys {
+            let key = Color::from_rgb_tuple(key);
+            for step in 1..=steps {
+                // Even steps in sRGB, where banding is visible, not in linear light
+                let amount = (step as f32 / steps as f32).powf(2.2);
+                colors.push(rgb(&mix_linear(base, key, amount)));
+            }
+        }
+        Self::new(colors)
+    }
+
+    /// Build a palette from the dominant colours of a rendered frame
+    /// Useful when colours come from somewhere the config can't predict.
+    pub fn from_framebuffer(framebuffer: &Framebuffer, count: usize) -> Self {
+        let pixels: Vec<(u8, u8, u8)> = framebuffer
+            .to_rgba8()
+            .chunks_exact(4)
+            .map(|p| (p[0], p[1], p[2]))
+            .collect();
+        Self::new(dominant_colors(&pixels, count.min(MAX_PALETTE_COLORS)))
+    }
+
+    /// The palette's colours
+    pub fn colors(&self) -> &[(u8, u8, u8)] {
+        &self.colors
+    }
+
+    /// Number of colours
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Check whether the palette has no colours (never true: an empty palette holds black)
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Index of the palette colour nearest to a colour
+    pub fn index_of(&mut self, color: (u8, u8, u8)) -> u8 {
+        if let Some(&index) = self.lookup.get(&color) {
+            return index;
+        }
+        // Weighted RGB distance, roughly matching the eye's sensitivity
+        let distance = |(r, g, b): (u8, u8, u8)| {
+            let dr = r as i32 - color.0 as i32;
+            let dg = g as i32 - color.1 as i32;
+            let db = b as i32 - color.2 as i32;
+            2 * dr * dr + 4 * dg * dg + 3 * db * db
+        };
+        let index = (0..self.colors.len())
+            .min_by_key(|&i| distance(self.colors[i]))
+            .unwrap_or(0) as u8;
+        self.lookup.insert(color, index);
+        index
+    }
+
+    /// Map every pixel of a frame to a palette index (alpha is ignored)
+    pub fn quantize(&mut self, framebuffer: &Framebuffer) -> Vec<u8> {
+        framebuffer
+            .to_rgba8()
+            .chunks_exact(4)
+            .map(|p| self.index_of((p[0], p[1], p[2])))
+            .collect()
+    }
+
+    /// The colours as packed RGB bytes (the layout of a GIF colour table)
+    pub fn to_rgb_bytes(&self) -> Vec<u8> {
+        self.colors
+            .iter()
+            .flat_map(|&(r, g, b)| [r, g, b])
+            .collect()
+    }
+}
+
+/// Colours a scheme draws trails with
+fn scheme_colors(scheme: &ColorScheme) -> Vec<(u8, u8, u8)> {
+    vec![
+        scheme.get_primary_color(),
+        scheme.get_secondary_color(),
+        scheme.get_tertiary_color(),
+    ]
+}
+
+/// Mix two colours in linear light
+fn mix_linear(from: Color, to: Color, amount: f32) -> Color {
+    let (from, to) = (from.to_linear(), to.to_linear());
+    let mut mixed = [0.0; 4];
+    for i in 0..4 {
+        mixed[i] = from[i] + (to[i] - from[i]) * amount;
+    }
+    Color::from_linear(mixed)
+}
+
+fn rgb(color: &Color) -> (u8, u8, u8) {
+    (color.r, color.g, color.b)
+}
+
+/// Delay of a GIF frame in hundredths of a second
+/// Rounding carries over between frames so the average matches the frame rate. Most
+/// viewers slow down delays under 2 (faster than 50fps), so those are raised to 2.
+pub fn gif_frame_delay(index: u32, fps: f32) -> u16 {
+    let at = |frame: u32| (frame as f64 * 100.0 / fps.max(1.0) as f64).round() as u64;
+    (at(index + 1) - at(index)).clamp(2, u16::MAX as u64) as u16
+}
+
+/// Render every remaining frame of a sequence as a looping GIF
+pub fn write_gif<R: GlyphRasterizer>(
+    sequence: &mut FrameSequence<R>,
+    palette: &mut Palette,
+    writer: impl Write,
+) -> Result<(), ExportError> {
+    let options = *sequence.options();
+    let (width, height) = match (u16::try_from(options.width), u16::try_from(options.height)) {
+        (Ok(width), Ok(height)) => (width, height),
+        _ => return Err(ExportError::TooLarge(options.width, options.height)),
+    };
+
+    let mut encoder = gif::Encoder::new(writer, width, height, &palette.to_rgb_bytes())?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    let mut index = sequence.frames_rendered();
+    while let Some(framebuffer) = sequence.next_frame() {
+        let frame = gif::Frame {
+            width,
+            height,
+            delay: gif_frame_delay(index, options.fps),
+            buffer: Cow::Owned(palette.quantize(framebuffer)),
+            ..Default::default()
+        };
+        encoder.write_frame(&frame)?;
+        index += 1;
+    }
+    Ok(())
+}
+
+/// Render every frame of a fresh sequence as a looping APNG
+pub fn write_apng<R: GlyphRasterizer>(
+    sequence: &mut FrameSequence<R>,
+    writer: impl Write,
+) -> Result<(), ExportError> {
+    let options = *sequence.options();
+    let mut encoder = png::Encoder::new(writer, options.width, options.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+    encoder.set_animated(sequence.len() - sequence.frames_rendered(), 0)?;
+    // Whole frame rates are exact; others are rounded to the millisecond
+    if options.fps.fract() == 0.0 && options.fps >= 1.0 && options.fps <= u16::MAX as f32 {
+        encoder.set_frame_delay(1, options.fps as u16)?;
+    } else {
+        let millis = (1000.0 / options.fps.max(0.1)).round().min(u16::MAX as f32) as u16;
+        encoder.set_frame_delay(millis, 1000)?;
+    }
+
+    let mut writer = encoder.write_header()?;
+    while let Some(framebuffer) = sequence.next_frame() {
+        writer.write_image_data(&framebuffer.to_rgba8())?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Render a config to an animated file
+/// GIFs use the palette from `Palette::for_config`.
+pub fn export_animation<R: GlyphRasterizer>(
+    config: ScreenSaverConfig,
+    options: &FrameOptions,
+    rasterizer: R,
+    format: AnimationFormat,
+    path: impl AsRef<Path>,
+) -> Result<(), ExportError> {
+    let mut palette = Palette::for_config(&config);
+    let mut sequence = FrameSequence::new(config, options, rasterizer);
+    let mut writer = BufWriter::new(File::create(path)?);
+    match format {
+        AnimationFormat::Gif => write_gif(&mut sequence, &mut palette, &mut writer)?,
+        AnimationFormat::Apng => write_apng(&mut sequence, &mut writer)?,
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// File name of the preview for a character set and colour scheme
+/// (e.g. "japanese-matrix-green.gif")
+pub fn preview_file_name(
+    character_set: CharacterSet,
+    color_scheme: ColorScheme,
+    format: AnimationFormat,
+) -> String {
+    format!(
+        "{}-{}.{}",
+        character_set.info().id,
+        color_scheme.info().id,
+        format.extension()
+    )
+}
+
+/// Render a preview for every character set and preset colour scheme into a directory
+///
+/// Each preview starts from `base` with the pair applied. `setup` gets that config
+/// (to adjust, e.g. set `coverage_font`) and returns the rasterizer to draw it with,
+/// or an error such as `ExportError::Setup` to stop the batch;
+/// `progress` is called after each file is written.
+pub fn export_previews<R: GlyphRasterizer>(
+    base: &ScreenSaverConfig,
+    options: &FrameOptions,
+    format: AnimationFormat,
+    dir: impl AsRef<Path>,
+    mut setup: impl FnMut(&mut ScreenSaverConfig) -> Result<R, ExportError>,
+    mut progress: impl FnMut(&Path),
+) -> Result<Vec<PathBuf>, ExportError> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let mut paths = Vec::new();
+    for character_set in CharacterSet::all_sets() {
+        for color_scheme in ColorScheme::all_schemes() {
+            let mut config = ScreenSaverConfig {
+                character_set,
+                color_scheme,
+                ..base.clone()
+            };
+            let rasterizer = setup(&mut config)?;
+            let path = dir.join(preview_file_name(character_set, color_scheme, format));
+            export_animation(config, options, rasterizer, format, &path)?;
+            progress(&path);
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Gradient, Interpolation, PaletteAnimation};
+    use crate::rendering::TestRasterizer;
+
+    /// Soft-edged blocks, so frames contain partial coverage
+    fn soft_blocks() -> TestRasterizer {
+        TestRasterizer::blocks().with_margin(2).soft()
+    }
+
+    fn small() -> FrameOptions {
+        FrameOptions {
+            width: 48,
+            height: 40,
+            frames: 4,
+            fps: 25.0,
+            seed: 3,
+            warmup: 2.0,
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("matrix-rain-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_formats() {
+        assert_eq!(AnimationFormat::from_id("GIF"), Some(AnimationFormat::Gif));
+        assert_eq!(
+            AnimationFormat::from_id("apng"),
+            Some(AnimationFormat::Apng)
+        );
+        assert_eq!(
+            AnimationFormat::from_path("out/preview.png"),
+            Some(AnimationFormat::Apng)
+        );
+        assert_eq!(AnimationFormat::from_path("preview.webp"), None);
+        assert_eq!(
+            preview_file_name(
+                CharacterSet::Korean,
+                ColorScheme::MatrixGreen,
+                AnimationFormat::Gif
+            ),
+            "korean-matrix-green.gif"
+        );
+    }
+
+    #[test]
+    fn test_scheme_palette() {
+        let config = ScreenSaverConfig::default();
+        let palette = Palette::for_config(&config);
+        assert!(palette.len() > 200 && palette.len() <= MAX_PALETTE_COLORS);
+
+        let scheme = config.color_scheme;
+        for key in [
+            (0, 0, 0),
+            scheme.get_leader_color(),
+            scheme.get_primary_color(),
+            scheme.get_tertiary_color(),
+        ] {
+            assert!(palette.colors().contains(&key), "{:?}", key);
+        }
+        // Half-covered primary over black is close to a palette entry
+        let half = rgb(&mix_linear(
+            Color::BLACK,
+            Color::from_rgb_tuple(scheme.get_primary_color()),
+            0.5,
+        ));
+        let mut palette = palette;
+        let index = palette.index_of(half) as usize;
+        let nearest = palette.colors()[index];
+        assert!((nearest.1 as i32 - half.1 as i32).abs() <= 4);
+    }
+
+    #[test]
+    fn test_palette_covers_gradients_and_animation() {
+        let config = ScreenSaverConfig {
+            gradient: Gradient::evenly_spaced(&[(255, 0, 0), (0, 0, 255)], Interpolation::Linear),
+            palette_animation: PaletteAnimation::rainbow(10.0),
+            ..Default::default()
+        };
+        let palette = Palette::for_config(&config);
+        assert!(palette.colors().contains(&(255, 0, 0)));
+        assert!(palette.colors().contains(&rotate_hue((255, 0, 0), 120.0)));
+        assert!(palette.len() <= MAX_PALETTE_COLORS);
+    }
+
+    #[test]
+    fn test_palette_lookup() {
+        let mut palette = Palette::new([(0, 0, 0), (0, 255, 0), (0, 255, 0), (255, 255, 255)]);
+        assert_eq!(palette.len(), 3);
+        assert_eq!(palette.index_of((10, 200, 20)), 1);
+        assert_eq!(palette.index_of((250, 250, 240)), 2);
+        assert_eq!(Palette::new([]).colors(), &[(0, 0, 0)]);
+
+        let mut framebuffer = Framebuffer::new(2, 1);
+        framebuffer.clear(Color::WHITE);
+        assert_eq!(palette.quantize(&framebuffer), vec![2, 2]);
+        assert_eq!(
+            Palette::from_framebuffer(&framebuffer, 8).colors(),
+            &[(255, 255, 255)]
+        );
+    }
+
+    #[test]
+    fn test_gif_delays() {
+        // 25fps is exactly 4 hundredths per frame
+        assert!((0..10).all(|i| gif_frame_delay(i, 25.0) == 4));
+        // 30fps alternates to average 3.33
+        let total: u32 = (0..30).map(|i| gif_frame_delay(i, 30.0) as u32).sum();
+        assert_eq!(total, 100);
+        // Too fast for viewers
+        assert_eq!(gif_frame_delay(0, 120.0), 2);
+    }
+
+    #[test]
+    fn test_gif_round_trip() {
+        let config = ScreenSaverConfig::default();
+        let mut palette = Palette::for_config(&config);
+        let mut sequence = FrameSequence::new(config, &small(), soft_blocks());
+        let mut bytes = Vec::new();
+        write_gif(&mut sequence, &mut palette, &mut bytes).unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(bytes.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (48, 40));
+        let mut frames = 0;
+        let mut used_colors = std::collections::HashSet::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 4);
+            used_colors.extend(frame.buffer.iter().copied());
+            frames += 1;
+        }
+        assert_eq!(frames, 4);
+        // Background plus rain in several shades
+        assert!(used_colors.len() > 3);
+    }
+
+    #[test]
+    fn test_apng_round_trip() {
+        let mut sequence =
+            FrameSequence::new(ScreenSaverConfig::default(), &small(), soft_blocks());
+        let mut bytes = Vec::new();
+        write_apng(&mut sequence, &mut bytes).unwrap();
+
+        let decoder = png::Decoder::new(bytes.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let control = reader.info().animation_control().unwrap();
+        assert_eq!((control.num_frames, control.num_plays), (4, 0));
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let mut frames = 0;
+        while reader.next_frame(&mut buffer).is_ok() {
+            let frame = reader.info().frame_control().unwrap();
+            assert_eq!((frame.delay_num, frame.delay_den), (1, 25));
+            frames += 1;
+        }
+        assert_eq!(frames, 4);
+    }
+
+    #[test]
+    fn test_gif_size_limit() {
+        let options = FrameOptions {
+            width: 70_000,
+            height: 1,
+            frames: 0,
+            ..small()
+        };
+        let mut sequence =
+            FrameSequence::new(ScreenSaverConfig::default(), &options, soft_blocks());
+        let mut palette = Palette::new([]);
+        assert!(matches!(
+            write_gif(&mut sequence, &mut palette, Vec::new()),
+            Err(ExportError::TooLarge(70_000, 1))
+        ));
+    }
+
+    #[test]
+    fn test_export_previews() {
+        let dir = temp_dir("previews");
+        let options = FrameOptions {
+            width: 24,
+            height: 24,
+            frames: 2,
+            ..small()
+        };
+        let mut setups = 0;
+        let mut written = 0;
+        let paths = export_previews(
+            &ScreenSaverConfig::default(),
+            &options,
+            AnimationFormat::Gif,
+            &dir,
+            |config| {
+                setups += 1;
+                assert!(CharacterSet::all_sets().contains(&config.character_set));
+                Ok(soft_blocks())
+            },
+            |_| written += 1,
+        )
+        .unwrap();
+
+        let pairs = CharacterSet::all_sets().len() * ColorScheme::all_schemes().len();
+        assert_eq!((paths.len(), setups, written), (pairs, pairs, pairs));
+        assert!(dir.join("hebrew-teal.gif").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
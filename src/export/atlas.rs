@@ -0,0 +1,345 @@
+//! Glyph atlases for GPU hosts
+//!
+//! Rasterizes every glyph of a pool into one packed coverage texture, with
+//! metadata saying where each glyph is and where it goes in its cell. Glyphs
+//! keep their pool positions as atlas indices, so hosts can draw each
+//! `RenderChar::glyph_index` as a textured quad without shaping any text.
+
+use super::ExportError;
+use crate::rendering::{Framebuffer, Glyph, GlyphRasterizer, CELL_HEIGHT_RATIO, CELL_WIDTH_RATIO};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Largest atlas width and height, a texture size GPUs commonly support
+pub const MAX_ATLAS_SIZE: u32 = 8192;
+
+/// Transparent pixels around each glyph, so texture filtering never picks up a neighbour
+pub const ATLAS_PADDING: u32 = 1;
+
+/// Narrowest atlas tried when packing
+const MIN_ATLAS_WIDTH: u32 = 64;
+
+/// Where a glyph is in the atlas texture and how to place it in a cell
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AtlasGlyph {
+    /// Position in the glyph pool, the same as `RenderChar::glyph_index`
+    pub index: u32,
+    /// The glyph (a character or grapheme cluster)
+    pub glyph: Glyph,
+    /// Left edge of the glyph's bitmap in the texture, in pixels
+    pub x: u32,
+    /// Top edge of the glyph's bitmap in the texture, in pixels
+    pub y: u32,
+    /// Bitmap width in pixels (0 for glyphs with nothing to draw, such as spaces)
+    pub width: u32,
+    /// Bitmap height in pixels
+    pub height: u32,
+    /// Texture coordinates of the bitmap: left, top, right, bottom (0.0-1.0, top-left origin)
+    pub uv: [f32; 4],
+    /// Offset of the bitmap's left edge from the cell's left edge
+    pub left: i32,
+    /// Offset of the bitmap's top edge from the cell's top edge
+    pub top: i32,
+    /// Horizontal advance of the glyph in the font
+    pub advance: f32,
+}
+
+impl AtlasGlyph {
+    /// Check whether the glyph has a bitmap in the texture
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+/// Glyph bitmaps packed into one texture, with the metadata to draw them
+///
+/// The texture is white, with each pixel's alpha holding glyph coverage, so
+/// hosts tint glyphs by multiplying with their colour. Glyph metrics are for
+/// `font_size`; hosts drawing other sizes scale them, and the texture, by
+/// `RenderChar::font_size / font_size`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GlyphAtlas {
+    /// Font size the glyphs were rasterized at
+    pub font_size: f32,
+    /// Width of the cell glyph offsets are relative to
+    pub cell_width: f32,
+    /// Height of the cell glyph offsets are relative to
+    pub cell_height: f32,
+    /// Distance from the top of a cell to the font's baseline
+    pub baseline: f32,
+    /// Texture width in pixels
+    pub width: u32,
+    /// Texture height in pixels
+    pub height: u32,
+    /// One entry per glyph, in pool order
+    pub glyphs: Vec<AtlasGlyph>,
+    #[serde(skip)]
+    texture: Framebuffer,
+}
+
+impl GlyphAtlas {
+    /// Rasterize glyphs at `font_size` and pack them into an atlas
+    ///
+    /// Pass the engine's pool (`MatrixRain::glyph_pool().glyphs()`) so atlas
+    /// indices match `RenderChar::glyph_index`; the atlas has to be rebuilt
+    /// when the pool changes. Fails if the glyphs don't fit in
+    /// `MAX_ATLAS_SIZE` pixels square.
+    pub fn build(
+        glyphs: &[Glyph],
+        rasterizer: &mut impl GlyphRasterizer,
+        font_size: f32,
+    ) -> Result<Self, ExportError> {
+        let bitmaps: Vec<_> = glyphs
+            .iter()
+            .map(|&glyph| rasterizer.rasterize(glyph, font_size))
+            .collect();
+        let sizes: Vec<(u32, u32)> = bitmaps
+            .iter()
+            .map(|bitmap| match bitmap {
+                Some(bitmap) => (bitmap.width as u32, bitmap.height as u32),
+                None => (0, 0),
+            })
+            .collect();
+        let Packing {
+            width,
+            height,
+            positions,
+        } = pack(&sizes).ok_or(ExportError::AtlasFull(glyphs.len(), MAX_ATLAS_SIZE))?;
+
+        let mut texture = Framebuffer::new(width, height);
+        let mut entries = Vec::with_capacity(glyphs.len());
+        for (index, ((&glyph, bitmap), (x, y))) in
+            glyphs.iter().zip(&bitmaps).zip(positions).enumerate()
+        {
+            let mut entry = AtlasGlyph {
+                index: index as u32,
+                glyph,
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0,
+                uv: [0.0; 4],
+                left: 0,
+                top: 0,
+                advance: rasterizer.advance(glyph, font_size),
+            };
+            if let Some(bitmap) = bitmap {
+                let pixels = texture.pixels_mut();
+                for row in 0..bitmap.height {
+                    let start = (y as usize + row) * width as usize + x as usize;
+                    for (column, pixel) in
+                        pixels[start..start + bitmap.width].iter_mut().enumerate()
+                    {
+                        *pixel = [bitmap.coverage_at(column, row); 4];
+                    }
+                }
+                entry.x = x;
+                entry.y = y;
+                entry.width = bitmap.width as u32;
+                entry.height = bitmap.height as u32;
+                entry.uv = [
+                    x as f32 / width as f32,
+                    y as f32 / height as f32,
+                    (x + entry.width) as f32 / width as f32,
+                    (y + entry.height) as f32 / height as f32,
+                ];
+                entry.left = bitmap.left;
+                entry.top = bitmap.top;
+            }
+            entries.push(entry);
+        }
+
+        Ok(Self {
+            font_size,
+            cell_width: font_size * CELL_WIDTH_RATIO,
+            cell_height: font_size * CELL_HEIGHT_RATIO,
+            baseline: rasterizer.baseline(font_size),
+            width,
+            height,
+            glyphs: entries,
+            texture,
+        })
+    }
+
+    /// The packed texture
+    pub fn texture(&self) -> &Framebuffer {
+        &self.texture
+    }
+
+    /// Look up a glyph by its atlas index
+    pub fn glyph(&self, index: u32) -> Option<&AtlasGlyph> {
+        self.glyphs.get(index as usize)
+    }
+
+    /// The metadata (everything but the texture) as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("atlas metadata is always serializable")
+    }
+
+    /// Write the texture as a PNG and the metadata as JSON
+    #[cfg(feature = "png")]
+    pub fn write(
+        &self,
+        png_path: impl AsRef<Path>,
+        json_path: impl AsRef<Path>,
+    ) -> Result<(), ExportError> {
+        super::write_png(&self.texture, png_path)?;
+        std::fs::write(json_path, self.to_json())?;
+        Ok(())
+    }
+}
+
+/// Path of the metadata file written next to an atlas texture
+pub fn atlas_metadata_path(png_path: impl AsRef<Path>) -> PathBuf {
+    png_path.as_ref().with_extension("json")
+}
+
+/// Where `pack` put a set of rectangles
+struct Packing {
+    width: u32,
+    height: u32,
+    /// Top left of each rectangle, inside its padding
+    positions: Vec<(u32, u32)>,
+}
+
+/// Shelf-pack rectangles, tallest first, into the narrowest power-of-two width
+/// that keeps the atlas no taller than it is wide
+fn pack(sizes: &[(u32, u32)]) -> Option<Packing> {
+    let padded = |(width, height): (u32, u32)| {
+        if width == 0 || height == 0 {
+            (0, 0)
+        } else {
+            (width + 2 * ATLAS_PADDING, height + 2 * ATLAS_PADDING)
+        }
+    };
+    let area: u64 = sizes
+        .iter()
+        .map(|&size| {
+            let (width, height) = padded(size);
+            width as u64 * height as u64
+        })
+        .sum();
+    let widest = sizes.iter().map(|&size| padded(size).0).max().unwrap_or(0);
+
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse((sizes[i].1, sizes[i].0)));
+
+    let mut width = MIN_ATLAS_WIDTH
+        .max(widest.next_power_of_two())
+        .max(((area as f64).sqrt().ceil() as u32).next_power_of_two());
+    while width <= MAX_ATLAS_SIZE {
+        let mut positions = vec![(0, 0); sizes.len()];
+        let (mut x, mut y, mut shelf) = (0, 0, 0);
+        for &i in &order {
+            let (padded_width, padded_height) = padded(sizes[i]);
+            if padded_width == 0 {
+                continue;
+            }
+            if x + padded_width > width {
+                (x, y, shelf) = (0, y + shelf, 0);
+            }
+            positions[i] = (x + ATLAS_PADDING, y + ATLAS_PADDING);
+            x += padded_width;
+            shelf = shelf.max(padded_height);
+        }
+        let height = (y + shelf).max(1);
+        if height <= width {
+            return Some(Packing {
+                width,
+                height,
+                positions,
+            });
+        }
+        width *= 2;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::TestRasterizer;
+
+    /// Solid boxes whose size depends on the glyph, with nothing for spaces
+    fn sized_boxes() -> TestRasterizer {
+        TestRasterizer::blocks().with_margin(1).varied()
+    }
+
+    fn glyphs(text: &str) -> Vec<Glyph> {
+        text.chars().map(Glyph::from).collect()
+    }
+
+    #[test]
+    fn test_glyphs_are_packed_without_overlap() {
+        let glyphs = glyphs("ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789");
+        let atlas = GlyphAtlas::build(&glyphs, &mut sized_boxes(), 32.0).unwrap();
+        assert!(atlas.width.is_power_of_two() && atlas.height <= atlas.width);
+        assert_eq!(atlas.glyphs.len(), glyphs.len());
+
+        for (i, a) in atlas.glyphs.iter().enumerate() {
+            assert_eq!(a.index as usize, i);
+            assert_eq!(a.glyph, glyphs[i]);
+            assert!(a.x + a.width <= atlas.width && a.y + a.height <= atlas.height);
+            for b in &atlas.glyphs[i + 1..] {
+                let apart = a.x + a.width + ATLAS_PADDING <= b.x
+                    || b.x + b.width + ATLAS_PADDING <= a.x
+                    || a.y + a.height + ATLAS_PADDING <= b.y
+                    || b.y + b.height + ATLAS_PADDING <= a.y;
+                assert!(apart, "{:?} overlaps {:?}", a.glyph, b.glyph);
+            }
+        }
+    }
+
+    #[test]
+    fn test_texture_and_metadata_agree() {
+        let glyphs = glyphs("A B");
+        let atlas = GlyphAtlas::build(&glyphs, &mut sized_boxes(), 20.0).unwrap();
+        let texture = atlas.texture();
+        assert_eq!(
+            (texture.width(), texture.height()),
+            (atlas.width, atlas.height)
+        );
+
+        // Covered pixels are opaque white, padding stays transparent
+        let a = atlas.glyph(0).unwrap();
+        assert_eq!(texture.pixel(a.x, a.y), Some(crate::Color::WHITE));
+        assert_eq!(texture.pixels()[0], [0.0; 4]);
+        assert_eq!(a.uv[0], a.x as f32 / atlas.width as f32);
+        assert_eq!(a.uv[3], (a.y + a.height) as f32 / atlas.height as f32);
+        assert_eq!((a.left, a.top), (1, 1));
+
+        // Spaces keep their index but take no space
+        let space = atlas.glyph(1).unwrap();
+        assert!(space.is_empty() && space.uv == [0.0; 4]);
+        assert_eq!(atlas.glyph(2).unwrap().glyph, 'B');
+        assert!(atlas.glyph(3).is_none());
+
+        // Default metrics come from the cell
+        assert_eq!(a.advance, 20.0 * CELL_WIDTH_RATIO);
+        assert_eq!(atlas.cell_height, 20.0 * CELL_HEIGHT_RATIO);
+    }
+
+    #[test]
+    fn test_json_metadata() {
+        let atlas = GlyphAtlas::build(&glyphs("ア"), &mut sized_boxes(), 16.0).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&atlas.to_json()).unwrap();
+        assert_eq!(json["width"], atlas.width);
+        assert_eq!(json["glyphs"][0]["glyph"], "ア");
+        assert_eq!(json["glyphs"][0]["uv"].as_array().unwrap().len(), 4);
+        assert!(json.get("texture").is_none());
+        assert_eq!(
+            atlas_metadata_path("out/atlas.png"),
+            Path::new("out/atlas.json")
+        );
+    }
+
+    #[test]
+    fn test_too_many_glyphs() {
+        let empty = pack(&[]).unwrap();
+        assert_eq!((empty.width, empty.height), (MIN_ATLAS_WIDTH, 1));
+        // Too wide once padded, then too much area in total
+        assert!(pack(&[(MAX_ATLAS_SIZE, 10)]).is_none());
+        assert!(pack(&[(4000, 4000); 5]).is_none());
+        assert!(pack(&[(4000, 4000); 4]).is_some());
+    }
+}
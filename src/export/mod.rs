@@ -0,0 +1,116 @@
+//! Offline export of rendered rain to image files
+//!
+//! Drives `MatrixRain` with simulated time and rasterizes each frame on the CPU
+//! (or writes it as SVG), so sequences are reproducible and need no window.
+//! Glyph atlases package the glyphs themselves for hosts drawing on the GPU.
+
+#[cfg(feature = "animation")]
+mod animation;
+mod atlas;
+mod sequence;
+#[cfg(feature = "png")]
+mod still;
+mod vector;
+
+#[cfg(feature = "animation")]
+pub use animation::{
+    export_animation, export_previews, gif_frame_delay, preview_file_name, write_apng, write_gif,
+    AnimationFormat, Palette, MAX_PALETTE_COLORS,
+};
+pub use atlas::{atlas_metadata_path, AtlasGlyph, GlyphAtlas, ATLAS_PADDING, MAX_ATLAS_SIZE};
+pub use sequence::{FrameOptions, FrameSequence};
+#[cfg(feature = "png")]
+pub use still::{encode_png, export_png_sequence, frame_file_name, write_png};
+pub use vector::export_svg_sequence;
+
+use crate::fonts::FontError;
+use std::fmt;
+
+/// Errors that can occur while exporting frames
+#[derive(Debug)]
+pub enum ExportError {
+    /// An output file or directory could not be written
+    Io(std::io::Error),
+    /// The font could not be loaded
+    Font(FontError),
+    /// A caller-supplied setup step failed (its message)
+    Setup(String),
+    /// The frame size is beyond what the format can store (width, height)
+    TooLarge(u32, u32),
+    /// Glyphs don't fit in a glyph atlas of the maximum size (glyph count, maximum size)
+    AtlasFull(usize, u32),
+    /// A PNG could not be encoded
+    #[cfg(feature = "png")]
+    Png(png::EncodingError),
+    /// A GIF could not be encoded
+    #[cfg(feature = "animation")]
+    Gif(gif::EncodingError),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io(err) => write!(f, "failed to write output: {}", err),
+            ExportError::Font(err) => write!(f, "{}", err),
+            ExportError::Setup(message) => write!(f, "{}", message),
+            ExportError::TooLarge(width, height) => {
+                write!(
+                    f,
+                    "{}x{} frames are too large for this format",
+                    width, height
+                )
+            }
+            ExportError::AtlasFull(glyphs, size) => {
+                write!(
+                    f,
+                    "{} glyphs don't fit in a {}x{} atlas; try a smaller font size",
+                    glyphs, size, size
+                )
+            }
+            #[cfg(feature = "png")]
+            ExportError::Png(err) => write!(f, "failed to encode PNG: {}", err),
+            #[cfg(feature = "animation")]
+            ExportError::Gif(err) => write!(f, "failed to encode GIF: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExportError::Io(err) => Some(err),
+            ExportError::Font(err) => Some(err),
+            ExportError::Setup(_) | ExportError::TooLarge(..) | ExportError::AtlasFull(..) => None,
+            #[cfg(feature = "png")]
+            ExportError::Png(err) => Some(err),
+            #[cfg(feature = "animation")]
+            ExportError::Gif(err) => Some(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(err: std::io::Error) -> Self {
+        ExportError::Io(err)
+    }
+}
+
+impl From<FontError> for ExportError {
+    fn from(err: FontError) -> Self {
+        ExportError::Font(err)
+    }
+}
+
+#[cfg(feature = "png")]
+impl From<png::EncodingError> for ExportError {
+    fn from(err: png::EncodingError) -> Self {
+        ExportError::Png(err)
+    }
+}
+
+#[cfg(feature = "animation")]
+impl From<gif::EncodingError> for ExportError {
+    fn from(err: gif::EncodingError) -> Self {
+        ExportError::Gif(err)
+    }
+}
@@ -0,0 +1,192 @@
+//! Fixed-rate frame sequences rendered with simulated time
+
+use crate::rendering::{FrameStats, Framebuffer, GlyphRasterizer, SoftwareRenderer};
+use crate::{MatrixRain, ScreenSaverConfig};
+
+/// Resolution, length and timing of an exported sequence
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameOptions {
+    /// Frame width in pixels
+    pub width: u32,
+    /// Frame height in pixels
+    pub height: u32,
+    /// Number of frames to render
+    pub frames: u32,
+    /// Simulated frames per second
+    pub fps: f32,
+    /// Seed for the engine's random number generator
+    pub seed: u64,
+    /// Seconds simulated before the first frame, so the screen is already full of rain
+    pub warmup: f32,
+}
+
+impl Default for FrameOptions {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            frames: 60,
+            fps: 60.0,
+            seed: 0,
+            warmup: 3.0,
+        }
+    }
+}
+
+impl FrameOptions {
+    /// Simulated time between frames in seconds
+    pub fn frame_interval(&self) -> f32 {
+        1.0 / self.fps.max(1.0)
+    }
+}
+
+/// Renders a seeded engine frame by frame into a software framebuffer
+///
+/// The same config, options and font always produce the same frames.
+pub struct FrameSequence<R: GlyphRasterizer> {
+    matrix: MatrixRain,
+    renderer: SoftwareRenderer<R>,
+    options: FrameOptions,
+    rendered: u32,
+    /// Glyph and batch counts of each rendered frame
+    stats: Vec<FrameStats>,
+}
+
+impl<R: GlyphRasterizer> FrameSequence<R> {
+    /// Set up a sequence (the config's screen size is replaced by the options')
+    pub fn new(config: ScreenSaverConfig, options: &FrameOptions, rasterizer: R) -> Self {
+        let config = ScreenSaverConfig {
+            screen_width: options.width,
+            screen_height: options.height,
+            ..config
+        };
+        let (blend_mode, bloom, crt) = (config.blend_mode, config.bloom, config.crt);
+        let mut matrix = MatrixRain::with_seed(config, options.seed);
+        matrix.advance(options.warmup);
+
+        Self {
+            matrix,
+            renderer: SoftwareRenderer::new(options.width, options.height, rasterizer)
+                .with_blend_mode(blend_mode)
+                .with_bloom(bloom)
+                .with_crt(crt),
+            options: *options,
+            rendered: 0,
+            stats: Vec::new(),
+        }
+    }
+
+    /// Render the next frame (None once every frame has been rendered)
+    pub fn next_frame(&mut self) -> Option<&Framebuffer> {
+        if self.rendered >= self.options.frames {
+            return None;
+        }
+        if self.rendered > 0 {
+            self.matrix.advance(self.options.frame_interval());
+        }
+        let stats = self.matrix.render(&mut self.renderer);
+        self.stats.push(stats);
+        self.rendered += 1;
+        Some(self.renderer.framebuffer())
+    }
+
+    /// Number of frames rendered so far
+    pub fn frames_rendered(&self) -> u32 {
+        self.rendered
+    }
+
+    /// Glyph and batch counts of each frame rendered so far
+    pub fn frame_stats(&self) -> &[FrameStats] {
+        &self.stats
+    }
+
+    /// Total number of frames in the sequence
+    pub fn len(&self) -> u32 {
+        self.options.frames
+    }
+
+    /// Check whether the sequence has no frames
+    pub fn is_empty(&self) -> bool {
+        self.options.frames == 0
+    }
+
+    /// The options the sequence was created with
+    pub fn options(&self) -> &FrameOptions {
+        &self.options
+    }
+
+    /// The engine being rendered
+    pub fn matrix(&self) -> &MatrixRain {
+        &self.matrix
+    }
+}
+
+/// File name of a frame in a sequence, zero-padded so names sort in order
+pub(super) fn numbered_file_name(index: u32, total: u32, extension: &str) -> String {
+    let digits = total.saturating_sub(1).max(1).ilog10() as usize + 1;
+    format!(
+        "frame_{:0width$}.{}",
+        index,
+        extension,
+        width = digits.max(4)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::TestRasterizer;
+
+    fn options() -> FrameOptions {
+        FrameOptions {
+            width: 96,
+            height: 64,
+            frames: 3,
+            fps: 30.0,
+            seed: 11,
+            warmup: 2.0,
+        }
+    }
+
+    #[test]
+    fn test_renders_requested_frames() {
+        let mut sequence = FrameSequence::new(
+            ScreenSaverConfig::default(),
+            &options(),
+            TestRasterizer::blocks(),
+        );
+        assert_eq!(sequence.len(), 3);
+        let first = sequence.next_frame().unwrap().clone();
+        assert_eq!((first.width(), first.height()), (96, 64));
+        assert!(sequence.next_frame().is_some());
+        assert!(sequence.next_frame().is_some());
+        assert!(sequence.next_frame().is_none());
+        assert_eq!(sequence.frames_rendered(), 3);
+        assert_eq!(sequence.frame_stats().len(), 3);
+        assert!(sequence
+            .frame_stats()
+            .iter()
+            .all(|stats| stats.batches > 0 && stats.batches <= stats.glyphs));
+        // Warmup plus two frame intervals of simulated time (in whole updates)
+        assert!((sequence.matrix().time() - 2.05).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_same_seed_same_frames() {
+        let render = |seed| {
+            let options = FrameOptions { seed, ..options() };
+            let mut sequence = FrameSequence::new(
+                ScreenSaverConfig::default(),
+                &options,
+                TestRasterizer::blocks(),
+            );
+            let mut frames = Vec::new();
+            while let Some(frame) = sequence.next_frame() {
+                frames.push(frame.to_rgba8());
+            }
+            frames
+        };
+        assert_eq!(render(11), render(11));
+        assert_ne!(render(11), render(12));
+    }
+}
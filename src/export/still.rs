@@ -0,0 +1,109 @@
+//! PNG output of single frames and numbered sequences
+
+use super::sequence::numbered_file_name;
+use super::{ExportError, FrameSequence};
+use crate::rendering::{Framebuffer, GlyphRasterizer};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Encode a frame as an 8-bit sRGB RGBA PNG
+pub fn encode_png(framebuffer: &Framebuffer, writer: impl Write) -> Result<(), ExportError> {
+    let mut encoder = png::Encoder::new(writer, framebuffer.width(), framebuffer.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&framebuffer.to_rgba8())?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Write a frame to a PNG file
+pub fn write_png(framebuffer: &Framebuffer, path: impl AsRef<Path>) -> Result<(), ExportError> {
+    encode_png(framebuffer, BufWriter::new(File::create(path)?))
+}
+
+/// File name of a frame in a sequence, zero-padded so names sort in order
+pub fn frame_file_name(index: u32, total: u32) -> String {
+    numbered_file_name(index, total, "png")
+}
+
+/// Render every remaining frame of a sequence to numbered PNGs in a directory
+/// The directory is created if needed. `progress` is called after each file is written.
+pub fn export_png_sequence<R: GlyphRasterizer>(
+    sequence: &mut FrameSequence<R>,
+    dir: impl AsRef<Path>,
+    mut progress: impl FnMut(u32, &Path),
+) -> Result<Vec<PathBuf>, ExportError> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let total = sequence.len();
+    let mut paths = Vec::new();
+    for index in sequence.frames_rendered()..total {
+        let Some(frame) = sequence.next_frame() else {
+            break;
+        };
+        let path = dir.join(frame_file_name(index, total));
+        write_png(frame, &path)?;
+        progress(index, &path);
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::FrameOptions;
+    use crate::rendering::TestRasterizer;
+    use crate::{Color, ScreenSaverConfig};
+
+    #[test]
+    fn test_frame_file_names() {
+        assert_eq!(frame_file_name(0, 10), "frame_0000.png");
+        assert_eq!(frame_file_name(599, 600), "frame_0599.png");
+        assert_eq!(frame_file_name(42, 100_000), "frame_00042.png");
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let mut framebuffer = Framebuffer::new(3, 2);
+        framebuffer.clear(Color::MATRIX_GREEN);
+        let mut bytes = Vec::new();
+        encode_png(&framebuffer, &mut bytes).unwrap();
+
+        let decoder = png::Decoder::new(bytes.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (3, 2));
+        assert_eq!(&pixels[..4], &[0, 255, 70, 255]);
+    }
+
+    #[test]
+    fn test_export_sequence() {
+        let dir = std::env::temp_dir().join(format!("matrix-rain-frames-{}", std::process::id()));
+        let options = FrameOptions {
+            width: 32,
+            height: 24,
+            frames: 3,
+            ..Default::default()
+        };
+        let mut sequence = FrameSequence::new(
+            ScreenSaverConfig::default(),
+            &options,
+            TestRasterizer::empty(),
+        );
+        let mut reported = Vec::new();
+        let paths =
+            export_png_sequence(&mut sequence, &dir, |index, _| reported.push(index)).unwrap();
+
+        assert_eq!(reported, vec![0, 1, 2]);
+        assert_eq!(paths.len(), 3);
+        assert!(paths.iter().all(|path| path.exists()));
+        assert!(paths[2].ends_with("frame_0002.png"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,80 @@
+//! SVG output of numbered sequences
+
+use super::sequence::numbered_file_name;
+use super::{ExportError, FrameOptions};
+use crate::rendering::SvgRenderer;
+use crate::{MatrixRain, ScreenSaverConfig};
+use std::path::{Path, PathBuf};
+
+/// Render a seeded sequence to numbered SVG files in a directory
+///
+/// Timing matches `FrameSequence`, so frame N shows the same rain as the PNG
+/// export. The renderer (and the config's screen size) is set to the options'
+/// frame size. `progress` is called after each file is written.
+pub fn export_svg_sequence(
+    config: ScreenSaverConfig,
+    options: &FrameOptions,
+    mut renderer: SvgRenderer,
+    dir: impl AsRef<Path>,
+    mut progress: impl FnMut(u32, &Path),
+) -> Result<Vec<PathBuf>, ExportError> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let config = ScreenSaverConfig {
+        screen_width: options.width,
+        screen_height: options.height,
+        ..config
+    };
+    renderer.resize(options.width, options.height);
+    let mut matrix = MatrixRain::with_seed(config, options.seed);
+    matrix.advance(options.warmup);
+
+    let mut paths = Vec::new();
+    for index in 0..options.frames {
+        if index > 0 {
+            matrix.advance(options.frame_interval());
+        }
+        matrix.render(&mut renderer);
+        let path = dir.join(numbered_file_name(index, options.frames, "svg"));
+        std::fs::write(&path, renderer.document())?;
+        progress(index, &path);
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_svg_sequence() {
+        let dir = std::env::temp_dir().join(format!("matrix-rain-svg-{}", std::process::id()));
+        let options = FrameOptions {
+            width: 160,
+            height: 120,
+            frames: 3,
+            fps: 5.0,
+            ..Default::default()
+        };
+        let mut reported = Vec::new();
+        let paths = export_svg_sequence(
+            ScreenSaverConfig::default(),
+            &options,
+            SvgRenderer::new(1, 1),
+            &dir,
+            |index, _| reported.push(index),
+        )
+        .unwrap();
+
+        assert_eq!(reported, [0, 1, 2]);
+        assert_eq!(paths[2].file_name().unwrap(), "frame_0002.svg");
+        let first = std::fs::read_to_string(&paths[0]).unwrap();
+        assert!(first.contains("viewBox=\"0 0 160 120\""));
+        assert!(first.contains("<text"));
+        assert_ne!(first, std::fs::read_to_string(&paths[1]).unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,13 +2,30 @@
 //!
 //! This module provides C-compatible exports that can be called from Swift/Objective-C
 
-use crate::{CharacterSet, ColorScheme, MatrixRain, RainSpeed, ScreenSaverConfig};
+use crate::config::{
+    AccessiblePalette, BackgroundConfig, BackgroundFill, ContrastReport, Gradient, GradientStop,
+    ImportedPalette, Interpolation, LeaderStyle, PaletteAnimation, SequenceConfig, SequenceSource,
+    Theme, ThemeLibrary,
+};
+use crate::engine::DeltaTracker;
+use crate::rendering::{Backdrop, BlendMode, Color, RenderChar, MAX_GLYPH_BYTES};
+use crate::{
+    CharacterSet, ColorScheme, CustomColors, Glyph, GlyphTransformConfig, MatrixRain, RainSpeed,
+    ScreenSaverConfig,
+};
+use std::ffi::{c_char, CStr, CString};
 use std::ptr;
+use std::sync::OnceLock;
 
 /// Opaque pointer to MatrixRain engine (hides implementation details from C/Swift)
 pub struct MatrixRainHandle {
     engine: MatrixRain,
     render_cache: Vec<RenderCharFFI>,
+    hdr_render_cache: Vec<RenderCharHdrFFI>,
+    batch_cache: Vec<RenderBatchFFI>,
+    delta: DeltaTracker,
+    delta_cache: Vec<CellDeltaFFI>,
+    dirty_rect_cache: Vec<DirtyRectFFI>,
 }
 
 /// Create a new Matrix Rain engine
@@ -23,43 +40,11 @@ pub unsafe extern "C" fn matrix_rain_new(
     color: u8,
     speed: u8,
 ) -> *mut MatrixRainHandle {
-    let character_set = match charset {
-        0 => CharacterSet::Japanese,
-        1 => CharacterSet::Hindi,
-        2 => CharacterSet::Tamil,
-        3 => CharacterSet::Sinhala,
-        4 => CharacterSet::Korean,
-        5 => CharacterSet::Jawi,
-        6 => CharacterSet::Arabic,
-        7 => CharacterSet::Hebrew,
-        8 => CharacterSet::Thai,
-        9 => CharacterSet::Mixed,
-        _ => CharacterSet::Japanese,
-    };
+    let character_set = CharacterSet::from_index(charset).unwrap_or_default();
 
-    let color_scheme = match color {
-        0 => ColorScheme::MatrixGreen,
-        1 => ColorScheme::DarkBlue,
-        2 => ColorScheme::Purple,
-        3 => ColorScheme::Orange,
-        4 => ColorScheme::Red,
-        5 => ColorScheme::Cyan,
-        6 => ColorScheme::Yellow,
-        7 => ColorScheme::Pink,
-        8 => ColorScheme::White,
-        9 => ColorScheme::LimeGreen,
-        10 => ColorScheme::Teal,
-        _ => ColorScheme::MatrixGreen,
-    };
+    let color_scheme = ColorScheme::from_index(color).unwrap_or_default();
 
-    let rain_speed = match speed {
-        0 => RainSpeed::VerySlow,
-        1 => RainSpeed::Slow,
-        2 => RainSpeed::Medium,
-        3 => RainSpeed::Fast,
-        4 => RainSpeed::VeryFast,
-        _ => RainSpeed::Medium,
-    };
+    let rain_speed = RainSpeed::from_index(speed).unwrap_or_default();
 
     let config = ScreenSaverConfig::new(character_set, color_scheme, rain_speed, width, height);
     let engine = MatrixRain::new(config);
@@ -67,6 +52,11 @@ pub unsafe extern "C" fn matrix_rain_new(
     Box::into_raw(Box::new(MatrixRainHandle {
         engine,
         render_cache: Vec::new(),
+        hdr_render_cache: Vec::new(),
+        batch_cache: Vec::new(),
+        delta: DeltaTracker::new(),
+        delta_cache: Vec::new(),
+        dirty_rect_cache: Vec::new(),
     }))
 }
 
@@ -87,7 +77,7 @@ pub unsafe extern "C" fn matrix_rain_update(handle: *mut MatrixRainHandle) {
 /// Represents a character to render (C-compatible struct)
 #[repr(C)]
 pub struct RenderCharFFI {
-    pub character: u32, // Unicode codepoint
+    pub character: u32, // First Unicode codepoint of the glyph
     pub x: f32,
     pub y: f32,
     pub r: u8,
@@ -95,6 +85,33 @@ pub struct RenderCharFFI {
     pub b: u8,
     pub a: f32,
     pub font_size: f32,
+    pub transform: u8, // GlyphTransform bit flags (mirror x/y, rotate 90/180)
+    pub glyph_len: u8, // Number of UTF-8 bytes in glyph_utf8
+    pub glyph_utf8: [u8; MAX_GLYPH_BYTES], // Full glyph (may be a grapheme cluster), zero padded
+    pub leader: u8,    // 1 for leader (head) glyphs
+    pub glow: f32,     // Glow/halo intensity (0.0 = none)
+    pub glyph_index: i32, // Index in the glyph pool and atlases built from it, -1 if outside it
+}
+
+impl From<&RenderChar> for RenderCharFFI {
+    fn from(render_char: &RenderChar) -> Self {
+        RenderCharFFI {
+            character: render_char.character.first_char() as u32,
+            x: render_char.x,
+            y: render_char.y,
+            r: render_char.color.r,
+            g: render_char.color.g,
+            b: render_char.color.b,
+            a: render_char.color.a,
+            font_size: render_char.font_size,
+            transform: render_char.transform.bits(),
+            glyph_len: render_char.character.len() as u8,
+            glyph_utf8: render_char.character.raw_bytes(),
+            leader: render_char.leader as u8,
+            glow: render_char.glow,
+            glyph_index: render_char.glyph_index.map_or(-1, |index| index as i32),
+        }
+    }
 }
 
 /// Get render data for drawing
@@ -123,21 +140,235 @@ pub unsafe extern "C" fn matrix_rain_get_render_chars(
     handle.render_cache.clear();
     handle.render_cache.reserve(render_data.len());
 
+    handle
+        .render_cache
+        .extend(render_data.iter().map(RenderCharFFI::from));
+
+    *out_count = handle.render_cache.len();
+    handle.render_cache.as_ptr()
+}
+
+/// A run of render chars that share a drawing style (C-compatible struct)
+#[repr(C)]
+pub struct RenderBatchFFI {
+    pub font_size: f32,
+    pub r: u8, // Batch colour, quantized so the whole batch can be drawn in it
+    pub g: u8,
+    pub b: u8,
+    pub a: f32,
+    pub glow: f32,    // Glow/halo intensity shared by the batch (0.0 = none)
+    pub start: usize, // Index of the batch's first glyph in the render chars array
+    pub count: usize, // Number of glyphs in the batch
+}
+
+/// Get render data grouped into batches by font size, glow and quantized colour
+/// Hosts can set up text attributes once per batch. The glyphs are written to the
+/// same array `matrix_rain_get_render_chars` returns, ordered batch by batch, with the
+/// background layer's batches first. The batch count is the number of batches per frame.
+///
+/// # Safety
+/// - `handle` must be a valid pointer returned from `matrix_rain_new`
+/// - `out_count`, `out_chars` and `out_char_count` must be valid pointers to write to
+/// - The returned pointers are valid until the next call to this function,
+///   `matrix_rain_get_render_chars` or `matrix_rain_destroy`
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_get_render_batches(
+    handle: *mut MatrixRainHandle,
+    out_count: *mut usize,
+    out_chars: *mut *const RenderCharFFI,
+    out_char_count: *mut usize,
+) -> *const RenderBatchFFI {
+    if handle.is_null() || out_count.is_null() || out_chars.is_null() || out_char_count.is_null() {
+        return ptr::null();
+    }
+
+    let handle = &mut *handle;
+    let batches = handle.engine.get_render_batches();
+
+    handle.render_cache.clear();
+    handle.batch_cache.clear();
+    for batch in &batches {
+        handle.batch_cache.push(RenderBatchFFI {
+            font_size: batch.font_size,
+            r: batch.color.r,
+            g: batch.color.g,
+            b: batch.color.b,
+            a: batch.color.a,
+            glow: batch.glow,
+            start: handle.render_cache.len(),
+            count: batch.len(),
+        });
+        handle
+            .render_cache
+            .extend(batch.chars.iter().map(RenderCharFFI::from));
+    }
+
+    *out_chars = handle.render_cache.as_ptr();
+    *out_char_count = handle.render_cache.len();
+    *out_count = handle.batch_cache.len();
+    handle.batch_cache.as_ptr()
+}
+
+/// A character to render with extended-range colour (C-compatible struct)
+/// Same as `RenderCharFFI` except the colour is linear extended sRGB, where
+/// 1.0 is SDR white and leaders may go above it.
+#[repr(C)]
+pub struct RenderCharHdrFFI {
+    pub character: u32, // First Unicode codepoint of the glyph
+    pub x: f32,
+    pub y: f32,
+    pub r: f32, // Linear, may exceed 1.0
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+    pub font_size: f32,
+    pub transform: u8, // GlyphTransform bit flags (mirror x/y, rotate 90/180)
+    pub glyph_len: u8, // Number of UTF-8 bytes in glyph_utf8
+    pub glyph_utf8: [u8; MAX_GLYPH_BYTES], // Full glyph (may be a grapheme cluster), zero padded
+    pub leader: u8,    // 1 for leader (head) glyphs
+    pub glow: f32,     // Glow/halo intensity (0.0 = none), drawn in the glyph's colour
+    pub glyph_index: i32, // Index in the glyph pool and atlases built from it, -1 if outside it
+}
+
+/// Get render data with extended-range colours for HDR/EDR-capable hosts
+/// Returns a pointer to an array of RenderCharHdrFFI and sets the count
+///
+/// # Safety
+/// - `handle` must be a valid pointer returned from `matrix_rain_new`
+/// - `out_count` must be a valid pointer to write the count
+/// - The returned pointer is valid until the next call to this function or `matrix_rain_destroy`
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_get_render_chars_hdr(
+    handle: *mut MatrixRainHandle,
+    out_count: *mut usize,
+) -> *const RenderCharHdrFFI {
+    if handle.is_null() || out_count.is_null() {
+        return ptr::null();
+    }
+
+    let handle = &mut *handle;
+    let render_data = handle.engine.get_render_data();
+
+    handle.hdr_render_cache.clear();
+    handle.hdr_render_cache.reserve(render_data.len());
+
     for render_char in render_data {
-        handle.render_cache.push(RenderCharFFI {
-            character: render_char.character as u32,
+        let color = render_char.extended_color();
+        handle.hdr_render_cache.push(RenderCharHdrFFI {
+            character: render_char.character.first_char() as u32,
             x: render_char.x,
             y: render_char.y,
-            r: render_char.color.r,
-            g: render_char.color.g,
-            b: render_char.color.b,
-            a: render_char.color.a,
+            r: color.r,
+            g: color.g,
+            b: color.b,
+            a: color.a,
             font_size: render_char.font_size,
+            transform: render_char.transform.bits(),
+            glyph_len: render_char.character.len() as u8,
+            glyph_utf8: render_char.character.raw_bytes(),
+            leader: render_char.leader as u8,
+            glow: render_char.glow,
+            glyph_index: render_char.glyph_index.map_or(-1, |index| index as i32),
         });
     }
 
-    *out_count = handle.render_cache.len();
-    handle.render_cache.as_ptr()
+    *out_count = handle.hdr_render_cache.len();
+    handle.hdr_render_cache.as_ptr()
+}
+
+/// A cell that changed since the previous delta (C-compatible struct)
+#[repr(C)]
+pub struct CellDeltaFFI {
+    pub layer: u8,  // 0 = background, 1 = foreground
+    pub change: u8, // 0 = added, 1 = removed, 2 = recoloured, 3 = re-glyphed, 4 = restyled
+    pub column: u32,
+    pub row: i32,
+    pub glyph: RenderCharFFI, // The cell's glyph now, or the one it last had if removed
+}
+
+/// A region to redraw, in the same coordinates as render char positions (C-compatible struct)
+#[repr(C)]
+pub struct DirtyRectFFI {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// What changed since the previous delta (C-compatible struct)
+#[repr(C)]
+pub struct FrameDeltaFFI {
+    pub full_frame: u8, // 1 if the whole screen must be redrawn
+    pub changes: *const CellDeltaFFI,
+    pub change_count: usize,
+    pub dirty_rects: *const DirtyRectFFI, // Just the screen when `full_frame` is set
+    pub dirty_rect_count: usize,
+}
+
+/// Get what changed since the previous call, so hosts can redraw only the dirty rects
+/// The first call, and any after a resize, backdrop change or
+/// `matrix_rain_invalidate_delta`, reports a full frame, as do frames where most of
+/// the screen changed. Redrawing a dirty rect means repainting the background there
+/// and every glyph from `matrix_rain_get_render_chars` that overlaps it.
+/// Returns false if a pointer is null
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+/// - `out_delta` must be a valid pointer to write to
+/// - The arrays in `out_delta` are valid until the next call to this function or `matrix_rain_destroy`
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_get_delta(
+    handle: *mut MatrixRainHandle,
+    out_delta: *mut FrameDeltaFFI,
+) -> bool {
+    if handle.is_null() || out_delta.is_null() {
+        return false;
+    }
+
+    let handle = &mut *handle;
+    let delta = handle.delta.diff(&handle.engine);
+
+    handle.delta_cache.clear();
+    handle
+        .delta_cache
+        .extend(delta.changes.iter().map(|change| CellDeltaFFI {
+            layer: change.key.layer.index(),
+            change: change.change.index(),
+            column: change.key.column,
+            row: change.key.row,
+            glyph: RenderCharFFI::from(&change.render_char),
+        }));
+    handle.dirty_rect_cache.clear();
+    handle
+        .dirty_rect_cache
+        .extend(delta.dirty_rects.iter().map(|rect| DirtyRectFFI {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+        }));
+
+    *out_delta = FrameDeltaFFI {
+        full_frame: delta.full_frame as u8,
+        changes: handle.delta_cache.as_ptr(),
+        change_count: handle.delta_cache.len(),
+        dirty_rects: handle.dirty_rect_cache.as_ptr(),
+        dirty_rect_count: handle.dirty_rect_cache.len(),
+    };
+    true
+}
+
+/// Make the next `matrix_rain_get_delta` report a full frame
+/// Call this when the host's drawing was lost (e.g. the view was resized or hidden).
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_invalidate_delta(handle: *mut MatrixRainHandle) {
+    if handle.is_null() {
+        return;
+    }
+    (*handle).delta.invalidate();
 }
 
 /// Update the configuration
@@ -157,125 +388,993 @@ pub unsafe extern "C" fn matrix_rain_set_config(
         return;
     }
 
-    let character_set = match charset {
-        0 => CharacterSet::Japanese,
-        1 => CharacterSet::Hindi,
-        2 => CharacterSet::Tamil,
-        3 => CharacterSet::Sinhala,
-        4 => CharacterSet::Korean,
-        5 => CharacterSet::Jawi,
-        6 => CharacterSet::Arabic,
-        7 => CharacterSet::Hebrew,
-        8 => CharacterSet::Thai,
-        9 => CharacterSet::Mixed,
-        _ => CharacterSet::Japanese,
+    let character_set = CharacterSet::from_index(charset).unwrap_or_default();
+
+    let rain_speed = RainSpeed::from_index(speed).unwrap_or_default();
+
+    let handle = &mut *handle;
+
+    // An index past the presets keeps a scheme set by `matrix_rain_set_custom_colors`
+    let color_scheme = ColorScheme::from_index(color).unwrap_or_else(|| {
+        match handle.engine.config().color_scheme {
+            custom @ ColorScheme::Custom(_) => custom,
+            _ => ColorScheme::default(),
+        }
+    });
+
+    // Keep settings that have their own setters (transforms, sequence, ...)
+    let config = ScreenSaverConfig {
+        character_set,
+        color_scheme,
+        speed: rain_speed,
+        screen_width: width,
+        screen_height: height,
+        ..handle.engine.config().clone()
     };
+    handle.engine.set_config(config);
+}
+
+/// Use explicit colours instead of a preset scheme
+/// Each colour is packed as 0xRRGGBB.
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_set_custom_colors(
+    handle: *mut MatrixRainHandle,
+    leader: u32,
+    primary: u32,
+    secondary: u32,
+    tertiary: u32,
+    background: u32,
+) {
+    if handle.is_null() {
+        return;
+    }
+
+    let handle = &mut *handle;
+    let mut config = handle.engine.config().clone();
+    config.color_scheme = ColorScheme::Custom(CustomColors {
+        leader: unpack_rgb(leader),
+        primary: unpack_rgb(primary),
+        secondary: unpack_rgb(secondary),
+        tertiary: unpack_rgb(tertiary),
+        background: unpack_rgb(background),
+    });
+    handle.engine.set_config(config);
+}
+
+/// Colour the trail with a gradient instead of the scheme's stepped colours
+/// `colors` (packed 0xRRGGBB) and `positions` (0.0 = head, 1.0 = tail) hold `count` stops each.
+/// `interpolation`: 0 = linear RGB, 1 = OKLab, 2 = step.
+/// Pass a `count` of 0 to return to the scheme's colours.
+/// Returns true if a gradient is active afterwards
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+/// - `colors` and `positions` must each point to `count` values (may be null if `count` is 0)
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_set_gradient(
+    handle: *mut MatrixRainHandle,
+    colors: *const u32,
+    positions: *const f32,
+    count: usize,
+    interpolation: u8,
+) -> bool {
+    if handle.is_null() || (count > 0 && (colors.is_null() || positions.is_null())) {
+        return false;
+    }
 
-    let color_scheme = match color {
-        0 => ColorScheme::MatrixGreen,
-        1 => ColorScheme::DarkBlue,
-        2 => ColorScheme::Purple,
-        3 => ColorScheme::Orange,
-        4 => ColorScheme::Red,
-        5 => ColorScheme::Cyan,
-        6 => ColorScheme::Yellow,
-        7 => ColorScheme::Pink,
-        8 => ColorScheme::White,
-        9 => ColorScheme::LimeGreen,
-        10 => ColorScheme::Teal,
-        _ => ColorScheme::MatrixGreen,
+    let interpolation = match interpolation {
+        0 => Interpolation::Linear,
+        2 => Interpolation::Step,
+        _ => Interpolation::OkLab,
     };
 
-    let rain_speed = match speed {
-        0 => RainSpeed::VerySlow,
-        1 => RainSpeed::Slow,
-        2 => RainSpeed::Medium,
-        3 => RainSpeed::Fast,
-        4 => RainSpeed::VeryFast,
-        _ => RainSpeed::Medium,
+    let stops = if count == 0 {
+        Vec::new()
+    } else {
+        let colors = std::slice::from_raw_parts(colors, count);
+        let positions = std::slice::from_raw_parts(positions, count);
+        colors
+            .iter()
+            .zip(positions)
+            .map(|(&color, &position)| GradientStop::new(position, unpack_rgb(color)))
+            .collect()
     };
 
     let handle = &mut *handle;
-    let config = ScreenSaverConfig::new(character_set, color_scheme, rain_speed, width, height);
+    let mut config = handle.engine.config().clone();
+    config.gradient = Gradient::new(stops, interpolation);
     handle.engine.set_config(config);
+    handle.engine.config().gradient.is_some()
 }
 
-/// Destroy the Matrix Rain engine and free memory
+/// Animate the palette over time and across columns
+/// - `hue_cycle_period`: seconds per full rainbow rotation (0 disables)
+/// - `column_hue_offset`: hue degrees between neighbouring columns
+/// - `breathe_scheme`: preset index to breathe towards (any index past the presets disables)
+/// - `breathe_period`: seconds per breath (0 disables)
 ///
 /// # Safety
-/// - `handle` must be a valid pointer returned from `matrix_rain_new`
-/// - `handle` must not be used after this call
-/// - `handle` can be null (no-op in that case)
+/// - `handle` must be a valid pointer
 #[no_mangle]
-pub unsafe extern "C" fn matrix_rain_destroy(handle: *mut MatrixRainHandle) {
-    if !handle.is_null() {
-        let _ = Box::from_raw(handle);
+pub unsafe extern "C" fn matrix_rain_set_palette_animation(
+    handle: *mut MatrixRainHandle,
+    hue_cycle_period: f32,
+    column_hue_offset: f32,
+    breathe_scheme: u8,
+    breathe_period: f32,
+) {
+    if handle.is_null() {
+        return;
     }
+
+    let handle = &mut *handle;
+    let mut config = handle.engine.config().clone();
+    config.palette_animation = PaletteAnimation {
+        hue_cycle_period: hue_cycle_period.max(0.0),
+        column_hue_offset,
+        breathe_with: ColorScheme::from_index(breathe_scheme),
+        breathe_period: breathe_period.max(0.0),
+    };
+    handle.engine.set_config(config);
 }
 
-/// Get the update interval in milliseconds for the given speed
+/// Style the head of each foreground trail
+/// - `enabled`: false draws heads in the trail colours
+/// - `color`: leader colour packed as 0xRRGGBB, or any value above 0xFFFFFF for the scheme's leader
+/// - `head_glyphs`: number of glyphs drawn in the leader colour, or 0 for the classic
+///   rule (the first 5% of the trail)
+/// - `glow`: halo intensity reported on leader glyphs (0.0 = none)
+///
+/// # Safety
+/// - `handle` must be a valid pointer
 #[no_mangle]
-pub extern "C" fn matrix_rain_get_update_interval_ms(speed: u8) -> u64 {
-    let rain_speed = match speed {
-        0 => RainSpeed::VerySlow,
-        1 => RainSpeed::Slow,
-        2 => RainSpeed::Medium,
-        3 => RainSpeed::Fast,
-        4 => RainSpeed::VeryFast,
-        _ => RainSpeed::Medium,
+pub unsafe extern "C" fn matrix_rain_set_leader_style(
+    handle: *mut MatrixRainHandle,
+    enabled: bool,
+    color: u32,
+    head_glyphs: u32,
+    glow: f32,
+) {
+    if handle.is_null() {
+        return;
+    }
+
+    let handle = &mut *handle;
+    let mut config = handle.engine.config().clone();
+    config.leader = LeaderStyle {
+        enabled,
+        color: (color <= 0xFFFFFF).then(|| unpack_rgb(color)),
+        head_glyphs: (head_glyphs > 0).then_some(head_glyphs as usize),
+        glow: glow.max(0.0),
+        ..config.leader
     };
-    rain_speed.update_interval_ms()
+    handle.engine.set_config(config);
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_ffi_lifecycle() {
-        unsafe {
-            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
-            assert!(!handle.is_null());
+/// Set how far leaders exceed SDR white in extended-range output (1.0 = no boost)
+/// Only affects `matrix_rain_get_render_chars_hdr`; SDR colours are unchanged.
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_set_leader_brightness(
+    handle: *mut MatrixRainHandle,
+    brightness: f32,
+) {
+    if handle.is_null() {
+        return;
+    }
 
-            matrix_rain_update(handle);
-            matrix_rain_update(handle);
+    let handle = &mut *handle;
+    let mut config = handle.engine.config().clone();
+    config.leader.brightness = brightness.max(0.0);
+    handle.engine.set_config(config);
+}
 
-            matrix_rain_destroy(handle);
-        }
+/// Set the animation clock in seconds
+/// The clock otherwise advances by one update interval per `matrix_rain_update`.
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_set_time(handle: *mut MatrixRainHandle, seconds: f32) {
+    if handle.is_null() {
+        return;
     }
+    (*handle).engine.set_time(seconds);
+}
 
-    #[test]
-    fn test_ffi_config_update() {
-        unsafe {
-            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+/// Get the animation clock in seconds
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_get_time(handle: *const MatrixRainHandle) -> f32 {
+    if handle.is_null() {
+        return 0.0;
+    }
+    (*handle).engine.time()
+}
 
-            // Change to Korean, Purple, Fast
-            matrix_rain_set_config(handle, 2560, 1440, 4, 2, 3);
+/// Background description for hosts (C-compatible struct)
+#[repr(C)]
+pub struct BackgroundFFI {
+    /// 0 = solid, 1 = vertical gradient, 2 = radial gradient, 3 = transparent
+    pub kind: u8,
+    /// Solid colour, top of a vertical gradient or centre of a radial one (0xRRGGBB)
+    pub color_a: u32,
+    /// Bottom of a vertical gradient or corners of a radial one (0xRRGGBB, same as `color_a` when solid)
+    pub color_b: u32,
+}
 
-            matrix_rain_destroy(handle);
-        }
+/// Set the background
+/// - `kind`: 0 = solid, 1 = vertical gradient, 2 = radial gradient, 3 = transparent,
+///   anything else follows the color scheme
+/// - `color_a`/`color_b`: colours as described on `BackgroundFFI` (0xRRGGBB)
+/// - `tint`: how much of the scheme's primary colour tints the background (0.0 = none)
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_set_background(
+    handle: *mut MatrixRainHandle,
+    kind: u8,
+    color_a: u32,
+    color_b: u32,
+    tint: f32,
+) {
+    if handle.is_null() {
+        return;
     }
 
-    #[test]
-    fn test_null_handle_safety() {
-        unsafe {
-            matrix_rain_update(ptr::null_mut());
-            matrix_rain_destroy(ptr::null_mut());
-            // Should not crash
-        }
-    }
+    let (a, b) = (unpack_rgb(color_a), unpack_rgb(color_b));
+    let fill = match kind {
+        0 => BackgroundFill::Solid(a),
+        1 => BackgroundFill::Vertical { top: a, bottom: b },
+        2 => BackgroundFill::Radial { center: a, edge: b },
+        3 => BackgroundFill::Transparent,
+        _ => BackgroundFill::Scheme,
+    };
 
-    #[test]
-    fn test_update_interval() {
-        assert_eq!(matrix_rain_get_update_interval_ms(2), 50); // Medium
-        assert_eq!(matrix_rain_get_update_interval_ms(4), 15); // Very fast
+    let handle = &mut *handle;
+    let mut config = handle.engine.config().clone();
+    config.background = BackgroundConfig { fill, tint };
+    handle.engine.set_config(config);
+}
+
+/// Set how glyphs blend and whether blending is baked into the exported colours
+/// - `mode`: 0 = normal, 1 = additive, 2 = screen (anything else is normal)
+/// - `precomposite`: when true, render chars carry opaque colours already blended
+///   over the background and overlapping glyphs, for hosts that draw without blending
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_set_blend_mode(
+    handle: *mut MatrixRainHandle,
+    mode: u8,
+    precomposite: bool,
+) {
+    if handle.is_null() {
+        return;
     }
 
-    #[test]
-    fn test_render_data_export() {
-        unsafe {
-            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
-            assert!(!handle.is_null());
+    let handle = &mut *handle;
+    let mut config = handle.engine.config().clone();
+    config.blend_mode = match mode {
+        1 => BlendMode::Additive,
+        2 => BlendMode::Screen,
+        _ => BlendMode::Normal,
+    };
+    config.precomposite = precomposite;
+    handle.engine.set_config(config);
+}
+
+/// Get the resolved background (scheme colours and tint applied)
+/// Returns false if a pointer is null
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+/// - `out_background` must be a valid pointer to write to
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_get_background(
+    handle: *const MatrixRainHandle,
+    out_background: *mut BackgroundFFI,
+) -> bool {
+    if handle.is_null() || out_background.is_null() {
+        return false;
+    }
+
+    let pack = |color: Color| pack_rgb((color.r, color.g, color.b));
+    *out_background = match (*handle).engine.backdrop() {
+        Backdrop::Solid(color) => BackgroundFFI {
+            kind: 0,
+            color_a: pack(color),
+            color_b: pack(color),
+        },
+        Backdrop::Vertical { top, bottom } => BackgroundFFI {
+            kind: 1,
+            color_a: pack(top),
+            color_b: pack(bottom),
+        },
+        Backdrop::Radial { center, edge } => BackgroundFFI {
+            kind: 2,
+            color_a: pack(center),
+            color_b: pack(edge),
+        },
+        Backdrop::Transparent => BackgroundFFI {
+            kind: 3,
+            color_a: 0,
+            color_b: 0,
+        },
+    };
+    true
+}
+
+/// Get a single background colour, packed as 0xRRGGBB
+/// Gradients report their midpoint; prefer `matrix_rain_get_background`
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_get_background_color(handle: *const MatrixRainHandle) -> u32 {
+    if handle.is_null() {
+        return 0;
+    }
+
+    let color = (*handle).engine.background_color();
+    pack_rgb((color.r, color.g, color.b))
+}
+
+fn unpack_rgb(rgb: u32) -> (u8, u8, u8) {
+    ((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+}
+
+fn pack_rgb((r, g, b): (u8, u8, u8)) -> u32 {
+    ((r as u32) << 16) | ((g as u32) << 8) | b as u32
+}
+
+/// Set the probabilities (0.0-1.0) of mirrored and rotated glyphs
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_set_glyph_transforms(
+    handle: *mut MatrixRainHandle,
+    mirror_x: f32,
+    mirror_y: f32,
+    rotate_90: f32,
+    rotate_180: f32,
+) {
+    if handle.is_null() {
+        return;
+    }
+
+    let handle = &mut *handle;
+    let mut config = handle.engine.config().clone();
+    config.glyph_transforms = GlyphTransformConfig {
+        mirror_x,
+        mirror_y,
+        rotate_90,
+        rotate_180,
+    };
+    handle.engine.set_config(config);
+}
+
+/// Limit spawned glyphs to those present in a font file (.ttf, .otf or .ttc)
+/// Pass a null path to remove the filter.
+/// Returns the number of glyphs left in the pool, or 0 if the font could not be read
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+/// - `font_path` must be null or a valid NUL-terminated UTF-8 string
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_set_coverage_font(
+    handle: *mut MatrixRainHandle,
+    font_path: *const c_char,
+) -> usize {
+    if handle.is_null() {
+        return 0;
+    }
+
+    let handle = &mut *handle;
+    let path = if font_path.is_null() {
+        None
+    } else {
+        let Ok(path) = CStr::from_ptr(font_path).to_str() else {
+            return 0;
+        };
+        Some(path.into())
+    };
+    handle.engine.set_coverage_font(path).unwrap_or(0)
+}
+
+/// Switch columns to spelling text, one entry per line (word lists, names, source code)
+/// Pass a null `text` to return to random glyphs.
+/// Returns true if sequence mode is active afterwards
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+/// - `text` must be null or a valid NUL-terminated UTF-8 string
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_set_sequence_text(
+    handle: *mut MatrixRainHandle,
+    text: *const c_char,
+    mutation_rate: f32,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+
+    let handle = &mut *handle;
+    let mut config = handle.engine.config().clone();
+    config.sequence = if text.is_null() {
+        None
+    } else {
+        let Ok(text) = CStr::from_ptr(text).to_str() else {
+            return false;
+        };
+        Some(SequenceConfig {
+            source: SequenceSource::Text(text.to_string()),
+            mutation_rate,
+        })
+    };
+    handle.engine.set_config(config);
+    handle.engine.is_sequence_mode()
+}
+
+/// Set how often idle columns start a new trail (1.0 = normal, 0.0 lets the rain die out)
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_set_density(handle: *mut MatrixRainHandle, density: f32) {
+    if handle.is_null() {
+        return;
+    }
+
+    let handle = &mut *handle;
+    let mut config = handle.engine.config().clone();
+    config.density = density.max(0.0);
+    handle.engine.set_config(config);
+}
+
+/// Read a path argument (None if null or not UTF-8)
+unsafe fn path_arg(path: *const c_char) -> Option<std::path::PathBuf> {
+    if path.is_null() {
+        return None;
+    }
+    CStr::from_ptr(path).to_str().ok().map(Into::into)
+}
+
+/// Apply a theme file (charset, palette, speed, density, layers and effects)
+/// The screen size, font and sequence settings are kept.
+/// Returns false if the file can't be read or isn't a valid theme
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+/// - `path` must be a valid NUL-terminated UTF-8 string
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_apply_theme_file(
+    handle: *mut MatrixRainHandle,
+    path: *const c_char,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    let Some(theme) = path_arg(path).and_then(|path| Theme::load(path).ok()) else {
+        return false;
+    };
+
+    let handle = &mut *handle;
+    let config = theme.applied_to(handle.engine.config());
+    handle.engine.set_config(config);
+    true
+}
+
+/// Apply a theme from a themes directory by name (case-insensitive) or file name
+/// Returns false if no such theme exists
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+/// - `themes_dir` and `name` must be valid NUL-terminated UTF-8 strings
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_apply_theme(
+    handle: *mut MatrixRainHandle,
+    themes_dir: *const c_char,
+    name: *const c_char,
+) -> bool {
+    if handle.is_null() || name.is_null() {
+        return false;
+    }
+    let (Some(dir), Ok(name)) = (path_arg(themes_dir), CStr::from_ptr(name).to_str()) else {
+        return false;
+    };
+    let Ok(theme) = ThemeLibrary::new(dir).load(name) else {
+        return false;
+    };
+
+    let handle = &mut *handle;
+    let config = theme.applied_to(handle.engine.config());
+    handle.engine.set_config(config);
+    true
+}
+
+/// Save the current look as a theme in a themes directory (created if missing)
+/// `author` may be null.
+/// Returns false if the theme couldn't be written
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+/// - `themes_dir` and `name` must be valid NUL-terminated UTF-8 strings
+/// - `author` must be null or a valid NUL-terminated UTF-8 string
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_save_theme(
+    handle: *const MatrixRainHandle,
+    themes_dir: *const c_char,
+    name: *const c_char,
+    author: *const c_char,
+) -> bool {
+    if handle.is_null() || name.is_null() {
+        return false;
+    }
+    let (Some(dir), Ok(name)) = (path_arg(themes_dir), CStr::from_ptr(name).to_str()) else {
+        return false;
+    };
+    let mut theme = Theme::from_config(name, (*handle).engine.config());
+    if !author.is_null() {
+        let Ok(author) = CStr::from_ptr(author).to_str() else {
+            return false;
+        };
+        theme.author = author.to_string();
+    }
+    ThemeLibrary::new(dir).save(&theme).is_ok()
+}
+
+/// Switch to a custom scheme imported from a palette file
+/// Supports GIMP `.gpl`, base16 `.yaml`/`.yml`, `.ppm` and (with the `png` feature) `.png`.
+/// Returns false if the file can't be imported
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+/// - `path` must be a valid NUL-terminated UTF-8 string
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_import_palette(
+    handle: *mut MatrixRainHandle,
+    path: *const c_char,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    let Some(palette) = path_arg(path).and_then(|path| ImportedPalette::load(path).ok()) else {
+        return false;
+    };
+
+    let handle = &mut *handle;
+    let mut config = handle.engine.config().clone();
+    config.color_scheme = palette.scheme();
+    handle.engine.set_config(config);
+    true
+}
+
+/// Draw custom glyphs instead of the character set
+/// `text` is split into grapheme clusters, so emoji, flags and conjuncts stay whole.
+/// Pass a null or empty `text` to return to the character set.
+/// Returns the number of glyphs in the pool afterwards
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+/// - `text` must be null or a valid NUL-terminated UTF-8 string
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_set_custom_glyphs(
+    handle: *mut MatrixRainHandle,
+    text: *const c_char,
+) -> usize {
+    if handle.is_null() {
+        return 0;
+    }
+
+    let handle = &mut *handle;
+    let mut config = handle.engine.config().clone();
+    config.custom_glyphs = if text.is_null() {
+        None
+    } else {
+        let Ok(text) = CStr::from_ptr(text).to_str() else {
+            return 0;
+        };
+        let glyphs: Vec<Glyph> = Glyph::segment(text)
+            .into_iter()
+            .filter(|glyph| !glyph.as_str().trim().is_empty())
+            .collect();
+        Some(glyphs).filter(|glyphs| !glyphs.is_empty())
+    };
+    handle.engine.set_config(config);
+    handle.engine.glyph_pool().len()
+}
+
+/// Number of glyphs in the active glyph pool
+/// Render data `glyph_index` values, and glyph atlases built from the pool, run from 0 to
+/// this count - 1. The pool changes with the character set, custom glyphs and coverage font.
+///
+/// # Safety
+/// - `handle` must be a valid pointer returned from `matrix_rain_new`
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_glyph_count(handle: *const MatrixRainHandle) -> u32 {
+    if handle.is_null() {
+        return 0;
+    }
+    (*handle).engine.glyph_pool().len() as u32
+}
+
+/// Copy a glyph of the active glyph pool as UTF-8, for hosts building their own glyph atlas
+/// Returns the number of bytes written, or 0 if `index` is out of range
+///
+/// # Safety
+/// - `handle` must be a valid pointer returned from `matrix_rain_new`
+/// - `out_utf8` must point to at least `MAX_GLYPH_BYTES` (32) writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_get_glyph(
+    handle: *const MatrixRainHandle,
+    index: u32,
+    out_utf8: *mut u8,
+) -> u8 {
+    if handle.is_null() || out_utf8.is_null() {
+        return 0;
+    }
+    let Some(glyph) = (*handle).engine.glyph_pool().glyphs().get(index as usize) else {
+        return 0;
+    };
+    ptr::copy_nonoverlapping(glyph.as_bytes().as_ptr(), out_utf8, glyph.len());
+    glyph.len() as u8
+}
+
+/// Destroy the Matrix Rain engine and free memory
+///
+/// # Safety
+/// - `handle` must be a valid pointer returned from `matrix_rain_new`
+/// - `handle` must not be used after this call
+/// - `handle` can be null (no-op in that case)
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_destroy(handle: *mut MatrixRainHandle) {
+    if !handle.is_null() {
+        let _ = Box::from_raw(handle);
+    }
+}
+
+/// Get the update interval in milliseconds for the given speed
+#[no_mangle]
+pub extern "C" fn matrix_rain_get_update_interval_ms(speed: u8) -> u64 {
+    let rain_speed = RainSpeed::from_index(speed).unwrap_or_default();
+    rain_speed.update_interval_ms()
+}
+
+/// Character set metadata (C-compatible struct)
+/// String pointers are NUL-terminated UTF-8 and valid for the lifetime of the process
+#[repr(C)]
+pub struct CharacterSetInfoFFI {
+    pub id: *const c_char,
+    pub english_name: *const c_char,
+    pub native_name: *const c_char,
+    pub direction: u8, // 0 = left-to-right, 1 = right-to-left, 2 = mixed
+    pub glyph_count: u32,
+    pub recommended_fonts: *const c_char, // Comma-separated, most preferred first
+    pub preview: *const c_char,
+}
+
+/// Color scheme metadata (C-compatible struct)
+#[repr(C)]
+pub struct ColorSchemeInfoFFI {
+    pub id: *const c_char,
+    pub name: *const c_char,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+/// Speed metadata (C-compatible struct)
+#[repr(C)]
+pub struct RainSpeedInfoFFI {
+    pub id: *const c_char,
+    pub name: *const c_char,
+    pub update_interval_ms: u64,
+}
+
+/// Convert a metadata string to a C string (metadata never contains NUL)
+fn c_string(value: &str) -> CString {
+    CString::new(value).unwrap_or_default()
+}
+
+/// NUL-terminated copies of the character set strings, built on first use
+fn charset_strings() -> &'static [[CString; 5]] {
+    static STRINGS: OnceLock<Vec<[CString; 5]>> = OnceLock::new();
+    STRINGS.get_or_init(|| {
+        CharacterSet::all_sets()
+            .iter()
+            .map(|set| {
+                let info = set.info();
+                [
+                    c_string(info.id),
+                    c_string(info.english_name),
+                    c_string(info.native_name),
+                    c_string(&info.recommended_fonts.join(",")),
+                    c_string(info.preview),
+                ]
+            })
+            .collect()
+    })
+}
+
+/// NUL-terminated copies of the color scheme strings, built on first use
+fn color_scheme_strings() -> &'static [[CString; 2]] {
+    static STRINGS: OnceLock<Vec<[CString; 2]>> = OnceLock::new();
+    STRINGS.get_or_init(|| {
+        ColorScheme::all_schemes()
+            .iter()
+            .map(|scheme| {
+                let info = scheme.info();
+                [c_string(info.id), c_string(info.name)]
+            })
+            .collect()
+    })
+}
+
+/// NUL-terminated copies of the accessible palette strings, built on first use
+fn accessible_palette_strings() -> &'static [[CString; 2]] {
+    static STRINGS: OnceLock<Vec<[CString; 2]>> = OnceLock::new();
+    STRINGS.get_or_init(|| {
+        AccessiblePalette::all()
+            .iter()
+            .map(|palette| [c_string(palette.id), c_string(palette.name)])
+            .collect()
+    })
+}
+
+/// NUL-terminated copies of the speed strings, built on first use
+fn speed_strings() -> &'static [[CString; 2]] {
+    static STRINGS: OnceLock<Vec<[CString; 2]>> = OnceLock::new();
+    STRINGS.get_or_init(|| {
+        RainSpeed::all_speeds()
+            .iter()
+            .map(|speed| {
+                let info = speed.info();
+                [c_string(info.id), c_string(info.name)]
+            })
+            .collect()
+    })
+}
+
+/// Get the number of available character sets
+#[no_mangle]
+pub extern "C" fn matrix_rain_charset_count() -> u8 {
+    CharacterSet::all_sets().len() as u8
+}
+
+/// Get metadata for the character set at `index`
+/// Returns false if the index is out of range
+///
+/// # Safety
+/// - `out_info` must be a valid pointer to write the result
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_charset_info(
+    index: u8,
+    out_info: *mut CharacterSetInfoFFI,
+) -> bool {
+    let Some(set) = CharacterSet::from_index(index) else {
+        return false;
+    };
+    if out_info.is_null() {
+        return false;
+    }
+
+    let [id, english_name, native_name, recommended_fonts, preview] =
+        &charset_strings()[index as usize];
+    let info = set.info();
+    *out_info = CharacterSetInfoFFI {
+        id: id.as_ptr(),
+        english_name: english_name.as_ptr(),
+        native_name: native_name.as_ptr(),
+        direction: info.direction.code(),
+        glyph_count: info.glyph_count as u32,
+        recommended_fonts: recommended_fonts.as_ptr(),
+        preview: preview.as_ptr(),
+    };
+    true
+}
+
+/// Get the number of available color schemes
+#[no_mangle]
+pub extern "C" fn matrix_rain_color_scheme_count() -> u8 {
+    ColorScheme::all_schemes().len() as u8
+}
+
+/// Get metadata for the color scheme at `index`
+/// Returns false if the index is out of range
+///
+/// # Safety
+/// - `out_info` must be a valid pointer to write the result
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_color_scheme_info(
+    index: u8,
+    out_info: *mut ColorSchemeInfoFFI,
+) -> bool {
+    let Some(scheme) = ColorScheme::from_index(index) else {
+        return false;
+    };
+    if out_info.is_null() {
+        return false;
+    }
+
+    let [id, name] = &color_scheme_strings()[index as usize];
+    let (r, g, b) = scheme.get_primary_color();
+    *out_info = ColorSchemeInfoFFI {
+        id: id.as_ptr(),
+        name: name.as_ptr(),
+        r,
+        g,
+        b,
+    };
+    true
+}
+
+/// Get the number of colour-vision accessible palettes
+#[no_mangle]
+pub extern "C" fn matrix_rain_accessible_palette_count() -> u8 {
+    AccessiblePalette::all().len() as u8
+}
+
+/// Get metadata for the accessible palette at `index`
+/// Returns false if the index is out of range
+///
+/// # Safety
+/// - `out_info` must be a valid pointer to write the result
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_accessible_palette_info(
+    index: u8,
+    out_info: *mut ColorSchemeInfoFFI,
+) -> bool {
+    let Some(palette) = AccessiblePalette::all().get(index as usize).copied() else {
+        return false;
+    };
+    if out_info.is_null() {
+        return false;
+    }
+
+    let [id, name] = &accessible_palette_strings()[index as usize];
+    let (r, g, b) = palette.colors.primary;
+    *out_info = ColorSchemeInfoFFI {
+        id: id.as_ptr(),
+        name: name.as_ptr(),
+        r,
+        g,
+        b,
+    };
+    true
+}
+
+/// Switch to the accessible palette at `index`
+/// Returns false if the index is out of range
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_set_accessible_palette(
+    handle: *mut MatrixRainHandle,
+    index: u8,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    let Some(palette) = AccessiblePalette::all().get(index as usize).copied() else {
+        return false;
+    };
+
+    let handle = &mut *handle;
+    let mut config = handle.engine.config().clone();
+    config.color_scheme = palette.scheme();
+    handle.engine.set_config(config);
+    true
+}
+
+/// Get the lowest WCAG contrast ratio of any trail stage against the current background
+/// Returns 0.0 for a null handle
+///
+/// # Safety
+/// - `handle` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_min_contrast(handle: *const MatrixRainHandle) -> f32 {
+    if handle.is_null() {
+        return 0.0;
+    }
+    let engine = &(*handle).engine;
+    let scheme = engine.config().color_scheme;
+    let background = engine.background_color();
+    ContrastReport::for_scheme(&scheme, (background.r, background.g, background.b)).min()
+}
+
+/// Get the number of available speed settings
+#[no_mangle]
+pub extern "C" fn matrix_rain_speed_count() -> u8 {
+    RainSpeed::all_speeds().len() as u8
+}
+
+/// Get metadata for the speed at `index`
+/// Returns false if the index is out of range
+///
+/// # Safety
+/// - `out_info` must be a valid pointer to write the result
+#[no_mangle]
+pub unsafe extern "C" fn matrix_rain_speed_info(
+    index: u8,
+    out_info: *mut RainSpeedInfoFFI,
+) -> bool {
+    let Some(speed) = RainSpeed::from_index(index) else {
+        return false;
+    };
+    if out_info.is_null() {
+        return false;
+    }
+
+    let [id, name] = &speed_strings()[index as usize];
+    *out_info = RainSpeedInfoFFI {
+        id: id.as_ptr(),
+        name: name.as_ptr(),
+        update_interval_ms: speed.update_interval_ms(),
+    };
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi_lifecycle() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            assert!(!handle.is_null());
+
+            matrix_rain_update(handle);
+            matrix_rain_update(handle);
+
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_ffi_config_update() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+
+            // Change to Korean, Purple, Fast
+            matrix_rain_set_config(handle, 2560, 1440, 4, 2, 3);
+
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_null_handle_safety() {
+        unsafe {
+            matrix_rain_update(ptr::null_mut());
+            matrix_rain_destroy(ptr::null_mut());
+            // Should not crash
+        }
+    }
+
+    #[test]
+    fn test_update_interval() {
+        assert_eq!(matrix_rain_get_update_interval_ms(2), 50); // Medium
+        assert_eq!(matrix_rain_get_update_interval_ms(4), 15); // Very fast
+    }
+
+    #[test]
+    fn test_render_data_export() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            assert!(!handle.is_null());
 
             // Update several times to build up trails
             for _ in 0..50 {
@@ -308,6 +1407,482 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_glyph_transforms_export() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            matrix_rain_set_glyph_transforms(handle, 1.0, 0.0, 0.0, 0.0);
+
+            for _ in 0..50 {
+                matrix_rain_update(handle);
+            }
+
+            let mut count: usize = 0;
+            let data_ptr = matrix_rain_get_render_chars(handle, &mut count as *mut usize);
+            let render_slice = std::slice::from_raw_parts(data_ptr, count);
+            assert!(count > 0);
+            for render_char in render_slice {
+                assert_eq!(
+                    render_char.transform,
+                    crate::GlyphTransform::MIRROR_X.bits()
+                );
+            }
+
+            // Changing the base config keeps the transform settings
+            matrix_rain_set_config(handle, 1920, 1080, 4, 2, 3);
+            assert_eq!(
+                (*handle).engine.config().glyph_transforms,
+                GlyphTransformConfig {
+                    mirror_x: 1.0,
+                    mirror_y: 0.0,
+                    rotate_90: 0.0,
+                    rotate_180: 0.0,
+                }
+            );
+            assert_eq!(
+                (*handle).engine.config().character_set,
+                CharacterSet::Korean
+            );
+
+            // A null handle is ignored
+            matrix_rain_set_glyph_transforms(ptr::null_mut(), 1.0, 1.0, 1.0, 1.0);
+
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_coverage_font_errors() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            let full = matrix_rain_set_coverage_font(handle, ptr::null());
+            assert!(full > 0);
+
+            let missing = c"/nonexistent/font.ttf";
+            assert_eq!(matrix_rain_set_coverage_font(handle, missing.as_ptr()), 0);
+            assert_eq!((*handle).engine.config().coverage_font, None);
+            assert_eq!(
+                matrix_rain_set_coverage_font(ptr::null_mut(), missing.as_ptr()),
+                0
+            );
+
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_metadata_enumeration() {
+        unsafe {
+            let count = matrix_rain_charset_count();
+            assert_eq!(count as usize, CharacterSet::all_sets().len());
+
+            let mut info = std::mem::zeroed::<CharacterSetInfoFFI>();
+            assert!(matrix_rain_charset_info(7, &mut info));
+            assert_eq!(CStr::from_ptr(info.id).to_str().unwrap(), "hebrew");
+            assert_eq!(CStr::from_ptr(info.native_name).to_str().unwrap(), "עברית");
+            assert_eq!(info.direction, 1);
+            assert!(info.glyph_count > 0);
+            assert!(!matrix_rain_charset_info(count, &mut info));
+            assert!(!matrix_rain_charset_info(0, ptr::null_mut()));
+
+            let mut scheme = std::mem::zeroed::<ColorSchemeInfoFFI>();
+            assert!(matrix_rain_color_scheme_info(0, &mut scheme));
+            assert_eq!(
+                CStr::from_ptr(scheme.name).to_str().unwrap(),
+                "Matrix Green"
+            );
+            assert_eq!((scheme.r, scheme.g, scheme.b), (0, 255, 70));
+            assert!(!matrix_rain_color_scheme_info(
+                matrix_rain_color_scheme_count(),
+                &mut scheme
+            ));
+
+            let mut speed = std::mem::zeroed::<RainSpeedInfoFFI>();
+            assert_eq!(matrix_rain_speed_count(), 5);
+            assert!(matrix_rain_speed_info(4, &mut speed));
+            assert_eq!(CStr::from_ptr(speed.id).to_str().unwrap(), "very-fast");
+            assert_eq!(speed.update_interval_ms, 15);
+        }
+    }
+
+    #[test]
+    fn test_accessible_palettes() {
+        unsafe {
+            let count = matrix_rain_accessible_palette_count();
+            assert!(count >= 4);
+
+            let mut info = std::mem::zeroed::<ColorSchemeInfoFFI>();
+            assert!(matrix_rain_accessible_palette_info(0, &mut info));
+            assert_eq!(CStr::from_ptr(info.id).to_str().unwrap(), "cvd-sky-blue");
+            assert!(!matrix_rain_accessible_palette_info(count, &mut info));
+
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            assert!(matrix_rain_min_contrast(handle) < 3.0);
+            assert!(matrix_rain_set_accessible_palette(handle, 1));
+            assert_eq!(
+                (*handle).engine.config().color_scheme,
+                AccessiblePalette::all()[1].scheme()
+            );
+            assert!(matrix_rain_min_contrast(handle) >= 3.0);
+            assert!(!matrix_rain_set_accessible_palette(handle, count));
+            assert!(!matrix_rain_set_accessible_palette(ptr::null_mut(), 0));
+            assert_eq!(matrix_rain_min_contrast(ptr::null()), 0.0);
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_sequence_text() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            assert!(matrix_rain_set_sequence_text(
+                handle,
+                c"NEO\nTRINITY".as_ptr(),
+                0.0
+            ));
+            assert!(!matrix_rain_set_sequence_text(
+                handle,
+                c"\n\n".as_ptr(),
+                0.0
+            ));
+            assert!(!matrix_rain_set_sequence_text(handle, ptr::null(), 0.0));
+            assert!(!matrix_rain_set_sequence_text(
+                ptr::null_mut(),
+                c"NEO".as_ptr(),
+                0.0
+            ));
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_custom_glyph_export() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            assert_eq!(matrix_rain_set_custom_glyphs(handle, c"👩‍💻 🇯🇵".as_ptr()), 2);
+
+            for _ in 0..50 {
+                matrix_rain_update(handle);
+            }
+
+            let mut count: usize = 0;
+            let chars = matrix_rain_get_render_chars(handle, &mut count);
+            assert!(count > 0);
+            for rc in std::slice::from_raw_parts(chars, count) {
+                let text = std::str::from_utf8(&rc.glyph_utf8[..rc.glyph_len as usize]).unwrap();
+                assert!(text == "👩‍💻" || text == "🇯🇵");
+                assert_eq!(Some(rc.character), text.chars().next().map(u32::from));
+            }
+
+            // Null returns to the character set
+            assert_eq!(
+                matrix_rain_set_custom_glyphs(handle, ptr::null()),
+                CharacterSet::Japanese.get_glyphs().len()
+            );
+            assert_eq!(
+                matrix_rain_set_custom_glyphs(ptr::null_mut(), ptr::null()),
+                0
+            );
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_custom_colors() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            assert_eq!(matrix_rain_get_background_color(handle), 0x000000);
+
+            matrix_rain_set_custom_colors(handle, 0xFFFFFF, 0xFF6600, 0x993D00, 0x4D1F00, 0x101820);
+            assert_eq!(matrix_rain_get_background_color(handle), 0x101820);
+            let scheme = (*handle).engine.config().color_scheme;
+            assert_eq!(scheme.get_primary_color(), (0xFF, 0x66, 0x00));
+
+            // The custom scheme survives config updates that pass its index
+            let custom_index = matrix_rain_color_scheme_count();
+            matrix_rain_set_config(handle, 1280, 720, 0, custom_index, 2);
+            assert_eq!((*handle).engine.config().color_scheme, scheme);
+
+            // Picking a preset replaces it
+            matrix_rain_set_config(handle, 1280, 720, 0, 1, 2);
+            assert_eq!(
+                (*handle).engine.config().color_scheme,
+                ColorScheme::DarkBlue
+            );
+            assert_eq!(matrix_rain_get_background_color(ptr::null()), 0);
+
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_gradient() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            let colors = [0xFFFFFF, 0x00FF46, 0x002810];
+            let positions = [0.0, 0.3, 1.0];
+            assert!(matrix_rain_set_gradient(
+                handle,
+                colors.as_ptr(),
+                positions.as_ptr(),
+                3,
+                0
+            ));
+            let gradient = (*handle).engine.config().gradient.clone().unwrap();
+            assert_eq!(gradient.interpolation, Interpolation::Linear);
+            assert_eq!(gradient.color_at(0.3), (0x00, 0xFF, 0x46));
+
+            assert!(!matrix_rain_set_gradient(
+                handle,
+                ptr::null(),
+                ptr::null(),
+                0,
+                0
+            ));
+            assert!((*handle).engine.config().gradient.is_none());
+            assert!(!matrix_rain_set_gradient(
+                handle,
+                ptr::null(),
+                positions.as_ptr(),
+                3,
+                0
+            ));
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_palette_animation() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            matrix_rain_set_palette_animation(handle, 20.0, 3.0, 4, 8.0);
+            let animation = (*handle).engine.config().palette_animation;
+            assert_eq!(animation.hue_cycle_period, 20.0);
+            assert_eq!(animation.breathe_with, Some(ColorScheme::Red));
+
+            matrix_rain_set_palette_animation(handle, 0.0, 0.0, u8::MAX, 0.0);
+            assert!(!(*handle).engine.config().palette_animation.is_enabled());
+
+            matrix_rain_update(handle);
+            assert!(matrix_rain_get_time(handle) > 0.0);
+            matrix_rain_set_time(handle, 5.0);
+            assert_eq!(matrix_rain_get_time(handle), 5.0);
+            assert_eq!(matrix_rain_get_time(ptr::null()), 0.0);
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_leader_style() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            matrix_rain_set_leader_style(handle, true, 0xFFC800, 2, 0.5);
+            for _ in 0..60 {
+                matrix_rain_update(handle);
+            }
+
+            let mut count: usize = 0;
+            let chars = matrix_rain_get_render_chars(handle, &mut count);
+            let chars = std::slice::from_raw_parts(chars, count);
+            assert!(chars.iter().any(|rc| rc.leader == 1));
+            for rc in chars.iter().filter(|rc| rc.leader == 1) {
+                assert_eq!((rc.r, rc.g, rc.b), (0xFF, 0xC8, 0x00));
+                assert_eq!(rc.glow, 0.5);
+            }
+
+            matrix_rain_set_leader_style(handle, true, u32::MAX, 0, 0.0);
+            assert_eq!((*handle).engine.config().leader, LeaderStyle::default());
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_background() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            let mut background = BackgroundFFI {
+                kind: 255,
+                color_a: 1,
+                color_b: 1,
+            };
+            assert!(matrix_rain_get_background(handle, &mut background));
+            assert_eq!(
+                (background.kind, background.color_a, background.color_b),
+                (0, 0, 0)
+            );
+
+            matrix_rain_set_background(handle, 2, 0x003010, 0x000000, 0.0);
+            assert!(matrix_rain_get_background(handle, &mut background));
+            assert_eq!(
+                (background.kind, background.color_a, background.color_b),
+                (2, 0x003010, 0x000000)
+            );
+            assert_eq!(matrix_rain_get_background_color(handle), 0x001808);
+
+            matrix_rain_set_background(handle, 3, 0, 0, 0.0);
+            assert!(matrix_rain_get_background(handle, &mut background));
+            assert_eq!(background.kind, 3);
+
+            assert!(!matrix_rain_get_background(handle, ptr::null_mut()));
+            assert!(!matrix_rain_get_background(ptr::null(), &mut background));
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_hdr_render_chars() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            matrix_rain_set_leader_brightness(handle, 4.0);
+            for _ in 0..60 {
+                matrix_rain_update(handle);
+            }
+
+            let mut sdr_count: usize = 0;
+            let sdr = matrix_rain_get_render_chars(handle, &mut sdr_count);
+            let sdr = std::slice::from_raw_parts(sdr, sdr_count);
+            let mut count: usize = 0;
+            let hdr = matrix_rain_get_render_chars_hdr(handle, &mut count);
+            let hdr = std::slice::from_raw_parts(hdr, count);
+            assert_eq!(count, sdr_count);
+            assert!(hdr.iter().any(|rc| rc.leader == 1));
+
+            for (h, s) in hdr.iter().zip(sdr) {
+                assert_eq!((h.x, h.y, h.leader), (s.x, s.y, s.leader));
+                if h.leader == 1 {
+                    assert_eq!(h.r.max(h.g).max(h.b), 4.0);
+                    assert_eq!((s.r, s.g, s.b), (255, 255, 255));
+                } else {
+                    assert!(h.r.max(h.g).max(h.b) <= 1.0);
+                }
+            }
+
+            // Changing the leader style keeps the brightness
+            matrix_rain_set_leader_style(handle, true, 0xFFFFFFFF, 1, 0.5);
+            assert_eq!((*handle).engine.config().leader.brightness, 4.0);
+
+            assert!(matrix_rain_get_render_chars_hdr(ptr::null_mut(), &mut count).is_null());
+            assert!(matrix_rain_get_render_chars_hdr(handle, ptr::null_mut()).is_null());
+            matrix_rain_set_leader_brightness(ptr::null_mut(), 2.0);
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_themes() {
+        let dir =
+            std::env::temp_dir().join(format!("matrix-rain-ffi-themes-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let dir_c = CString::new(dir.to_str().unwrap()).unwrap();
+
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 3, 4, 2);
+            matrix_rain_set_blend_mode(handle, 1, false);
+            matrix_rain_set_density(handle, 2.5);
+            assert!(matrix_rain_save_theme(
+                handle,
+                dir_c.as_ptr(),
+                c"Deep Blue".as_ptr(),
+                c"Morpheus".as_ptr()
+            ));
+            let saved = (*handle).engine.config().clone();
+
+            let other = matrix_rain_new(800, 600, 0, 0, 2);
+            assert!(matrix_rain_apply_theme(
+                other,
+                dir_c.as_ptr(),
+                c"deep blue".as_ptr()
+            ));
+            let config = (*other).engine.config();
+            assert_eq!(config.character_set, saved.character_set);
+            assert_eq!(config.color_scheme, saved.color_scheme);
+            assert_eq!(config.blend_mode, BlendMode::Additive);
+            assert_eq!(config.density, 2.5);
+            assert_eq!(config.screen_width, 800);
+
+            let path = CString::new(dir.join("deep-blue.json").to_str().unwrap()).unwrap();
+            assert!(matrix_rain_apply_theme_file(other, path.as_ptr()));
+            assert!(!matrix_rain_apply_theme_file(
+                other,
+                c"/nonexistent.json".as_ptr()
+            ));
+            assert!(!matrix_rain_apply_theme_file(other, ptr::null()));
+            assert!(!matrix_rain_apply_theme(
+                other,
+                dir_c.as_ptr(),
+                c"Missing".as_ptr()
+            ));
+            assert!(!matrix_rain_apply_theme(
+                ptr::null_mut(),
+                dir_c.as_ptr(),
+                c"Deep Blue".as_ptr()
+            ));
+            assert!(!matrix_rain_save_theme(
+                handle,
+                ptr::null(),
+                c"X".as_ptr(),
+                ptr::null()
+            ));
+
+            matrix_rain_destroy(other);
+            matrix_rain_destroy(handle);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_import_palette() {
+        let path = std::env::temp_dir().join(format!("matrix-rain-ffi-{}.gpl", std::process::id()));
+        std::fs::write(&path, "GIMP Palette\n0 0 0\n255 120 0\n").unwrap();
+        let path_c = CString::new(path.to_str().unwrap()).unwrap();
+
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            assert!(matrix_rain_import_palette(handle, path_c.as_ptr()));
+            let scheme = (*handle).engine.config().color_scheme;
+            assert_eq!(scheme.get_primary_color(), (255, 120, 0));
+            assert_eq!(matrix_rain_get_background_color(handle), 0x000000);
+
+            assert!(!matrix_rain_import_palette(
+                handle,
+                c"/nonexistent.gpl".as_ptr()
+            ));
+            assert!(!matrix_rain_import_palette(handle, ptr::null()));
+            assert!(!matrix_rain_import_palette(
+                ptr::null_mut(),
+                path_c.as_ptr()
+            ));
+            matrix_rain_destroy(handle);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_blend_mode() {
+        unsafe {
+            let handle = matrix_rain_new(1920, 1080, 0, 0, 2);
+            matrix_rain_set_blend_mode(handle, 2, true);
+            let config = (*handle).engine.config();
+            assert_eq!(config.blend_mode, BlendMode::Screen);
+            assert!(config.precomposite);
+
+            for _ in 0..50 {
+                matrix_rain_update(handle);
+            }
+            let mut count: usize = 0;
+            let data = matrix_rain_get_render_chars(handle, &mut count);
+            assert!(count > 0);
+            assert!(std::slice::from_raw_parts(data, count)
+                .iter()
+                .all(|rc| rc.a == 1.0));
+
+            matrix_rain_set_blend_mode(handle, 9, false);
+            assert_eq!((*handle).engine.config().blend_mode, BlendMode::Normal);
+            matrix_rain_set_blend_mode(ptr::null_mut(), 1, true);
+            matrix_rain_destroy(handle);
+        }
+    }
+
     #[test]
     fn test_render_data_null_safety() {
         unsafe {
@@ -325,4 +1900,131 @@ mod tests {
             matrix_rain_destroy(handle);
         }
     }
+
+    #[test]
+    fn test_delta() {
+        unsafe {
+            let handle = matrix_rain_new(640, 480, 0, 0, 2);
+            for _ in 0..40 {
+                matrix_rain_update(handle);
+            }
+
+            let mut delta = FrameDeltaFFI {
+                full_frame: 0,
+                changes: ptr::null(),
+                change_count: 0,
+                dirty_rects: ptr::null(),
+                dirty_rect_count: 0,
+            };
+            assert!(matrix_rain_get_delta(handle, &mut delta));
+            assert_eq!(delta.full_frame, 1);
+            let mut count: usize = 0;
+            matrix_rain_get_render_chars(handle, &mut count);
+            assert_eq!(delta.change_count, count);
+            let rects = std::slice::from_raw_parts(delta.dirty_rects, delta.dirty_rect_count);
+            assert_eq!(rects.len(), 1);
+            assert_eq!((rects[0].width, rects[0].height), (640.0, 480.0));
+
+            // Unchanged frames report nothing; updates report changed cells
+            assert!(matrix_rain_get_delta(handle, &mut delta));
+            assert_eq!((delta.full_frame, delta.change_count), (0, 0));
+            assert_eq!(delta.dirty_rect_count, 0);
+            matrix_rain_update(handle);
+            assert!(matrix_rain_get_delta(handle, &mut delta));
+            let changes = std::slice::from_raw_parts(delta.changes, delta.change_count);
+            assert!(!changes.is_empty());
+            assert!(changes
+                .iter()
+                .all(|change| change.layer <= 1 && change.change <= 4));
+            assert!(delta.full_frame == 1 || delta.dirty_rect_count > 0);
+
+            matrix_rain_invalidate_delta(handle);
+            assert!(matrix_rain_get_delta(handle, &mut delta));
+            assert_eq!(delta.full_frame, 1);
+
+            assert!(!matrix_rain_get_delta(ptr::null_mut(), &mut delta));
+            assert!(!matrix_rain_get_delta(handle, ptr::null_mut()));
+            matrix_rain_invalidate_delta(ptr::null_mut());
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_render_batches() {
+        unsafe {
+            let handle = matrix_rain_new(1280, 720, 0, 0, 2);
+            for _ in 0..60 {
+                matrix_rain_update(handle);
+            }
+
+            let mut count: usize = 0;
+            let mut chars: *const RenderCharFFI = ptr::null();
+            let mut char_count: usize = 0;
+            let batches =
+                matrix_rain_get_render_batches(handle, &mut count, &mut chars, &mut char_count);
+            let batches = std::slice::from_raw_parts(batches, count);
+            let chars = std::slice::from_raw_parts(chars, char_count);
+            assert!(!batches.is_empty() && batches.len() < chars.len());
+
+            // Batches tile the glyph array, and every glyph has its batch's size and glow
+            let mut next = 0;
+            for batch in batches {
+                assert_eq!(batch.start, next);
+                next += batch.count;
+                for rc in &chars[batch.start..batch.start + batch.count] {
+                    assert_eq!((rc.font_size, rc.glow), (batch.font_size, batch.glow));
+                }
+            }
+            assert_eq!(next, chars.len());
+
+            let mut plain_count: usize = 0;
+            matrix_rain_get_render_chars(handle, &mut plain_count);
+            assert_eq!(plain_count, char_count);
+
+            assert!(matrix_rain_get_render_batches(
+                handle,
+                &mut count,
+                ptr::null_mut(),
+                &mut char_count
+            )
+            .is_null());
+            matrix_rain_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_glyph_indices() {
+        unsafe {
+            let handle = matrix_rain_new(800, 600, 0, 0, 2);
+            for _ in 0..40 {
+                matrix_rain_update(handle);
+            }
+
+            let count = matrix_rain_glyph_count(handle);
+            assert!(count > 0);
+            let mut char_count: usize = 0;
+            let chars = matrix_rain_get_render_chars(handle, &mut char_count);
+            let chars = std::slice::from_raw_parts(chars, char_count);
+            assert!(!chars.is_empty());
+
+            // Every glyph's index points back at the same glyph in the pool
+            let mut utf8 = [0u8; MAX_GLYPH_BYTES];
+            for rc in chars {
+                assert!(rc.glyph_index >= 0 && (rc.glyph_index as u32) < count);
+                let len = matrix_rain_get_glyph(handle, rc.glyph_index as u32, utf8.as_mut_ptr());
+                assert_eq!(
+                    &utf8[..len as usize],
+                    &rc.glyph_utf8[..rc.glyph_len as usize]
+                );
+            }
+            assert_eq!(matrix_rain_get_glyph(handle, count, utf8.as_mut_ptr()), 0);
+
+            let text = c"AB";
+            assert_eq!(matrix_rain_set_custom_glyphs(handle, text.as_ptr()), 2);
+            assert_eq!(matrix_rain_glyph_count(handle), 2);
+            assert_eq!(matrix_rain_get_glyph(handle, 1, utf8.as_mut_ptr()), 1);
+            assert_eq!(utf8[0], b'B');
+            matrix_rain_destroy(handle);
+        }
+    }
 }
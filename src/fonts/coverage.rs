@@ -0,0 +1,540 @@
+//! Glyph coverage checking from a font's `cmap` table
+//!
+//! Parses TrueType/OpenType (`.ttf`, `.otf`) and collection (`.ttc`) files
+//! directly, so it works offline without any system font APIs.
+
+use crate::config::CharacterSet;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+/// Errors that can occur while reading a font's character map
+#[derive(Debug)]
+pub enum FontError {
+    /// The font file could not be read
+    Io(std::io::Error),
+    /// The data is not a font this parser understands
+    Malformed(&'static str),
+    /// The requested face index is not present in the collection
+    FaceIndexOutOfRange(u32),
+    /// The font has no Unicode character map
+    NoUnicodeCmap,
+    /// A table the operation needs is missing from the face
+    MissingTable([u8; 4]),
+    /// The font is valid but uses features this crate cannot process
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Io(err) => write!(f, "failed to read font: {}", err),
+            FontError::Malformed(reason) => write!(f, "malformed font: {}", reason),
+            FontError::FaceIndexOutOfRange(index) => {
+                write!(f, "font collection has no face at index {}", index)
+            }
+            FontError::NoUnicodeCmap => write!(f, "font has no Unicode cmap subtable"),
+            FontError::MissingTable(tag) => {
+                write!(f, "font has no '{}' table", String::from_utf8_lossy(tag))
+            }
+            FontError::Unsupported(reason) => write!(f, "unsupported font: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for FontError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FontError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for FontError {
+    fn from(err: std::io::Error) -> Self {
+        FontError::Io(err)
+    }
+}
+
+/// Big-endian reader over a byte slice with bounds checking
+pub(super) struct Reader<'a> {
+    pub(super) data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(super) fn u16_at(&self, offset: usize) -> Result<u16, FontError> {
+        self.data
+            .get(offset..offset + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or(FontError::Malformed("unexpected end of data"))
+    }
+
+    pub(super) fn u32_at(&self, offset: usize) -> Result<u32, FontError> {
+        self.data
+            .get(offset..offset + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or(FontError::Malformed("unexpected end of data"))
+    }
+}
+
+/// The set of Unicode codepoints a font face maps to real glyphs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontCoverage {
+    /// Sorted, deduplicated codepoints with a non-zero glyph id
+    codepoints: Vec<u32>,
+}
+
+impl FontCoverage {
+    /// Read coverage from a font file (face 0 for collections)
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        Self::from_file_index(path, 0)
+    }
+
+    /// Read coverage from a specific face of a font file
+    pub fn from_file_index(path: impl AsRef<Path>, face_index: u32) -> Result<Self, FontError> {
+        let data = std::fs::read(path)?;
+        Self::from_bytes(&data, face_index)
+    }
+
+    /// Read coverage from in-memory font data
+    pub fn from_bytes(data: &[u8], face_index: u32) -> Result<Self, FontError> {
+        let reader = Reader { data };
+        let face_offset = face_offset(&reader, face_index)?;
+        let mut codepoints = Vec::new();
+        read_cmap(&reader, face_offset, &mut |code, _| codepoints.push(code))?;
+
+        codepoints.sort_unstable();
+        codepoints.dedup();
+        Ok(Self { codepoints })
+    }
+
+    /// Check whether the font has a glyph for a character
+    pub fn contains(&self, ch: char) -> bool {
+        self.codepoints.binary_search(&(ch as u32)).is_ok()
+    }
+
+    /// Number of codepoints the font covers
+    pub fn len(&self) -> usize {
+        self.codepoints.len()
+    }
+
+    /// Check whether the font covers no codepoints at all
+    pub fn is_empty(&self) -> bool {
+        self.codepoints.is_empty()
+    }
+
+    /// Check which characters of a character set the font lacks
+    pub fn check(&self, character_set: CharacterSet) -> CoverageReport {
+        let chars = character_set.get_characters();
+        let missing: Vec<u32> = chars
+            .iter()
+            .filter(|&&ch| !self.contains(ch))
+            .map(|&ch| ch as u32)
+            .collect();
+
+        CoverageReport {
+            character_set,
+            total: chars.len(),
+            covered: chars.len() - missing.len(),
+            missing,
+        }
+    }
+
+    /// Check every built-in character set
+    pub fn check_all(&self) -> Vec<CoverageReport> {
+        CharacterSet::all_sets()
+            .into_iter()
+            .map(|set| self.check(set))
+            .collect()
+    }
+}
+
+/// Coverage of one character set by a font
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CoverageReport {
+    /// The character set that was checked
+    pub character_set: CharacterSet,
+    /// Number of characters in the set
+    pub total: usize,
+    /// Number of characters the font has glyphs for
+    pub covered: usize,
+    /// Codepoints the font lacks
+    pub missing: Vec<u32>,
+}
+
+impl CoverageReport {
+    /// Fraction of the set that is covered (0.0-1.0)
+    pub fn ratio(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.covered as f32 / self.total as f32
+        }
+    }
+
+    /// Check whether every character in the set is covered
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Locate the offset table of a face, handling `.ttc` collections
+pub(super) fn face_offset(reader: &Reader, face_index: u32) -> Result<usize, FontError> {
+    let tag = reader.u32_at(0)?;
+    if tag == u32::from_be_bytes(*b"ttcf") {
+        let num_fonts = reader.u32_at(8)?;
+        if face_index >= num_fonts {
+            return Err(FontError::FaceIndexOutOfRange(face_index));
+        }
+        return Ok(reader.u32_at(12 + face_index as usize * 4)? as usize);
+    }
+
+    if face_index != 0 {
+        return Err(FontError::FaceIndexOutOfRange(face_index));
+    }
+
+    match tag {
+        0x0001_0000 => Ok(0),
+        t if t == u32::from_be_bytes(*b"OTTO") || t == u32::from_be_bytes(*b"true") => Ok(0),
+        _ => Err(FontError::Malformed("unknown sfnt version")),
+    }
+}
+
+/// Find a table in the face's table directory
+pub(super) fn find_table(
+    reader: &Reader,
+    face_offset: usize,
+    tag: [u8; 4],
+) -> Result<usize, FontError> {
+    let num_tables = reader.u16_at(face_offset + 4)? as usize;
+    let wanted = u32::from_be_bytes(tag);
+
+    for i in 0..num_tables {
+        let record = face_offset + 12 + i * 16;
+        if reader.u32_at(record)? == wanted {
+            return Ok(reader.u32_at(record + 8)? as usize);
+        }
+    }
+    Err(FontError::MissingTable(tag))
+}
+
+/// Most codepoints all Unicode subtables of a cmap may cover together
+/// Real fonts have a few subtables of at most 0x110000 codepoints each; anything
+/// beyond this is malformed and would otherwise take minutes to walk.
+const MAX_CMAP_CODEPOINTS: usize = 4 * 0x110000;
+
+/// Visit every (codepoint, glyph id) pair of the face's Unicode cmap subtables
+/// Pairs mapping to glyph 0 are skipped; a codepoint may be visited more than once.
+pub(super) fn read_cmap(
+    reader: &Reader,
+    face_offset: usize,
+    visit: &mut dyn FnMut(u32, u32),
+) -> Result<(), FontError> {
+    let cmap_offset = find_table(reader, face_offset, *b"cmap")?;
+    let num_subtables = reader.u16_at(cmap_offset + 2)? as usize;
+    let mut found_unicode = false;
+    let mut budget = MAX_CMAP_CODEPOINTS;
+
+    for i in 0..num_subtables {
+        let record = cmap_offset + 4 + i * 8;
+        let platform_id = reader.u16_at(record)?;
+        let encoding_id = reader.u16_at(record + 2)?;
+        let subtable = cmap_offset + reader.u32_at(record + 4)? as usize;
+
+        let is_unicode = platform_id == 0 || (platform_id == 3 && matches!(encoding_id, 1 | 10));
+        if !is_unicode {
+            continue;
+        }
+
+        if read_subtable(reader, subtable, &mut budget, visit)? {
+            found_unicode = true;
+        }
+    }
+
+    if !found_unicode {
+        return Err(FontError::NoUnicodeCmap);
+    }
+    Ok(())
+}
+
+/// Visit the (codepoint, glyph id) pairs mapped by a cmap subtable
+/// Returns false for formats that do not map codepoints (e.g. variation sequences)
+/// Every codepoint a range covers is taken from `budget` before it is walked.
+fn read_subtable(
+    reader: &Reader,
+    offset: usize,
+    budget: &mut usize,
+    visit: &mut dyn FnMut(u32, u32),
+) -> Result<bool, FontError> {
+    match reader.u16_at(offset)? {
+        0 => {
+            spend(budget, 256)?;
+            for code in 0..256 {
+                let glyph = *reader
+                    .data
+                    .get(offset + 6 + code)
+                    .ok_or(FontError::Malformed("truncated format 0 subtable"))?;
+                if glyph != 0 {
+                    visit(code as u32, glyph as u32);
+                }
+            }
+        }
+        4 => {
+            let seg_count = reader.u16_at(offset + 6)? as usize / 2;
+            let end_codes = offset + 14;
+            let start_codes = end_codes + seg_count * 2 + 2;
+            let id_deltas = start_codes + seg_count * 2;
+            let id_range_offsets = id_deltas + seg_count * 2;
+
+            for seg in 0..seg_count {
+                let end = reader.u16_at(end_codes + seg * 2)? as u32;
+                let start = reader.u16_at(start_codes + seg * 2)? as u32;
+                let delta = reader.u16_at(id_deltas + seg * 2)?;
+                let range_offset_pos = id_range_offsets + seg * 2;
+                let range_offset = reader.u16_at(range_offset_pos)? as usize;
+
+                if start > end {
+                    continue;
+                }
+
+                let end = end.min(0xFFFE);
+                spend(budget, (end + 1).saturating_sub(start) as usize)?;
+                for code in start..=end {
+                    let glyph = if range_offset == 0 {
+                        (code as u16).wrapping_add(delta)
+                    } else {
+                        let addr = range_offset_pos + range_offset + (code - start) as usize * 2;
+                        match reader.u16_at(addr)? {
+                            0 => 0,
+                            g => g.wrapping_add(delta),
+                        }
+                    };
+                    if glyph != 0 {
+                        visit(code, glyph as u32);
+                    }
+                }
+            }
+        }
+        6 => {
+            let first_code = reader.u16_at(offset + 6)? as u32;
+            let entry_count = reader.u16_at(offset + 8)? as usize;
+            spend(budget, entry_count)?;
+            for i in 0..entry_count {
+                let glyph = reader.u16_at(offset + 10 + i * 2)?;
+                if glyph != 0 {
+                    visit(first_code + i as u32, glyph as u32);
+                }
+            }
+        }
+        format @ (12 | 13) => {
+            let num_groups = reader.u32_at(offset + 12)? as usize;
+            for i in 0..num_groups {
+                let group = offset + 16 + i * 12;
+                let start = reader.u32_at(group)?;
+                let end = reader.u32_at(group + 4)?.min(0x10FFFF);
+                let start_glyph = reader.u32_at(group + 8)?;
+
+                if start > end || (format == 13 && start_glyph == 0) {
+                    continue;
+                }
+                // Only the first codepoint of a format 12 group can map to glyph 0
+                let first = if format == 12 && start_glyph == 0 {
+                    start + 1
+                } else {
+                    start
+                };
+                spend(budget, (end - start) as usize + 1)?;
+                for code in first..=end {
+                    let glyph = if format == 12 {
+                        start_glyph.wrapping_add(code - start)
+                    } else {
+                        start_glyph
+                    };
+                    visit(code, glyph);
+                }
+            }
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Take `count` codepoints from a cmap's budget, failing once it runs out
+fn spend(budget: &mut usize, count: usize) -> Result<(), FontError> {
+    *budget = budget
+        .checked_sub(count)
+        .ok_or(FontError::Malformed("cmap maps too many codepoints"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Build a minimal sfnt containing only a cmap with a format 4 subtable
+    /// mapping each inclusive range to consecutive glyph ids
+    pub(crate) fn build_test_font(ranges: &[(u16, u16)]) -> Vec<u8> {
+        // Segments plus the mandatory 0xFFFF terminator
+        let mut segments: Vec<(u16, u16, u16)> = Vec::new();
+        let mut next_glyph: u16 = 1;
+        for &(start, end) in ranges {
+            let delta = next_glyph.wrapping_sub(start);
+            segments.push((start, end, delta));
+            next_glyph += end - start + 1;
+        }
+        segments.push((0xFFFF, 0xFFFF, 1));
+
+        let seg_count = segments.len() as u16;
+        let mut subtable = Vec::new();
+        let length = 16 + segments.len() * 8;
+        subtable.extend(4u16.to_be_bytes());
+        subtable.extend((length as u16).to_be_bytes());
+        subtable.extend(0u16.to_be_bytes()); // language
+        subtable.extend((seg_count * 2).to_be_bytes());
+        subtable.extend([0u8; 6]); // searchRange, entrySelector, rangeShift
+        for &(_, end, _) in &segments {
+            subtable.extend(end.to_be_bytes());
+        }
+        subtable.extend(0u16.to_be_bytes()); // reservedPad
+        for &(start, _, _) in &segments {
+            subtable.extend(start.to_be_bytes());
+        }
+        for &(_, _, delta) in &segments {
+            subtable.extend(delta.to_be_bytes());
+        }
+        for _ in &segments {
+            subtable.extend(0u16.to_be_bytes());
+        }
+        wrap_cmap_subtable(1, subtable)
+    }
+
+    /// Build a minimal sfnt containing only a cmap with a format 12 subtable
+    /// of (start, end, start glyph) groups, taken as is
+    fn build_format12_font(groups: &[(u32, u32, u32)]) -> Vec<u8> {
+        let mut subtable = Vec::new();
+        subtable.extend(12u16.to_be_bytes());
+        subtable.extend(0u16.to_be_bytes()); // reserved
+        subtable.extend((16 + groups.len() as u32 * 12).to_be_bytes());
+        subtable.extend(0u32.to_be_bytes()); // language
+        subtable.extend((groups.len() as u32).to_be_bytes());
+        for &(start, end, glyph) in groups {
+            subtable.extend(start.to_be_bytes());
+            subtable.extend(end.to_be_bytes());
+            subtable.extend(glyph.to_be_bytes());
+        }
+        wrap_cmap_subtable(10, subtable)
+    }
+
+    /// Wrap a Windows Unicode cmap subtable in a cmap table and a one-table sfnt
+    fn wrap_cmap_subtable(encoding_id: u16, subtable: Vec<u8>) -> Vec<u8> {
+        let mut cmap = Vec::new();
+        cmap.extend(0u16.to_be_bytes()); // version
+        cmap.extend(1u16.to_be_bytes()); // numTables
+        cmap.extend(3u16.to_be_bytes()); // platform: Windows
+        cmap.extend(encoding_id.to_be_bytes());
+        cmap.extend(12u32.to_be_bytes());
+        cmap.extend(subtable);
+
+        let mut font = Vec::new();
+        font.extend(0x0001_0000u32.to_be_bytes());
+        font.extend(1u16.to_be_bytes()); // numTables
+        font.extend([0u8; 6]);
+        font.extend(*b"cmap");
+        font.extend(0u32.to_be_bytes()); // checksum
+        font.extend(28u32.to_be_bytes()); // offset
+        font.extend((cmap.len() as u32).to_be_bytes());
+        font.extend(cmap);
+        font
+    }
+
+    #[test]
+    fn test_parse_format4() {
+        let font = build_test_font(&[(0x41, 0x5A), (0x30A0, 0x30FF)]);
+        let coverage = FontCoverage::from_bytes(&font, 0).unwrap();
+
+        assert!(coverage.contains('A'));
+        assert!(coverage.contains('Z'));
+        assert!(!coverage.contains('a'));
+        assert!(coverage.contains('\u{30A2}'));
+        assert_eq!(coverage.len(), 26 + 96);
+    }
+
+    #[test]
+    fn test_parse_format12() {
+        let font = build_format12_font(&[(0x41, 0x5A, 1), (0x1F600, 0x1F64F, 27)]);
+        let coverage = FontCoverage::from_bytes(&font, 0).unwrap();
+        assert!(coverage.contains('A'));
+        assert!(coverage.contains('\u{1F600}'));
+        assert_eq!(coverage.len(), 26 + 80);
+
+        // Thousands of groups covering all of Unicode are rejected instead of walked
+        let font = build_format12_font(&[(0, 0x10FFFF, 1); 10_000]);
+        assert!(matches!(
+            FontCoverage::from_bytes(&font, 0),
+            Err(FontError::Malformed("cmap maps too many codepoints"))
+        ));
+    }
+
+    #[test]
+    fn test_collection_face_lookup() {
+        let face = build_test_font(&[(0x41, 0x42)]);
+        let mut ttc = Vec::new();
+        ttc.extend(*b"ttcf");
+        ttc.extend(0x0001_0000u32.to_be_bytes());
+        ttc.extend(1u32.to_be_bytes());
+        ttc.extend(16u32.to_be_bytes());
+        // Table offsets in a collection are relative to the file start
+        let mut face = face;
+        face[20..24].copy_from_slice(&(28u32 + 16).to_be_bytes());
+        ttc.extend(face);
+
+        let coverage = FontCoverage::from_bytes(&ttc, 0).unwrap();
+        assert!(coverage.contains('A'));
+        assert!(matches!(
+            FontCoverage::from_bytes(&ttc, 1),
+            Err(FontError::FaceIndexOutOfRange(1))
+        ));
+    }
+
+    #[test]
+    fn test_malformed_data() {
+        assert!(matches!(
+            FontCoverage::from_bytes(b"not a font", 0),
+            Err(FontError::Malformed(_))
+        ));
+        assert!(FontCoverage::from_bytes(&[], 0).is_err());
+    }
+
+    #[test]
+    fn test_check_reports_missing() {
+        // Full-width katakana only: half-width and ASCII extras are missing
+        let font = build_test_font(&[(0x30A0, 0x30FF)]);
+        let coverage = FontCoverage::from_bytes(&font, 0).unwrap();
+        let report = coverage.check(CharacterSet::Japanese);
+
+        assert!(!report.is_complete());
+        assert_eq!(report.covered, 96);
+        assert_eq!(report.total, report.covered + report.missing.len());
+        assert!(report.missing.contains(&('0' as u32)));
+        assert!(!report.missing.contains(&0x30A2));
+        assert!(report.ratio() > 0.0 && report.ratio() < 1.0);
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains("\"character_set\":\"Japanese\""));
+    }
+
+    #[test]
+    fn test_check_all_sets() {
+        let font = build_test_font(&[(0x0E00, 0x0E7F)]);
+        let coverage = FontCoverage::from_bytes(&font, 0).unwrap();
+        let reports = coverage.check_all();
+
+        assert_eq!(reports.len(), CharacterSet::all_sets().len());
+        let thai = reports
+            .iter()
+            .find(|r| r.character_set == CharacterSet::Thai)
+            .unwrap();
+        assert!(thai.is_complete());
+    }
+}
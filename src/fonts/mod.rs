@@ -0,0 +1,13 @@
+//! Font inspection utilities
+//!
+//! Used to check which glyphs of a character set a font can actually display,
+//! and to cut fonts down to those glyphs for embedding.
+
+mod coverage;
+mod subset;
+
+pub use coverage::{CoverageReport, FontCoverage, FontError};
+pub use subset::subset_font;
+
+#[cfg(test)]
+pub(crate) use coverage::tests::build_test_font as coverage_test_font;
@@ -0,0 +1,529 @@
+//! TrueType font subsetting
+//!
+//! Cuts a face down to the glyphs a set of characters needs, so exported documents
+//! can embed the font without carrying every glyph. Only `glyf` outlines are
+//! supported; CFF-flavoured OpenType fonts are rejected.
+
+use super::coverage::{face_offset, read_cmap, Reader};
+use super::FontError;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Tables copied into the subset unchanged, as they do not refer to glyph ids
+const COPIED_TABLES: [[u8; 4]; 6] = [*b"OS/2", *b"cvt ", *b"fpgm", *b"gasp", *b"name", *b"prep"];
+
+/// Composite glyph component flags
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+/// Largest number of characters a format 4 cmap subtable can hold one segment each
+const MAX_SUBSET_CHARS: usize = 8000;
+
+/// Build a standalone TrueType font holding only the glyphs for `chars`
+///
+/// Glyph 0 (`.notdef`) and the components of composite glyphs are always kept.
+/// Characters the font lacks are ignored. Hinting tables are kept; layout
+/// tables (GSUB, GPOS, kern) are dropped, so ligatures and kerning are lost.
+pub fn subset_font(
+    data: &[u8],
+    face_index: u32,
+    chars: impl IntoIterator<Item = char>,
+) -> Result<Vec<u8>, FontError> {
+    let reader = Reader { data };
+    let face = face_offset(&reader, face_index)?;
+    let tables = table_directory(&reader, face)?;
+    if tables.contains_key(b"CFF ") || tables.contains_key(b"CFF2") {
+        return Err(FontError::Unsupported("CFF outlines cannot be subset"));
+    }
+    let table = |tag: [u8; 4]| {
+        tables
+            .get(&tag)
+            .copied()
+            .ok_or(FontError::MissingTable(tag))
+    };
+
+    let head = table(*b"head")?;
+    let hhea = table(*b"hhea")?;
+    let maxp = table(*b"maxp")?;
+    let hmtx = table(*b"hmtx")?;
+    let loca = table(*b"loca")?;
+    let glyf = table(*b"glyf")?;
+    let num_glyphs = Reader { data: maxp }.u16_at(4)? as u32;
+    let long_loca = Reader { data: head }.u16_at(50)? != 0;
+
+    let glyph_data = |glyph: u32| -> Result<&[u8], FontError> {
+        let loca = Reader { data: loca };
+        let (start, end) = if long_loca {
+            let offset = glyph as usize * 4;
+            (
+                loca.u32_at(offset)? as usize,
+                loca.u32_at(offset + 4)? as usize,
+            )
+        } else {
+            let offset = glyph as usize * 2;
+            (
+                loca.u16_at(offset)? as usize * 2,
+                loca.u16_at(offset + 2)? as usize * 2,
+            )
+        };
+        glyf.get(start..end)
+            .ok_or(FontError::Malformed("glyph outside the glyf table"))
+    };
+
+    // Map the wanted characters to their glyphs, first subtable wins
+    let wanted: BTreeSet<u32> = chars.into_iter().map(|ch| ch as u32).collect();
+    if wanted.len() > MAX_SUBSET_CHARS {
+        return Err(FontError::Unsupported("too many characters for one subset"));
+    }
+    let mut mapping = BTreeMap::new();
+    read_cmap(&reader, face, &mut |code, glyph| {
+        if glyph < num_glyphs && wanted.contains(&code) {
+            mapping.entry(code).or_insert(glyph);
+        }
+    })?;
+
+    // Keep .notdef, the mapped glyphs and everything composites refer to
+    let mut keep: BTreeSet<u32> = mapping.values().copied().collect();
+    keep.insert(0);
+    let mut pending: Vec<u32> = keep.iter().copied().collect();
+    while let Some(glyph) = pending.pop() {
+        let data = glyph_data(glyph)?;
+        for offset in component_offsets(data)? {
+            let component = Reader { data }.u16_at(offset)? as u32;
+            if component >= num_glyphs {
+                return Err(FontError::Malformed("composite refers to a missing glyph"));
+            }
+            if keep.insert(component) {
+                pending.push(component);
+            }
+        }
+    }
+    let new_ids: BTreeMap<u32, u16> = keep
+        .iter()
+        .enumerate()
+        .map(|(new, &old)| (old, new as u16))
+        .collect();
+
+    let mut glyf_out = Vec::new();
+    let mut loca_out = Vec::new();
+    for &old in &keep {
+        loca_out.extend((glyf_out.len() as u32).to_be_bytes());
+        let mut glyph = glyph_data(old)?.to_vec();
+        for offset in component_offsets(&glyph)? {
+            let component = u16::from_be_bytes([glyph[offset], glyph[offset + 1]]) as u32;
+            glyph[offset..offset + 2].copy_from_slice(&new_ids[&component].to_be_bytes());
+        }
+        glyf_out.extend(glyph);
+        glyf_out.resize(glyf_out.len().next_multiple_of(4), 0);
+    }
+    loca_out.extend((glyf_out.len() as u32).to_be_bytes());
+
+    // Every kept glyph gets a full metric so no trailing advance is shared
+    let hmtx_reader = Reader { data: hmtx };
+    let num_metrics = Reader { data: hhea }.u16_at(34)? as u32;
+    if num_metrics == 0 {
+        return Err(FontError::Malformed("hhea has no horizontal metrics"));
+    }
+    let mut hmtx_out = Vec::new();
+    for &old in &keep {
+        let (advance, side_bearing) = if old < num_metrics {
+            let offset = old as usize * 4;
+            (hmtx_reader.u16_at(offset)?, hmtx_reader.u16_at(offset + 2)?)
+        } else {
+            let last = (num_metrics as usize - 1) * 4;
+            let offset = num_metrics as usize * 4 + (old - num_metrics) as usize * 2;
+            (hmtx_reader.u16_at(last)?, hmtx_reader.u16_at(offset)?)
+        };
+        hmtx_out.extend(advance.to_be_bytes());
+        hmtx_out.extend(side_bearing.to_be_bytes());
+    }
+
+    let count = keep.len() as u16;
+    let mut out = BTreeMap::new();
+    out.insert(
+        *b"head",
+        patched(head, &[(8, &[0; 4]), (50, &1u16.to_be_bytes())])?,
+    );
+    out.insert(*b"hhea", patched(hhea, &[(34, &count.to_be_bytes())])?);
+    out.insert(*b"maxp", patched(maxp, &[(4, &count.to_be_bytes())])?);
+    out.insert(*b"hmtx", hmtx_out);
+    out.insert(*b"loca", loca_out);
+    out.insert(*b"glyf", glyf_out);
+    out.insert(
+        *b"cmap",
+        build_cmap(mapping.iter().map(|(&code, glyph)| (code, new_ids[glyph]))),
+    );
+    if let Ok(post) = table(*b"post") {
+        // Version 3 carries no glyph names
+        let mut post = post
+            .get(..32)
+            .ok_or(FontError::Malformed("truncated post table"))?
+            .to_vec();
+        post[..4].copy_from_slice(&0x0003_0000u32.to_be_bytes());
+        out.insert(*b"post", post);
+    }
+    for tag in COPIED_TABLES {
+        if let Some(data) = tables.get(&tag) {
+            out.insert(tag, data.to_vec());
+        }
+    }
+    Ok(write_sfnt(&out))
+}
+
+/// Table contents of a face by tag
+fn table_directory<'a>(
+    reader: &Reader<'a>,
+    face_offset: usize,
+) -> Result<BTreeMap<[u8; 4], &'a [u8]>, FontError> {
+    let num_tables = reader.u16_at(face_offset + 4)? as usize;
+    let mut tables = BTreeMap::new();
+    for i in 0..num_tables {
+        let record = face_offset + 12 + i * 16;
+        let tag = reader.u32_at(record)?.to_be_bytes();
+        let offset = reader.u32_at(record + 8)? as usize;
+        let length = reader.u32_at(record + 12)? as usize;
+        let data = reader
+            .data
+            .get(offset..offset + length)
+            .ok_or(FontError::Malformed("table outside the font data"))?;
+        tables.insert(tag, data);
+    }
+    Ok(tables)
+}
+
+/// Offsets of the component glyph ids inside a composite glyph (empty for simple glyphs)
+fn component_offsets(glyph: &[u8]) -> Result<Vec<usize>, FontError> {
+    let reader = Reader { data: glyph };
+    if glyph.is_empty() || reader.u16_at(0)? & 0x8000 == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut offsets = Vec::new();
+    let mut offset = 10;
+    loop {
+        let flags = reader.u16_at(offset)?;
+        reader.u16_at(offset + 2)?;
+        offsets.push(offset + 2);
+
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            8
+        } else {
+            6
+        };
+        offset += if flags & WE_HAVE_A_SCALE != 0 {
+            2
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            4
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            8
+        } else {
+            0
+        };
+        if flags & MORE_COMPONENTS == 0 {
+            return Ok(offsets);
+        }
+    }
+}
+
+/// Copy a table with some bytes overwritten
+fn patched(table: &[u8], patches: &[(usize, &[u8])]) -> Result<Vec<u8>, FontError> {
+    let mut table = table.to_vec();
+    for &(offset, bytes) in patches {
+        table
+            .get_mut(offset..offset + bytes.len())
+            .ok_or(FontError::Malformed("truncated table"))?
+            .copy_from_slice(bytes);
+    }
+    Ok(table)
+}
+
+/// Binary search header fields (searchRange, entrySelector, rangeShift) for `count` items
+fn search_fields(count: u16, item_size: u16) -> [u16; 3] {
+    let selector = count.max(1).ilog2() as u16;
+    let range = (1 << selector) * item_size;
+    [range, selector, count * item_size - range]
+}
+
+/// A cmap with a format 4 subtable for the BMP and a format 12 subtable for everything
+fn build_cmap(mapping: impl Iterator<Item = (u32, u16)>) -> Vec<u8> {
+    let mapping: Vec<(u32, u16)> = mapping.collect();
+
+    // Format 4: one segment per BMP character, then the required 0xFFFF segment
+    let mut segments: Vec<(u16, u16)> = mapping
+        .iter()
+        .filter(|&&(code, _)| code < 0xFFFF)
+        .map(|&(code, glyph)| (code as u16, glyph.wrapping_sub(code as u16)))
+        .collect();
+    segments.push((0xFFFF, 1));
+    let seg_count = segments.len() as u16;
+    let mut format4 = Vec::new();
+    format4.extend(4u16.to_be_bytes());
+    format4.extend((16 + segments.len() as u16 * 8).to_be_bytes());
+    format4.extend(0u16.to_be_bytes()); // language
+    format4.extend((seg_count * 2).to_be_bytes());
+    for field in search_fields(seg_count, 2) {
+        format4.extend(field.to_be_bytes());
+    }
+    for &(code, _) in &segments {
+        format4.extend(code.to_be_bytes()); // endCode
+    }
+    format4.extend(0u16.to_be_bytes()); // reservedPad
+    for &(code, _) in &segments {
+        format4.extend(code.to_be_bytes()); // startCode
+    }
+    for &(_, delta) in &segments {
+        format4.extend(delta.to_be_bytes());
+    }
+    for _ in &segments {
+        format4.extend(0u16.to_be_bytes()); // idRangeOffset
+    }
+
+    // Format 12: runs of consecutive codepoints with consecutive glyphs
+    let mut groups: Vec<(u32, u32, u32)> = Vec::new();
+    for &(code, glyph) in &mapping {
+        match groups.last_mut() {
+            Some((start, end, start_glyph))
+                if code == *end + 1 && glyph as u32 == *start_glyph + (code - *start) =>
+            {
+                *end = code;
+            }
+            _ => groups.push((code, code, glyph as u32)),
+        }
+    }
+    let mut format12 = Vec::new();
+    format12.extend(12u16.to_be_bytes());
+    format12.extend(0u16.to_be_bytes()); // reserved
+    format12.extend((16 + groups.len() as u32 * 12).to_be_bytes());
+    format12.extend(0u32.to_be_bytes()); // language
+    format12.extend((groups.len() as u32).to_be_bytes());
+    for (start, end, glyph) in groups {
+        format12.extend(start.to_be_bytes());
+        format12.extend(end.to_be_bytes());
+        format12.extend(glyph.to_be_bytes());
+    }
+
+    let mut cmap = Vec::new();
+    cmap.extend(0u16.to_be_bytes()); // version
+    cmap.extend(2u16.to_be_bytes()); // numTables
+    for (encoding, offset) in [(1u16, 20u32), (10, 20 + format4.len() as u32)] {
+        cmap.extend(3u16.to_be_bytes()); // platform: Windows
+        cmap.extend(encoding.to_be_bytes());
+        cmap.extend(offset.to_be_bytes());
+    }
+    cmap.extend(format4);
+    cmap.extend(format12);
+    cmap
+}
+
+/// Sum of a table's big-endian 32-bit words, zero padded
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Assemble a TrueType font from tables, filling in `head.checkSumAdjustment`
+fn write_sfnt(tables: &BTreeMap<[u8; 4], Vec<u8>>) -> Vec<u8> {
+    let num_tables = tables.len() as u16;
+    let mut font = Vec::new();
+    font.extend(0x0001_0000u32.to_be_bytes());
+    font.extend(num_tables.to_be_bytes());
+    for field in search_fields(num_tables, 16) {
+        font.extend(field.to_be_bytes());
+    }
+
+    let mut offset = 12 + tables.len() * 16;
+    let mut head_offset = None;
+    for (tag, data) in tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        font.extend(tag);
+        font.extend(checksum(data).to_be_bytes());
+        font.extend((offset as u32).to_be_bytes());
+        font.extend((data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for data in tables.values() {
+        font.extend(data);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+
+    if let Some(head) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&font));
+        font[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fonts::FontCoverage;
+
+    /// A simple glyph: one contour, with filler standing in for the outline data
+    fn simple_glyph(filler: u8) -> Vec<u8> {
+        let mut glyph = vec![0, 1];
+        glyph.extend([filler; 10]);
+        glyph
+    }
+
+    /// A composite glyph with one component using word arguments and a scale
+    fn composite_glyph(component: u16) -> Vec<u8> {
+        let mut glyph = vec![0xFF, 0xFF];
+        glyph.extend([0u8; 8]); // bounding box
+        glyph.extend((ARG_1_AND_2_ARE_WORDS | WE_HAVE_A_SCALE).to_be_bytes());
+        glyph.extend(component.to_be_bytes());
+        glyph.extend([0u8; 4]); // dx, dy
+        glyph.extend(0x4000u16.to_be_bytes()); // scale 1.0
+        glyph
+    }
+
+    /// Five glyphs: .notdef (empty), 'A', 'B' (composite of glyph 3), glyph 3 and 'C'
+    /// Only the first two glyphs have full metrics.
+    fn build_font() -> Vec<u8> {
+        let glyphs = [
+            Vec::new(),
+            simple_glyph(1),
+            composite_glyph(3),
+            simple_glyph(3),
+            simple_glyph(4),
+        ];
+        let mut glyf = Vec::new();
+        let mut loca = Vec::new();
+        for glyph in &glyphs {
+            loca.extend(((glyf.len() / 2) as u16).to_be_bytes());
+            glyf.extend(glyph);
+        }
+        loca.extend(((glyf.len() / 2) as u16).to_be_bytes());
+
+        let mut head = vec![0u8; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes()); // unitsPerEm
+        let mut hhea = vec![0u8; 36];
+        hhea[34..36].copy_from_slice(&2u16.to_be_bytes());
+        let mut maxp = vec![0u8; 6];
+        maxp[..4].copy_from_slice(&0x0000_5000u32.to_be_bytes());
+        maxp[4..6].copy_from_slice(&5u16.to_be_bytes());
+        let mut hmtx = Vec::new();
+        for (advance, side_bearing) in [(500u16, 0u16), (600, 10)] {
+            hmtx.extend(advance.to_be_bytes());
+            hmtx.extend(side_bearing.to_be_bytes());
+        }
+        for side_bearing in [20u16, 30, 40] {
+            hmtx.extend(side_bearing.to_be_bytes());
+        }
+
+        let tables = BTreeMap::from([
+            (
+                *b"cmap",
+                build_cmap([(0x41, 1), (0x42, 2), (0x43, 4)].into_iter()),
+            ),
+            (*b"glyf", glyf),
+            (*b"head", head),
+            (*b"hhea", hhea),
+            (*b"hmtx", hmtx),
+            (*b"loca", loca),
+            (*b"maxp", maxp),
+            (*b"name", vec![0u8; 6]),
+        ]);
+        write_sfnt(&tables)
+    }
+
+    #[test]
+    fn test_subset_keeps_components() {
+        let subset = subset_font(&build_font(), 0, ['B', 'Z']).unwrap();
+        let reader = Reader { data: &subset };
+        let tables = table_directory(&reader, 0).unwrap();
+
+        // .notdef, 'B' and its component, renumbered 0-2
+        assert_eq!(
+            Reader {
+                data: tables[b"maxp"]
+            }
+            .u16_at(4)
+            .unwrap(),
+            3
+        );
+        let coverage = FontCoverage::from_bytes(&subset, 0).unwrap();
+        assert!(coverage.contains('B'));
+        assert!(!coverage.contains('A'));
+        assert_eq!(coverage.len(), 1);
+
+        let loca = Reader {
+            data: tables[b"loca"],
+        };
+        let start = loca.u32_at(4).unwrap() as usize;
+        let end = loca.u32_at(8).unwrap() as usize;
+        assert_eq!(&tables[b"glyf"][start..end], composite_glyph(2).as_slice());
+        assert!(tables.contains_key(b"name"));
+    }
+
+    #[test]
+    fn test_subset_metrics() {
+        let subset = subset_font(&build_font(), 0, ['A', 'C']).unwrap();
+        let reader = Reader { data: &subset };
+        let tables = table_directory(&reader, 0).unwrap();
+
+        assert_eq!(
+            Reader {
+                data: tables[b"hhea"]
+            }
+            .u16_at(34)
+            .unwrap(),
+            3
+        );
+        // Glyph 4 shared the last advance and had its own side bearing
+        let hmtx = Reader {
+            data: tables[b"hmtx"],
+        };
+        let metrics: Vec<u16> = (0..6).map(|i| hmtx.u16_at(i * 2).unwrap()).collect();
+        assert_eq!(metrics, [500, 0, 600, 10, 600, 40]);
+    }
+
+    #[test]
+    fn test_subset_checksums() {
+        let subset = subset_font(&build_font(), 0, ['A']).unwrap();
+        assert_eq!(subset.len() % 4, 0);
+        assert_eq!(checksum(&subset), 0xB1B0_AFBA);
+    }
+
+    #[test]
+    fn test_rejects_cff() {
+        let tables = BTreeMap::from([
+            (*b"CFF ", vec![0u8; 4]),
+            (*b"cmap", build_cmap([].into_iter())),
+        ]);
+        let font = write_sfnt(&tables);
+        assert!(matches!(
+            subset_font(&font, 0, ['A']),
+            Err(FontError::Unsupported(_))
+        ));
+
+        let no_glyf = crate::fonts::coverage_test_font(&[(0x41, 0x42)]);
+        assert!(matches!(
+            subset_font(&no_glyf, 0, ['A']),
+            Err(FontError::MissingTable(_))
+        ));
+    }
+
+    #[cfg(feature = "software")]
+    #[test]
+    fn test_subset_of_system_font_loads() {
+        use ab_glyph::{Font, FontRef};
+
+        let path = "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf";
+        let Ok(data) = std::fs::read(path) else {
+            return;
+        };
+        let subset = subset_font(&data, 0, "Matrix".chars()).unwrap();
+        assert!(subset.len() < data.len() / 10);
+
+        let font = FontRef::try_from_slice(&subset).unwrap();
+        assert!(font.outline(font.glyph_id('M')).is_some());
+        assert_eq!(font.glyph_id('Z').0, 0);
+    }
+}
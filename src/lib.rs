@@ -5,14 +5,20 @@
 
 pub mod config;
 pub mod engine;
+pub mod fonts;
 pub mod rendering;
 
+#[cfg(feature = "software")]
+pub mod export;
 #[cfg(feature = "ffi")]
 pub mod ffi;
 
-pub use config::{CharacterSet, ColorScheme, RainSpeed, ScreenSaverConfig};
-pub use engine::{MatrixRain, RainColumn};
-pub use rendering::{Color, Renderer};
+pub use config::{
+    CharacterSet, ColorScheme, CustomColors, GlyphTransformConfig, RainSpeed, ScreenSaverConfig,
+};
+pub use engine::{GlyphPool, MatrixRain, RainColumn};
+pub use fonts::FontCoverage;
+pub use rendering::{BlendMode, Color, ExtendedColor, Glyph, GlyphTransform, Renderer};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,104 @@
+//! Resolved screen background that renderers paint before the glyphs
+
+use super::Color;
+
+/// What to paint behind the rain, in final colours
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backdrop {
+    /// One colour everywhere
+    Solid(Color),
+    /// Top-to-bottom gradient
+    Vertical {
+        /// Colour along the top edge
+        top: Color,
+        /// Colour along the bottom edge
+        bottom: Color,
+    },
+    /// Gradient from the screen centre out to the corners
+    Radial {
+        /// Colour at the centre
+        center: Color,
+        /// Colour at the corners
+        edge: Color,
+    },
+    /// Nothing: leave the surface transparent (for overlays)
+    Transparent,
+}
+
+impl Backdrop {
+    /// Colour at a point given in normalized screen coordinates (0.0-1.0, origin top-left)
+    pub fn color_at(&self, x: f32, y: f32) -> Color {
+        match *self {
+            Backdrop::Solid(color) => color,
+            Backdrop::Vertical { top, bottom } => top.mix(bottom, y),
+            Backdrop::Radial { center, edge } => {
+                let (dx, dy) = (x - 0.5, y - 0.5);
+                let distance = (dx * dx + dy * dy).sqrt() / std::f32::consts::FRAC_1_SQRT_2;
+                center.mix(edge, distance)
+            }
+            Backdrop::Transparent => Color::rgba(0, 0, 0, 0.0),
+        }
+    }
+
+    /// A single colour standing in for the backdrop, for renderers that can only clear
+    pub fn base_color(&self) -> Color {
+        match *self {
+            Backdrop::Solid(color) => color,
+            Backdrop::Vertical { top, bottom } => top.mix(bottom, 0.5),
+            Backdrop::Radial { center, edge } => center.mix(edge, 0.5),
+            Backdrop::Transparent => Color::rgba(0, 0, 0, 0.0),
+        }
+    }
+
+    /// Check whether nothing should be painted
+    pub fn is_transparent(&self) -> bool {
+        matches!(self, Backdrop::Transparent)
+    }
+}
+
+impl Default for Backdrop {
+    fn default() -> Self {
+        Backdrop::Solid(Color::BLACK)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solid() {
+        let backdrop = Backdrop::Solid(Color::rgb(1, 2, 3));
+        assert_eq!(backdrop.color_at(0.2, 0.9), Color::rgb(1, 2, 3));
+        assert_eq!(backdrop.base_color(), Color::rgb(1, 2, 3));
+        assert_eq!(Backdrop::default().base_color(), Color::BLACK);
+    }
+
+    #[test]
+    fn test_vertical() {
+        let backdrop = Backdrop::Vertical {
+            top: Color::BLACK,
+            bottom: Color::rgb(0, 100, 0),
+        };
+        assert_eq!(backdrop.color_at(0.5, 0.0), Color::BLACK);
+        assert_eq!(backdrop.color_at(0.5, 1.0), Color::rgb(0, 100, 0));
+        assert_eq!(backdrop.base_color(), Color::rgb(0, 50, 0));
+    }
+
+    #[test]
+    fn test_radial() {
+        let backdrop = Backdrop::Radial {
+            center: Color::rgb(0, 80, 0),
+            edge: Color::BLACK,
+        };
+        assert_eq!(backdrop.color_at(0.5, 0.5), Color::rgb(0, 80, 0));
+        assert_eq!(backdrop.color_at(0.0, 0.0), Color::BLACK);
+        assert_eq!(backdrop.color_at(1.0, 1.0), Color::BLACK);
+    }
+
+    #[test]
+    fn test_transparent() {
+        assert!(Backdrop::Transparent.is_transparent());
+        assert_eq!(Backdrop::Transparent.color_at(0.5, 0.5).a, 0.0);
+    }
+}
@@ -0,0 +1,198 @@
+//! Glyphs grouped by drawing style, so hosts can set up attributes once per group
+
+use super::{Color, RenderChar};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Steps each colour channel (and alpha) is rounded to when grouping glyphs
+pub const BATCH_COLOR_STEPS: u8 = 32;
+
+/// Glyphs that share a font size, glow, brightness and quantized colour
+///
+/// Hosts can draw a whole batch with one set of text attributes in `color`;
+/// each glyph still carries its exact colour for hosts that want it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderBatch {
+    /// Font size shared by every glyph
+    pub font_size: f32,
+    /// Colour of the batch, quantized to `BATCH_COLOR_STEPS` per channel
+    pub color: Color,
+    /// Glow intensity shared by every glyph
+    pub glow: f32,
+    /// Extended-range brightness shared by every glyph
+    pub intensity: f32,
+    /// The glyphs, in the order they were drawn in
+    pub chars: Vec<RenderChar>,
+}
+
+impl RenderBatch {
+    /// Number of glyphs in the batch
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    /// Check whether the batch has no glyphs
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+}
+
+/// Group glyphs into batches, ordered by each batch's first glyph
+///
+/// Glyphs move to their batch, so callers whose glyphs may overlap should batch
+/// each stacking layer separately and draw the layers in order.
+pub fn batch_render_chars(chars: impl IntoIterator<Item = RenderChar>) -> Vec<RenderBatch> {
+    let mut batches: Vec<RenderBatch> = Vec::new();
+    let mut index: HashMap<[u32; 5], usize> = HashMap::new();
+    for render_char in chars {
+        let color = quantize_color(render_char.color);
+        let key = [
+            u32::from_be_bytes([color.r, color.g, color.b, 0]),
+            color.a.to_bits(),
+            render_char.font_size.to_bits(),
+            render_char.glow.to_bits(),
+            render_char.intensity.to_bits(),
+        ];
+        match index.get(&key) {
+            Some(&i) => batches[i].chars.push(render_char),
+            None => {
+                index.insert(key, batches.len());
+                batches.push(RenderBatch {
+                    font_size: render_char.font_size,
+                    color,
+                    glow: render_char.glow,
+                    intensity: render_char.intensity,
+                    chars: vec![render_char],
+                });
+            }
+        }
+    }
+    batches
+}
+
+/// Round a colour to `BATCH_COLOR_STEPS` levels per channel
+pub fn quantize_color(color: Color) -> Color {
+    let levels = (BATCH_COLOR_STEPS - 1) as f32;
+    let channel =
+        |value: u8| ((value as f32 / 255.0 * levels).round() / levels * 255.0).round() as u8;
+    Color::rgba(
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        (color.a.clamp(0.0, 1.0) * levels).round() / levels,
+    )
+}
+
+/// How a frame was drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameStats {
+    /// Glyphs drawn
+    pub glyphs: usize,
+    /// Batches they were drawn in
+    pub batches: usize,
+}
+
+impl FrameStats {
+    /// Count the glyphs and batches of a frame
+    pub fn of(batches: &[RenderBatch]) -> Self {
+        Self {
+            glyphs: batches.iter().map(RenderBatch::len).sum(),
+            batches: batches.len(),
+        }
+    }
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} glyphs in {} batches", self.glyphs, self.batches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::GlyphTransform;
+
+    fn render_char(ch: char, color: Color, font_size: f32) -> RenderChar {
+        RenderChar {
+            character: ch.into(),
+            glyph_index: None,
+            x: 0.0,
+            y: 0.0,
+            color,
+            font_size,
+            transform: GlyphTransform::NONE,
+            leader: false,
+            glow: 0.0,
+            intensity: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_quantize() {
+        assert_eq!(quantize_color(Color::WHITE), Color::WHITE);
+        assert_eq!(quantize_color(Color::BLACK), Color::BLACK);
+        // Nearby colours share a step; alpha is stepped too
+        let a = quantize_color(Color::rgba(100, 200, 30, 0.5));
+        assert_eq!(a, quantize_color(Color::rgba(102, 199, 31, 0.51)));
+        assert_ne!(a, quantize_color(Color::rgba(120, 200, 30, 0.5)));
+        assert!((a.a - 16.0 / 31.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_groups_by_style_in_first_seen_order() {
+        let green = Color::rgb(0, 200, 60);
+        let chars = vec![
+            render_char('a', green, 16.0),
+            render_char('b', Color::WHITE, 16.0),
+            render_char('c', green, 14.4),
+            render_char('d', Color::rgb(0, 201, 60), 16.0),
+            render_char('e', Color::WHITE, 16.0),
+        ];
+        let batches = batch_render_chars(chars);
+
+        let summary: Vec<(f32, String)> = batches
+            .iter()
+            .map(|batch| {
+                let text = batch
+                    .chars
+                    .iter()
+                    .map(|rc| rc.character.to_string())
+                    .collect();
+                (batch.font_size, text)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (16.0, "ad".to_string()),
+                (16.0, "be".to_string()),
+                (14.4, "c".to_string())
+            ]
+        );
+        // Glyphs keep their exact colours
+        assert_eq!(batches[0].chars[1].color.g, 201);
+        assert_eq!(
+            FrameStats::of(&batches),
+            FrameStats {
+                glyphs: 5,
+                batches: 3
+            }
+        );
+        assert_eq!(
+            FrameStats::of(&batches).to_string(),
+            "5 glyphs in 3 batches"
+        );
+    }
+
+    #[test]
+    fn test_glow_and_intensity_split_batches() {
+        let mut leader = render_char('a', Color::WHITE, 16.0);
+        leader.glow = 0.5;
+        let mut bright = render_char('b', Color::WHITE, 16.0);
+        bright.intensity = 2.0;
+        let plain = render_char('c', Color::WHITE, 16.0);
+        assert_eq!(batch_render_chars(vec![leader, bright, plain]).len(), 3);
+        assert!(batch_render_chars(Vec::new()).is_empty());
+    }
+}
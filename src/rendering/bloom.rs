@@ -0,0 +1,494 @@
+//! Bloom post-process for software framebuffers
+//!
+//! Bright pixels are extracted at half resolution, blurred at several scales
+//! with separable Gaussians and added back onto the frame. Every scale works on
+//! a buffer half the size of the one before, so the wide blurs stay cheap, and
+//! the scale buffers are reused from frame to frame. Each pass is split into bands
+//! of rows spread across the available cores.
+
+use super::software::for_each_band;
+use super::Framebuffer;
+use crate::config::BloomConfig;
+
+/// Most blur scales combined, whatever the config asks for
+const MAX_SCALES: u32 = 6;
+
+/// Widest blur radius of the finest scale in pixels, whatever the config asks for
+const MAX_RADIUS: f32 = 64.0;
+
+/// One blur scale at a fraction of the frame size
+///
+/// Pixels are linear RGB plus the glow's brightness, which becomes its alpha;
+/// four lanes also let the blur loops vectorize.
+#[derive(Debug, Clone, Default)]
+struct Level {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Level {
+    fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.pixels.resize(width * height, [0.0; 4]);
+    }
+
+    fn row(&self, y: usize) -> &[[f32; 4]] {
+        &self.pixels[y * self.width..(y + 1) * self.width]
+    }
+}
+
+/// Applies a `BloomConfig` to framebuffers
+#[derive(Debug, Clone)]
+pub struct Bloom {
+    config: BloomConfig,
+    levels: Vec<Level>,
+    /// Holds the horizontal pass of a blur
+    scratch: Vec<[f32; 4]>,
+    kernel: Vec<f32>,
+}
+
+impl Bloom {
+    /// Create a bloom stage with the given settings
+    pub fn new(config: BloomConfig) -> Self {
+        let config = clamped(config);
+        Self {
+            config,
+            levels: Vec::new(),
+            scratch: Vec::new(),
+            kernel: gaussian_kernel(config.radius / 2.0),
+        }
+    }
+
+    /// The current settings
+    pub fn config(&self) -> &BloomConfig {
+        &self.config
+    }
+
+    /// Change the settings (takes effect on the next `apply`)
+    pub fn set_config(&mut self, config: BloomConfig) {
+        let config = clamped(config);
+        if config.radius != self.config.radius {
+            self.kernel = gaussian_kernel(config.radius / 2.0);
+        }
+        self.config = config;
+    }
+
+    /// Add the glow of the framebuffer's bright pixels onto it
+    /// Does nothing when the config is inactive.
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer) {
+        if !self.config.is_active() || framebuffer.width() == 0 || framebuffer.height() == 0 {
+            return;
+        }
+        self.allocate(framebuffer.width() as usize, framebuffer.height() as usize);
+
+        bright_pass(framebuffer, &mut self.levels[0], self.config.threshold);
+        for i in 1..self.levels.len() {
+            let (finer, coarser) = self.levels.split_at_mut(i);
+            downsample(&finer[i - 1], &mut coarser[0]);
+        }
+        for level in &mut self.levels {
+            blur(level, &mut self.scratch, &self.kernel);
+        }
+        // Fold the coarse scales into the finest so the frame is sampled only once
+        for i in (1..self.levels.len()).rev() {
+            let (finer, coarser) = self.levels.split_at_mut(i);
+            upsample_add(&coarser[0], &mut finer[i - 1]);
+        }
+
+        let strength = self.config.intensity / self.levels.len() as f32;
+        composite(&self.levels[0], framebuffer, strength);
+    }
+
+    /// Size the scale buffers for a frame: half size, then halving per scale
+    fn allocate(&mut self, width: usize, height: usize) {
+        let mut scales = self.config.scales.clamp(1, MAX_SCALES) as usize;
+        let (mut level_width, mut level_height) = ((width / 2).max(1), (height / 2).max(1));
+        self.levels.resize_with(scales, Level::default);
+        for (i, level) in self.levels.iter_mut().enumerate() {
+            level.resize(level_width, level_height);
+            if level_width == 1 && level_height == 1 {
+                scales = i + 1;
+                break;
+            }
+            level_width = (level_width / 2).max(1);
+            level_height = (level_height / 2).max(1);
+        }
+        self.levels.truncate(scales);
+    }
+}
+
+/// Bound settings to what the blur can handle
+///
+/// The kernel grows with the radius, so it is capped; negative or NaN values
+/// become zero and infinite ones the largest allowed.
+fn clamped(config: BloomConfig) -> BloomConfig {
+    let bounded = |value: f32, max: f32| {
+        if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, max)
+        }
+    };
+    BloomConfig {
+        radius: bounded(config.radius, MAX_RADIUS),
+        intensity: bounded(config.intensity, f32::MAX),
+        threshold: bounded(config.threshold, f32::MAX),
+        ..config
+    }
+}
+
+/// Normalized Gaussian weights spanning about two standard deviations each side
+fn gaussian_kernel(radius: f32) -> Vec<f32> {
+    let radius = radius.max(0.5);
+    let sigma = radius / 2.0;
+    let taps = radius.ceil() as i32;
+    let weights: Vec<f32> = (-taps..=taps)
+        .map(|offset| (-(offset * offset) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = weights.iter().sum();
+    weights.into_iter().map(|weight| weight / total).collect()
+}
+
+/// Downsample the frame 2×2 into the first scale, keeping only what exceeds the threshold
+///
+/// A soft knee eases pixels in just below the threshold, so glyphs fading
+/// through it do not pop.
+fn bright_pass(framebuffer: &Framebuffer, level: &mut Level, threshold: f32) {
+    let width = framebuffer.width() as usize;
+    let height = framebuffer.height() as usize;
+    let pixels = framebuffer.pixels();
+    let knee = (threshold * 0.5).max(1e-4);
+
+    for_each_band(&mut level.pixels, level.width, |first, band| {
+        for (y, out) in (first..).zip(band.chunks_exact_mut(level.width)) {
+            let top = &pixels[(y * 2).min(height - 1) * width..][..width];
+            let bottom = &pixels[(y * 2 + 1).min(height - 1) * width..][..width];
+            for (x, pixel) in out.iter_mut().enumerate() {
+                let mut sum = average_block(top, bottom, x);
+                let brightness = sum[0].max(sum[1]).max(sum[2]);
+                let soft = (brightness - threshold + knee).clamp(0.0, 2.0 * knee);
+                let soft = soft * soft / (4.0 * knee);
+                let contribution = soft.max(brightness - threshold) / brightness.max(1e-4);
+                sum[3] = brightness;
+                *pixel = sum.map(|channel| channel * contribution);
+            }
+        }
+    });
+}
+
+/// Average the 2×2 block under pixel `x` of the half-width row, repeating the
+/// last column of odd-width rows
+#[inline]
+fn average_block(top: &[[f32; 4]], bottom: &[[f32; 4]], x: usize) -> [f32; 4] {
+    let left = 2 * x;
+    let right = (left + 1).min(top.len() - 1);
+    let (a, b, c, d) = (top[left], top[right], bottom[left], bottom[right]);
+    [0, 1, 2, 3].map(|channel| (a[channel] + b[channel] + c[channel] + d[channel]) * 0.25)
+}
+
+/// Average 2×2 blocks of a scale into the next coarser one
+fn downsample(source: &Level, target: &mut Level) {
+    for_each_band(&mut target.pixels, target.width, |first, band| {
+        for (y, out) in (first..).zip(band.chunks_exact_mut(target.width)) {
+            let top = source.row((y * 2).min(source.height - 1));
+            let bottom = source.row((y * 2 + 1).min(source.height - 1));
+            for (x, pixel) in out.iter_mut().enumerate() {
+                *pixel = average_block(top, bottom, x);
+            }
+        }
+    });
+}
+
+/// Separable Gaussian blur in place, clamping at the edges
+fn blur(level: &mut Level, scratch: &mut Vec<[f32; 4]>, kernel: &[f32]) {
+    let (width, height) = (level.width, level.height);
+    let radius = kernel.len() / 2;
+    scratch.resize(width * height, [0.0; 4]);
+
+    // Horizontal: level -> scratch, through a row padded with its edge pixels so
+    // each output is a plain dot product over a window
+    let source = &level.pixels;
+    for_each_band(scratch, width, |first, band| {
+        let mut padded = Vec::with_capacity(width + 2 * radius);
+        for (row, out) in source[first * width..]
+            .chunks_exact(width)
+            .zip(band.chunks_exact_mut(width))
+        {
+            padded.clear();
+            padded.extend(std::iter::repeat_n(row[0], radius));
+            padded.extend_from_slice(row);
+            padded.extend(std::iter::repeat_n(row[width - 1], radius));
+            for (pixel, window) in out.iter_mut().zip(padded.windows(kernel.len())) {
+                let mut sum = window[radius].map(|channel| channel * kernel[radius]);
+                for (offset, &weight) in kernel[radius + 1..].iter().enumerate() {
+                    let (left, right) = (window[radius - offset - 1], window[radius + offset + 1]);
+                    for channel in 0..4 {
+                        sum[channel] += (left[channel] + right[channel]) * weight;
+                    }
+                }
+                *pixel = sum;
+            }
+        }
+    });
+
+    // Vertical: scratch -> level, whole rows at a time for cache-friendly access
+    let scratch = &scratch[..];
+    for_each_band(&mut level.pixels, width, |first, band| {
+        let row = |y: usize| &scratch[y * width..(y + 1) * width];
+        for (y, out) in (first..).zip(band.chunks_exact_mut(width)) {
+            let centre = kernel[radius];
+            for (pixel, source) in out.iter_mut().zip(row(y)) {
+                *pixel = source.map(|channel| channel * centre);
+            }
+            // The kernel is symmetric, so rows the same distance above and below share a weight
+            for (offset, &weight) in kernel[radius + 1..].iter().enumerate() {
+                let above = row(y.saturating_sub(offset + 1));
+                let below = row((y + offset + 1).min(height - 1));
+                for ((pixel, above), below) in out.iter_mut().zip(above).zip(below) {
+                    for channel in 0..4 {
+                        pixel[channel] += (above[channel] + below[channel]) * weight;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// For each pixel of a fine grid, the two coarse samples to interpolate between
+fn sample_axis(fine: usize, coarse: usize) -> Vec<(usize, usize, f32)> {
+    let scale = coarse as f32 / fine as f32;
+    (0..fine)
+        .map(|i| {
+            let position = ((i as f32 + 0.5) * scale - 0.5).max(0.0);
+            let first = (position as usize).min(coarse - 1);
+            let second = (first + 1).min(coarse - 1);
+            (first, second, position - first as f32)
+        })
+        .collect()
+}
+
+/// Bilinearly upsample a scale onto `pixels`, a `width`-wide buffer, handing
+/// each target pixel and its upsampled value to `merge`
+fn upsample_into<F>(coarse: &Level, pixels: &mut [[f32; 4]], width: usize, merge: F)
+where
+    F: Fn(&mut [f32; 4], [f32; 4]) + Sync,
+{
+    let columns = sample_axis(width, coarse.width);
+    let rows = sample_axis(pixels.len() / width, coarse.height);
+
+    for_each_band(pixels, width, |first, band| {
+        let mut line = vec![[0.0f32; 4]; coarse.width];
+        for (&(top, bottom, ty), out) in rows[first..].iter().zip(band.chunks_exact_mut(width)) {
+            // Interpolate vertically once per coarse pixel, then horizontally per fine pixel
+            for ((value, top), bottom) in
+                line.iter_mut().zip(coarse.row(top)).zip(coarse.row(bottom))
+            {
+                for channel in 0..4 {
+                    value[channel] = top[channel] + (bottom[channel] - top[channel]) * ty;
+                }
+            }
+            for (pixel, &(left, right, tx)) in out.iter_mut().zip(&columns) {
+                let (left, right) = (line[left], line[right]);
+                let mut value = [0.0f32; 4];
+                for channel in 0..4 {
+                    value[channel] = left[channel] + (right[channel] - left[channel]) * tx;
+                }
+                merge(pixel, value);
+            }
+        }
+    });
+}
+
+/// Add a coarse scale, bilinearly upsampled, onto the next finer one
+fn upsample_add(coarse: &Level, fine: &mut Level) {
+    upsample_into(coarse, &mut fine.pixels, fine.width, |pixel, value| {
+        for channel in 0..4 {
+            pixel[channel] += value[channel];
+        }
+    });
+}
+
+/// Add the glow onto the frame
+/// Alpha grows with the glow so it also shows over a transparent backdrop.
+fn composite(glow: &Level, framebuffer: &mut Framebuffer, strength: f32) {
+    let width = framebuffer.width() as usize;
+    upsample_into(glow, framebuffer.pixels_mut(), width, |pixel, value| {
+        for channel in 0..4 {
+            pixel[channel] += value[channel] * strength;
+        }
+        pixel[3] = pixel[3].min(1.0);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::software::thread_count;
+    use crate::rendering::{BlendMode, Color};
+
+    /// A black frame with one bright square in the middle
+    fn frame_with_square(color: Color) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(64, 48);
+        framebuffer.clear(Color::BLACK);
+        for y in 20..28 {
+            for x in 28..36 {
+                framebuffer.blend(x, y, color.premultiplied(), BlendMode::Normal);
+            }
+        }
+        framebuffer
+    }
+
+    #[test]
+    fn test_kernel_is_normalized() {
+        for radius in [0.0, 1.0, 3.5, 12.0] {
+            let kernel = gaussian_kernel(radius);
+            assert_eq!(kernel.len() % 2, 1);
+            assert!((kernel.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+            assert!(kernel[kernel.len() / 2] >= kernel[0]);
+        }
+    }
+
+    #[test]
+    fn test_bright_pixels_glow() {
+        let mut framebuffer = frame_with_square(Color::WHITE);
+        Bloom::new(BloomConfig::enabled()).apply(&mut framebuffer);
+
+        // Light spills around the square, fading with distance
+        let near = framebuffer.pixel(26, 24).unwrap();
+        let far = framebuffer.pixel(18, 24).unwrap();
+        assert!(near.r > far.r);
+        assert!(far.r > 0);
+        assert!(framebuffer.pixel(0, 0).unwrap().r < far.r);
+        assert_eq!(framebuffer.pixel(32, 24).unwrap(), Color::WHITE);
+    }
+
+    #[test]
+    fn test_dim_pixels_do_not_glow() {
+        let mut framebuffer = frame_with_square(Color::rgb(40, 80, 40));
+        let before = framebuffer.clone();
+        Bloom::new(BloomConfig::enabled()).apply(&mut framebuffer);
+        assert_eq!(framebuffer, before);
+    }
+
+    #[test]
+    fn test_inactive_config_is_a_no_op() {
+        let mut framebuffer = frame_with_square(Color::WHITE);
+        let before = framebuffer.clone();
+        Bloom::new(BloomConfig::default()).apply(&mut framebuffer);
+        assert_eq!(framebuffer, before);
+    }
+
+    #[test]
+    fn test_radius_and_intensity() {
+        let glow_at = |config: BloomConfig, x| {
+            let mut framebuffer = frame_with_square(Color::WHITE);
+            Bloom::new(config).apply(&mut framebuffer);
+            framebuffer.pixel(x, 24).unwrap().g
+        };
+        let base = BloomConfig::enabled();
+
+        assert!(
+            glow_at(
+                BloomConfig {
+                    radius: 16.0,
+                    ..base
+                },
+                14
+            ) > glow_at(base, 14)
+        );
+        assert!(
+            glow_at(
+                BloomConfig {
+                    intensity: 2.0,
+                    ..base
+                },
+                24
+            ) > glow_at(base, 24)
+        );
+    }
+
+    #[test]
+    fn test_settings_are_clamped() {
+        let bloom = Bloom::new(BloomConfig {
+            radius: f32::INFINITY,
+            intensity: -1.0,
+            threshold: f32::NAN,
+            ..BloomConfig::enabled()
+        });
+        assert_eq!(bloom.config().radius, MAX_RADIUS);
+        assert_eq!(bloom.config().intensity, 0.0);
+        assert_eq!(bloom.config().threshold, 0.0);
+        assert_eq!(bloom.kernel.len(), gaussian_kernel(MAX_RADIUS / 2.0).len());
+
+        // A huge radius still renders, with the capped kernel
+        let mut bloom = Bloom::new(BloomConfig::enabled());
+        bloom.set_config(BloomConfig {
+            radius: 1e9,
+            ..BloomConfig::enabled()
+        });
+        assert_eq!(bloom.config().radius, MAX_RADIUS);
+        let mut framebuffer = frame_with_square(Color::WHITE);
+        bloom.apply(&mut framebuffer);
+        assert!(framebuffer.pixel(0, 0).unwrap().r > 0);
+    }
+
+    /// Bloom at the default settings must fit a 60 fps frame (16.7 ms) on four
+    /// threads, as on a typical laptop; with fewer threads the budget grows in
+    /// proportion. Run with `cargo test --release -- --ignored --nocapture bloom`.
+    #[test]
+    #[ignore]
+    fn test_full_hd_frame_time() {
+        let mut framebuffer = Framebuffer::new(1920, 1080);
+        framebuffer.clear(Color::BLACK);
+        for y in (0..1080).step_by(19) {
+            for x in (0..1920).step_by(29) {
+                framebuffer.blend(x, y, Color::WHITE.premultiplied(), BlendMode::Normal);
+            }
+        }
+        let mut bloom = Bloom::new(BloomConfig::enabled());
+        bloom.apply(&mut framebuffer.clone());
+
+        let mut frames = vec![framebuffer; 10];
+        let start = std::time::Instant::now();
+        for frame in &mut frames {
+            bloom.apply(frame);
+        }
+        let per_frame = start.elapsed() / frames.len() as u32;
+
+        let threads = thread_count().min(4) as u32;
+        let budget = std::time::Duration::from_micros(16_667) * 4 / threads;
+        println!(
+            "bloom on a 1920x1080 frame: {:?} on {} thread(s), budget {:?}",
+            per_frame, threads, budget
+        );
+        assert!(per_frame < budget);
+    }
+
+    #[test]
+    fn test_glow_over_transparent_frame() {
+        let mut framebuffer = Framebuffer::new(32, 32);
+        for y in 12..20 {
+            for x in 12..20 {
+                framebuffer.blend(x, y, Color::WHITE.premultiplied(), BlendMode::Normal);
+            }
+        }
+        Bloom::new(BloomConfig::enabled()).apply(&mut framebuffer);
+        assert!(framebuffer.pixel(8, 16).unwrap().a > 0.0);
+    }
+
+    #[test]
+    fn test_tiny_frames_and_reuse() {
+        let mut bloom = Bloom::new(BloomConfig {
+            scales: 6,
+            ..BloomConfig::enabled()
+        });
+        for (width, height) in [(1, 1), (3, 2), (64, 48), (5, 40)] {
+            let mut framebuffer = Framebuffer::new(width, height);
+            framebuffer.clear(Color::WHITE);
+            bloom.apply(&mut framebuffer);
+            assert!(bloom.levels.len() <= 6);
+        }
+    }
+}
@@ -2,6 +2,65 @@
 
 use serde::{Deserialize, Serialize};
 
+/// How a colour is combined with what is already drawn beneath it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum BlendMode {
+    /// Source over destination, weighted by alpha
+    #[default]
+    Normal,
+    /// Light adds up (overlapping glyphs glow brighter)
+    Additive,
+    /// Inverted multiply: brightens like additive without blowing out
+    Screen,
+}
+
+impl BlendMode {
+    /// Composite premultiplied linear-light `src` onto `dst` ([r*a, g*a, b*a, a])
+    pub fn blend(&self, src: [f32; 4], dst: [f32; 4]) -> [f32; 4] {
+        let (sa, da) = (src[3], dst[3]);
+
+        let channel = |s: f32, d: f32| match self {
+            BlendMode::Normal => s + d * (1.0 - sa),
+            BlendMode::Additive => s + d,
+            BlendMode::Screen => s + d - s * d,
+        };
+        let alpha = match self {
+            BlendMode::Additive => (sa + da).min(1.0),
+            BlendMode::Normal | BlendMode::Screen => sa + da * (1.0 - sa),
+        };
+
+        // Additive light can exceed the coverage; clamp so unpremultiplying stays in range
+        let clamp = |c: f32| c.min(alpha);
+        [
+            clamp(channel(src[0], dst[0])),
+            clamp(channel(src[1], dst[1])),
+            clamp(channel(src[2], dst[2])),
+            alpha,
+        ]
+    }
+}
+
+/// Convert an sRGB channel (0-255) to linear light (0.0-1.0)
+pub fn srgb_to_linear(channel: u8) -> f32 {
+    let c = channel as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert linear light (0.0-1.0, clamped) to an sRGB channel (0-255)
+pub fn linear_to_srgb(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let c = if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
 /// Represents an RGBA color
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Color {
@@ -51,7 +110,37 @@ impl Color {
         )
     }
 
+    /// Linear-light channels (0.0-1.0) with straight alpha: [r, g, b, a]
+    pub fn to_linear(&self) -> [f32; 4] {
+        [
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+            self.a,
+        ]
+    }
+
+    /// Create a color from linear-light channels with straight alpha
+    pub fn from_linear([r, g, b, a]: [f32; 4]) -> Self {
+        Self::rgba(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a)
+    }
+
+    /// Linear-light channels premultiplied by alpha: [r*a, g*a, b*a, a]
+    pub fn premultiplied(&self) -> [f32; 4] {
+        let [r, g, b, a] = self.to_linear();
+        [r * a, g * a, b * a, a]
+    }
+
+    /// Create a color from premultiplied linear-light channels
+    pub fn from_premultiplied([r, g, b, a]: [f32; 4]) -> Self {
+        if a <= 0.0 {
+            return Self::rgba(0, 0, 0, 0.0);
+        }
+        Self::from_linear([r / a, g / a, b / a, a])
+    }
+
     /// Darken the color by a factor (0.0 = black, 1.0 = no change)
+    /// Scales the sRGB values directly; see `darken_linear` for a physically even fade
     pub fn darken(&self, factor: f32) -> Self {
         let factor = factor.clamp(0.0, 1.0);
         Self::rgba(
@@ -63,6 +152,7 @@ impl Color {
     }
 
     /// Lighten the color by a factor (0.0 = no change, 1.0 = white)
+    /// Works on the sRGB values directly; see `lighten_linear`
     pub fn lighten(&self, factor: f32) -> Self {
         let factor = factor.clamp(0.0, 1.0);
         Self::rgba(
@@ -73,11 +163,43 @@ impl Color {
         )
     }
 
+    /// Darken the color in linear light (0.0 = black, 1.0 = no change)
+    pub fn darken_linear(&self, factor: f32) -> Self {
+        let factor = factor.clamp(0.0, 1.0);
+        let [r, g, b, a] = self.to_linear();
+        Self::from_linear([r * factor, g * factor, b * factor, a])
+    }
+
+    /// Lighten the color in linear light (0.0 = no change, 1.0 = white)
+    pub fn lighten_linear(&self, factor: f32) -> Self {
+        let factor = factor.clamp(0.0, 1.0);
+        let [r, g, b, a] = self.to_linear();
+        let lift = |c: f32| c + (1.0 - c) * factor;
+        Self::from_linear([lift(r), lift(g), lift(b), a])
+    }
+
+    /// Composite this color onto `dst` in linear light with premultiplied alpha
+    pub fn blend_over(&self, dst: Color, mode: BlendMode) -> Self {
+        Self::from_premultiplied(mode.blend(self.premultiplied(), dst.premultiplied()))
+    }
+
     /// Set the alpha value
     pub fn with_alpha(&self, alpha: f32) -> Self {
         Self::rgba(self.r, self.g, self.b, alpha)
     }
 
+    /// Blend towards another color (0.0 = self, 1.0 = other), including alpha
+    pub fn mix(&self, other: Color, amount: f32) -> Self {
+        let t = amount.clamp(0.0, 1.0);
+        let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+        Self::rgba(
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+            self.a + (other.a - self.a) * t,
+        )
+    }
+
     /// Common colors
     pub const BLACK: Color = Color {
         r: 0,
@@ -171,6 +293,80 @@ mod tests {
         assert_eq!(with_alpha.a, 0.7);
     }
 
+    #[test]
+    fn test_mix() {
+        let mixed = Color::BLACK.mix(Color::rgba(200, 100, 50, 0.0), 0.5);
+        assert_eq!((mixed.r, mixed.g, mixed.b), (100, 50, 25));
+        assert_eq!(mixed.a, 0.5);
+        assert_eq!(Color::BLACK.mix(Color::WHITE, 2.0), Color::WHITE);
+    }
+
+    #[test]
+    fn test_linear_conversion() {
+        for v in [0u8, 1, 10, 128, 200, 255] {
+            assert_eq!(linear_to_srgb(srgb_to_linear(v)), v);
+        }
+        assert_eq!(srgb_to_linear(255), 1.0);
+        assert!((srgb_to_linear(128) - 0.2158).abs() < 1e-3);
+
+        // Half the light is much brighter than half the sRGB value
+        assert_eq!(Color::rgb(255, 255, 255).darken_linear(0.5).r, 188);
+        assert_eq!(Color::BLACK.lighten_linear(1.0), Color::WHITE);
+    }
+
+    #[test]
+    fn test_premultiplied_round_trip() {
+        let color = Color::rgba(0, 255, 70, 0.5);
+        let [r, g, _, a] = color.premultiplied();
+        assert_eq!((r, a), (0.0, 0.5));
+        assert!((g - 0.5).abs() < 1e-6);
+        assert_eq!(Color::from_premultiplied(color.premultiplied()), color);
+        assert_eq!(
+            Color::from_premultiplied([0.3, 0.3, 0.3, 0.0]),
+            Color::rgba(0, 0, 0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_blend_normal() {
+        // Opaque source replaces the destination
+        let green = Color::MATRIX_GREEN;
+        assert_eq!(green.blend_over(Color::WHITE, BlendMode::Normal), green);
+
+        // 50% white over black is half the light, not sRGB 128
+        let half = Color::WHITE
+            .with_alpha(0.5)
+            .blend_over(Color::BLACK, BlendMode::Normal);
+        assert_eq!((half.r, half.a), (188, 1.0));
+
+        // Transparent source leaves the destination unchanged
+        let none = Color::WHITE
+            .with_alpha(0.0)
+            .blend_over(green, BlendMode::Normal);
+        assert_eq!(none, green);
+    }
+
+    #[test]
+    fn test_blend_additive_and_screen() {
+        let red = Color::rgb(255, 0, 0);
+        let green = Color::rgb(0, 255, 0);
+        assert_eq!(
+            red.blend_over(green, BlendMode::Additive),
+            Color::rgb(255, 255, 0)
+        );
+        assert_eq!(
+            Color::WHITE.blend_over(Color::WHITE, BlendMode::Additive),
+            Color::WHITE
+        );
+
+        // Screen with black is the identity, and never darkens
+        let grey = Color::rgb(100, 100, 100);
+        assert_eq!(grey.blend_over(Color::BLACK, BlendMode::Screen), grey);
+        let screened = grey.blend_over(grey, BlendMode::Screen);
+        assert!(screened.r > 100);
+        assert!(screened.r < grey.blend_over(grey, BlendMode::Additive).r);
+    }
+
     #[test]
     fn test_from_tuples() {
         let color1 = Color::from_rgb_tuple((100, 150, 200));
@@ -0,0 +1,300 @@
+//! Retro CRT output stage for software framebuffers
+//!
+//! Phosphor persistence keeps a decaying copy of earlier frames under each new
+//! one, so moving glyphs leave trails. Scanlines then darken the gaps between
+//! rows, and last the picture is bent onto a curved tube, with red and blue
+//! sampled slightly apart like a misconverged set of electron guns.
+
+use super::software::for_each_band;
+use super::Framebuffer;
+use crate::config::CrtConfig;
+use std::f32::consts::{SQRT_2, TAU};
+
+/// Persistence is capped so trails always fade out
+const MAX_PERSISTENCE: f32 = 0.99;
+
+/// Applies a `CrtConfig` to framebuffers
+///
+/// Persistence has to see frames before any other post-processing, so the
+/// stage runs in two halves: `persist` right after drawing and `finish` last.
+/// `apply` runs both back to back.
+#[derive(Debug, Clone)]
+pub struct Crt {
+    config: CrtConfig,
+    /// The glow left on the phosphor: the last persisted frame, undistorted
+    afterglow: Option<Framebuffer>,
+    /// Copy of the frame that distortion samples from
+    source: Vec<[f32; 4]>,
+}
+
+impl Crt {
+    /// Create a CRT stage with the given settings
+    pub fn new(config: CrtConfig) -> Self {
+        Self {
+            config,
+            afterglow: None,
+            source: Vec::new(),
+        }
+    }
+
+    /// The current settings
+    pub fn config(&self) -> &CrtConfig {
+        &self.config
+    }
+
+    /// Change the settings (takes effect on the next frame)
+    pub fn set_config(&mut self, config: CrtConfig) {
+        if !config.has_persistence() {
+            self.afterglow = None;
+        }
+        self.config = config;
+    }
+
+    /// Forget the trails of earlier frames, e.g. after a jump in time
+    pub fn reset(&mut self) {
+        self.afterglow = None;
+    }
+
+    /// Run both halves of the stage on a freshly drawn frame
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer) {
+        self.persist(framebuffer);
+        self.finish(framebuffer);
+    }
+
+    /// Let the previous frames show through, faded, wherever they were brighter
+    ///
+    /// Equivalent to decaying the previous frame instead of clearing it, without
+    /// renderers having to change how they clear.
+    pub fn persist(&mut self, framebuffer: &mut Framebuffer) {
+        if !self.config.has_persistence() {
+            return;
+        }
+        let decay = self.config.persistence.min(MAX_PERSISTENCE);
+        match &mut self.afterglow {
+            Some(afterglow)
+                if afterglow.width() == framebuffer.width()
+                    && afterglow.height() == framebuffer.height() =>
+            {
+                for (pixel, glow) in framebuffer
+                    .pixels_mut()
+                    .iter_mut()
+                    .zip(afterglow.pixels_mut())
+                {
+                    for channel in 0..4 {
+                        pixel[channel] = pixel[channel].max(glow[channel] * decay);
+                    }
+                    *glow = *pixel;
+                }
+            }
+            _ => self.afterglow = Some(framebuffer.clone()),
+        }
+    }
+
+    /// Draw scanlines and bend the frame onto the tube
+    pub fn finish(&mut self, framebuffer: &mut Framebuffer) {
+        if framebuffer.width() == 0 || framebuffer.height() == 0 {
+            return;
+        }
+        if self.config.has_scanlines() {
+            scanlines(
+                framebuffer,
+                self.config.scanlines,
+                self.config.scanline_spacing,
+            );
+        }
+        if self.config.distorts() {
+            self.source.clear();
+            self.source.extend_from_slice(framebuffer.pixels());
+            distort(&self.source, framebuffer, &self.config);
+        }
+    }
+}
+
+/// Darken the rows between scanlines with a smooth cosine profile
+fn scanlines(framebuffer: &mut Framebuffer, strength: f32, spacing: f32) {
+    let width = framebuffer.width() as usize;
+    let strength = strength.clamp(0.0, 1.0);
+    for_each_band(framebuffer.pixels_mut(), width, |first, band| {
+        for (y, row) in (first..).zip(band.chunks_exact_mut(width)) {
+            let gap = 0.5 - 0.5 * ((y as f32 + 0.5) / spacing * TAU).cos();
+            let brightness = 1.0 - strength * gap;
+            for pixel in row {
+                for channel in &mut pixel[..3] {
+                    *channel *= brightness;
+                }
+            }
+        }
+    });
+}
+
+/// Resample `source` into the frame through barrel distortion, taking red and
+/// blue from points pushed outwards and inwards for chromatic aberration
+fn distort(source: &[[f32; 4]], framebuffer: &mut Framebuffer, config: &CrtConfig) {
+    let (width, height) = (framebuffer.width() as usize, framebuffer.height() as usize);
+    let (half_width, half_height) = (width as f32 / 2.0, height as f32 / 2.0);
+    let fringe = config.chromatic_aberration / SQRT_2;
+
+    for_each_band(framebuffer.pixels_mut(), width, |first, band| {
+        for (y, row) in (first..).zip(band.chunks_exact_mut(width)) {
+            let v = (y as f32 + 0.5) / half_height - 1.0;
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let u = (x as f32 + 0.5) / half_width - 1.0;
+                let bend = 1.0 + config.curvature * (u * u + v * v);
+                let (sx, sy) = (
+                    half_width * (1.0 + u * bend),
+                    half_height * (1.0 + v * bend),
+                );
+                if fringe == 0.0 {
+                    *pixel = sample(source, width, height, sx, sy);
+                    continue;
+                }
+
+                // Offsets grow towards the corners, where they reach the configured size
+                let (dx, dy) = (u * fringe, v * fringe);
+                let red = sample(source, width, height, sx + dx, sy + dy);
+                let green = sample(source, width, height, sx, sy);
+                let blue = sample(source, width, height, sx - dx, sy - dy);
+                *pixel = [red[0], green[1], blue[2], red[3].max(green[3]).max(blue[3])];
+            }
+        }
+    });
+}
+
+/// Bilinearly sample a pixel buffer at a point in pixel coordinates
+/// Outside the buffer is transparent black, like the bezel around the tube.
+fn sample(pixels: &[[f32; 4]], width: usize, height: usize, x: f32, y: f32) -> [f32; 4] {
+    let (fx, fy) = (x - 0.5, y - 0.5);
+    let (left, top) = (fx.floor(), fy.floor());
+    let (tx, ty) = (fx - left, fy - top);
+    let fetch = |column: f32, row: f32| {
+        if column < 0.0 || row < 0.0 || column >= width as f32 || row >= height as f32 {
+            [0.0; 4]
+        } else {
+            pixels[row as usize * width + column as usize]
+        }
+    };
+
+    let corners = [
+        (fetch(left, top), (1.0 - tx) * (1.0 - ty)),
+        (fetch(left + 1.0, top), tx * (1.0 - ty)),
+        (fetch(left, top + 1.0), (1.0 - tx) * ty),
+        (fetch(left + 1.0, top + 1.0), tx * ty),
+    ];
+    let mut sum = [0.0f32; 4];
+    for (value, weight) in corners {
+        for channel in 0..4 {
+            sum[channel] += value[channel] * weight;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::{BlendMode, Color};
+
+    #[test]
+    fn test_persistence_leaves_fading_trails() {
+        let mut crt = Crt::new(CrtConfig {
+            persistence: 0.5,
+            ..CrtConfig::default()
+        });
+        let mut framebuffer = Framebuffer::new(4, 4);
+        framebuffer.blend(1, 1, Color::WHITE.premultiplied(), BlendMode::Normal);
+        crt.apply(&mut framebuffer);
+
+        // The next frames are cleared, yet the pixel lingers at half, then a quarter
+        for expected in [0.5, 0.25] {
+            framebuffer.pixels_mut().fill([0.0; 4]);
+            crt.apply(&mut framebuffer);
+            let pixel = framebuffer.pixels()[4 + 1];
+            assert!((pixel[0] - expected).abs() < 1e-6);
+            assert!((pixel[3] - expected).abs() < 1e-6);
+            assert_eq!(framebuffer.pixels()[0], [0.0; 4]);
+        }
+
+        // New content wins over a fading trail, and a resize starts afresh
+        framebuffer.blend(1, 1, Color::WHITE.premultiplied(), BlendMode::Normal);
+        crt.apply(&mut framebuffer);
+        assert_eq!(framebuffer.pixel(1, 1), Some(Color::WHITE));
+        let mut resized = Framebuffer::new(2, 2);
+        crt.apply(&mut resized);
+        assert_eq!(resized.pixels()[3], [0.0; 4]);
+    }
+
+    #[test]
+    fn test_scanlines() {
+        let mut crt = Crt::new(CrtConfig {
+            scanlines: 0.5,
+            scanline_spacing: 4.0,
+            ..CrtConfig::default()
+        });
+        let mut framebuffer = Framebuffer::new(3, 12);
+        framebuffer.clear(Color::WHITE);
+        crt.apply(&mut framebuffer);
+
+        let rows: Vec<f32> = (0..12).map(|y| framebuffer.pixels()[y * 3][0]).collect();
+        // Repeats every four rows, darkest in the gaps, and alpha is untouched
+        for y in 0..8 {
+            assert!((rows[y] - rows[y + 4]).abs() < 1e-5);
+        }
+        let darkest = rows.iter().cloned().fold(1.0, f32::min);
+        assert!((0.5 - 1e-5..0.6).contains(&darkest));
+        assert!(rows.iter().any(|&row| row > 0.9));
+        assert!(framebuffer.pixels().iter().all(|pixel| pixel[3] == 1.0));
+    }
+
+    #[test]
+    fn test_curvature_bends_the_frame_onto_a_tube() {
+        let mut crt = Crt::new(CrtConfig {
+            curvature: 0.1,
+            ..CrtConfig::default()
+        });
+        let mut framebuffer = Framebuffer::new(40, 30);
+        framebuffer.clear(Color::WHITE);
+        crt.apply(&mut framebuffer);
+
+        // The centre is untouched; the corners fall off the tube
+        assert_eq!(framebuffer.pixel(20, 15), Some(Color::WHITE));
+        assert_eq!(framebuffer.pixels()[0], [0.0; 4]);
+        assert_eq!(framebuffer.pixels()[40 * 30 - 1], [0.0; 4]);
+    }
+
+    #[test]
+    fn test_chromatic_aberration_splits_colours_towards_edges() {
+        let mut crt = Crt::new(CrtConfig {
+            chromatic_aberration: 3.0,
+            ..CrtConfig::default()
+        });
+        // A white column right of centre
+        let mut framebuffer = Framebuffer::new(40, 20);
+        framebuffer.clear(Color::BLACK);
+        for y in 0..20 {
+            framebuffer.blend(34, y, Color::WHITE.premultiplied(), BlendMode::Normal);
+        }
+        crt.apply(&mut framebuffer);
+
+        // Red is sampled further out, so it appears inwards of the column; blue outwards
+        let left = framebuffer.pixel(32, 10).unwrap();
+        let right = framebuffer.pixel(36, 10).unwrap();
+        assert!(left.r > 0 && left.b == 0);
+        assert!(right.b > 0 && right.r == 0);
+        assert_eq!(framebuffer.pixel(20, 10), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn test_inactive_config_is_a_no_op() {
+        let mut framebuffer = Framebuffer::new(8, 8);
+        framebuffer.clear(Color::WHITE);
+        let before = framebuffer.clone();
+        let mut crt = Crt::new(CrtConfig::default());
+        crt.apply(&mut framebuffer);
+        assert_eq!(framebuffer, before);
+
+        crt.set_config(CrtConfig::retro());
+        assert_eq!(crt.config(), &CrtConfig::retro());
+        let mut empty = Framebuffer::new(0, 0);
+        crt.apply(&mut empty);
+    }
+}
@@ -0,0 +1,106 @@
+//! Extended-range (HDR) colour for EDR-capable displays
+
+use super::color::{linear_to_srgb, srgb_to_linear};
+use super::Color;
+use serde::{Deserialize, Serialize};
+
+/// An RGBA colour in linear extended sRGB
+///
+/// 1.0 is SDR ("UI") white; channels above 1.0 are brighter than white and
+/// only visible on displays with extended dynamic range headroom.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExtendedColor {
+    /// Red in linear light (1.0 = SDR white, may exceed 1.0)
+    pub r: f32,
+    /// Green in linear light (1.0 = SDR white, may exceed 1.0)
+    pub g: f32,
+    /// Blue in linear light (1.0 = SDR white, may exceed 1.0)
+    pub b: f32,
+    /// Alpha component (0.0-1.0)
+    pub a: f32,
+}
+
+impl ExtendedColor {
+    /// Create an extended colour from linear channels (negative values are clamped to 0.0)
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self {
+            r: r.max(0.0),
+            g: g.max(0.0),
+            b: b.max(0.0),
+            a: a.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Convert an 8-bit colour (always within SDR range)
+    pub fn from_color(color: Color) -> Self {
+        Self {
+            r: srgb_to_linear(color.r),
+            g: srgb_to_linear(color.g),
+            b: srgb_to_linear(color.b),
+            a: color.a,
+        }
+    }
+
+    /// Scale the brightness (alpha is unchanged)
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self::new(self.r * factor, self.g * factor, self.b * factor, self.a)
+    }
+
+    /// The brightest channel (above 1.0 means the colour needs EDR headroom)
+    pub fn max_component(&self) -> f32 {
+        self.r.max(self.g).max(self.b)
+    }
+
+    /// Check whether the colour is brighter than SDR white in any channel
+    pub fn is_extended(&self) -> bool {
+        self.max_component() > 1.0
+    }
+
+    /// Clip to SDR and convert back to an 8-bit colour
+    pub fn to_color(&self) -> Color {
+        Color::rgba(
+            linear_to_srgb(self.r),
+            linear_to_srgb(self.g),
+            linear_to_srgb(self.b),
+            self.a,
+        )
+    }
+}
+
+impl From<Color> for ExtendedColor {
+    fn from(color: Color) -> Self {
+        ExtendedColor::from_color(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sdr_round_trip() {
+        for color in [Color::BLACK, Color::WHITE, Color::MATRIX_GREEN] {
+            let extended = ExtendedColor::from_color(color.with_alpha(0.4));
+            assert!(!extended.is_extended());
+            assert_eq!(extended.to_color(), color.with_alpha(0.4));
+        }
+    }
+
+    #[test]
+    fn test_scaled_past_white() {
+        let white = ExtendedColor::from(Color::WHITE);
+        assert_eq!(white.max_component(), 1.0);
+
+        let bright = white.scaled(2.5);
+        assert!(bright.is_extended());
+        assert_eq!((bright.r, bright.a), (2.5, 1.0));
+        // SDR output clips at white
+        assert_eq!(bright.to_color(), Color::WHITE);
+    }
+
+    #[test]
+    fn test_new_clamps() {
+        let color = ExtendedColor::new(-1.0, 0.5, 3.0, 2.0);
+        assert_eq!((color.r, color.g, color.b, color.a), (0.0, 0.5, 3.0, 1.0));
+    }
+}
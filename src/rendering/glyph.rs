@@ -0,0 +1,372 @@
+//! Glyph representation: a single character or a short grapheme cluster
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Maximum UTF-8 length of a glyph in bytes
+/// Large enough for emoji ZWJ families, flags and Indic conjuncts
+pub const MAX_GLYPH_BYTES: usize = 32;
+
+/// One drawable glyph: a character or grapheme cluster stored inline as UTF-8
+///
+/// Glyphs are `Copy` and never allocate, so single-character sets stay as
+/// cheap as plain `char`s while clusters such as "क्ष", "கொ", "🇯🇵" or
+/// "👩‍💻" can flow through the engine unchanged.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Glyph {
+    /// Number of bytes used in `bytes`
+    len: u8,
+    /// UTF-8 data, zero padded
+    bytes: [u8; MAX_GLYPH_BYTES],
+}
+
+impl Glyph {
+    /// Create a glyph from a single character
+    pub const fn from_char(ch: char) -> Self {
+        let mut bytes = [0u8; MAX_GLYPH_BYTES];
+        let code = ch as u32;
+        let len = if code < 0x80 {
+            bytes[0] = code as u8;
+            1
+        } else if code < 0x800 {
+            bytes[0] = 0xC0 | (code >> 6) as u8;
+            bytes[1] = 0x80 | (code & 0x3F) as u8;
+            2
+        } else if code < 0x10000 {
+            bytes[0] = 0xE0 | (code >> 12) as u8;
+            bytes[1] = 0x80 | ((code >> 6) & 0x3F) as u8;
+            bytes[2] = 0x80 | (code & 0x3F) as u8;
+            3
+        } else {
+            bytes[0] = 0xF0 | (code >> 18) as u8;
+            bytes[1] = 0x80 | ((code >> 12) & 0x3F) as u8;
+            bytes[2] = 0x80 | ((code >> 6) & 0x3F) as u8;
+            bytes[3] = 0x80 | (code & 0x3F) as u8;
+            4
+        };
+        Self { len, bytes }
+    }
+
+    /// Create a glyph from a string (None if empty or longer than `MAX_GLYPH_BYTES`)
+    pub fn new(text: &str) -> Option<Self> {
+        if text.is_empty() || text.len() > MAX_GLYPH_BYTES {
+            return None;
+        }
+        let mut bytes = [0u8; MAX_GLYPH_BYTES];
+        bytes[..text.len()].copy_from_slice(text.as_bytes());
+        Some(Self {
+            len: text.len() as u8,
+            bytes,
+        })
+    }
+
+    /// The glyph as a string slice
+    pub fn as_str(&self) -> &str {
+        // Only ever filled from valid UTF-8 (`&str` or `char`)
+        std::str::from_utf8(self.as_bytes()).unwrap_or_default()
+    }
+
+    /// The glyph's UTF-8 bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    /// The full inline buffer (zero padded), as exported over FFI
+    pub fn raw_bytes(&self) -> [u8; MAX_GLYPH_BYTES] {
+        self.bytes
+    }
+
+    /// Length in bytes
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Glyphs are never empty; provided for API symmetry with `len`
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The first character of the glyph
+    pub fn first_char(&self) -> char {
+        self.chars().next().unwrap_or(' ')
+    }
+
+    /// Iterate over the characters of the glyph
+    pub fn chars(&self) -> std::str::Chars<'_> {
+        self.as_str().chars()
+    }
+
+    /// Check whether the glyph is a single character
+    pub fn is_single_char(&self) -> bool {
+        self.len() == self.first_char().len_utf8()
+    }
+
+    /// Number of terminal cells the glyph occupies (1 or 2)
+    /// Clusters take the width of their base character; East Asian wide
+    /// characters and emoji are double width.
+    pub fn display_width(&self) -> usize {
+        if is_wide(self.first_char()) {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Create a glyph from as many whole characters of a string as fit in
+    /// `MAX_GLYPH_BYTES` (None if empty)
+    pub fn truncated(text: &str) -> Option<Self> {
+        let mut end = text.len().min(MAX_GLYPH_BYTES);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        Self::new(&text[..end])
+    }
+
+    /// Split text into glyphs using an approximate grapheme segmentation
+    ///
+    /// Combining marks, Indic vowel signs, virama conjuncts, zero-width
+    /// joiner sequences, variation selectors, emoji modifiers and regional
+    /// indicator pairs stay attached to their base character. This covers the
+    /// scripts shipped with the library without a full Unicode segmentation table.
+    ///
+    /// Clusters longer than `MAX_GLYPH_BYTES` (long emoji ZWJ sequences, stacks of
+    /// combining marks) are cut after the last character that fits, so every
+    /// cluster still yields a glyph, led by its base character.
+    pub fn segment(text: &str) -> Vec<Glyph> {
+        let mut glyphs = Vec::new();
+        let mut cluster = String::new();
+        let mut join_next = false;
+        let mut chars = text.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            let attaches = !cluster.is_empty()
+                && (join_next
+                    || is_extending(ch)
+                    || (is_regional_indicator(ch) && is_single_regional_indicator(&cluster)));
+
+            if !attaches && !cluster.is_empty() {
+                glyphs.extend(Glyph::truncated(&cluster));
+                cluster.clear();
+            }
+
+            cluster.push(ch);
+            join_next = ch == '\u{200D}' || is_virama(ch);
+            // A trailing virama followed by a non-letter does not join
+            if join_next && chars.peek().is_none_or(|next| next.is_whitespace()) {
+                join_next = false;
+            }
+        }
+        if !cluster.is_empty() {
+            glyphs.extend(Glyph::truncated(&cluster));
+        }
+        glyphs
+    }
+}
+
+/// Indic script blocks that share the same layout of combining signs
+const INDIC_BLOCKS: [u32; 9] = [
+    0x0900, 0x0980, 0x0A00, 0x0A80, 0x0B00, 0x0B80, 0x0C00, 0x0C80, 0x0D00,
+];
+
+/// Characters that attach to the previous character instead of starting a glyph
+fn is_extending(ch: char) -> bool {
+    let code = ch as u32;
+
+    let indic = INDIC_BLOCKS.iter().any(|&base| {
+        let offset = code.wrapping_sub(base);
+        offset < 0x80
+            && (offset <= 0x03
+                || (0x3A..=0x4F).contains(&offset) && offset != 0x3D
+                || (0x51..=0x57).contains(&offset)
+                || (0x62..=0x63).contains(&offset))
+    });
+
+    indic
+        || matches!(code,
+            0x0300..=0x036F     // Combining diacritics
+            | 0x0591..=0x05BD | 0x05BF | 0x05C1..=0x05C2 | 0x05C4..=0x05C5 | 0x05C7 // Hebrew points
+            | 0x064B..=0x065F | 0x0670 | 0x06D6..=0x06DC | 0x06DF..=0x06E4 | 0x06E7..=0x06E8 | 0x06EA..=0x06ED // Arabic marks
+            | 0x0D81..=0x0D83 | 0x0DCA..=0x0DDF | 0x0DF2..=0x0DF3 // Sinhala signs
+            | 0x0E31 | 0x0E34..=0x0E3A | 0x0E47..=0x0E4E // Thai marks
+            | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x20D0..=0x20FF // More combining marks
+            | 0x200C..=0x200D   // ZWNJ / ZWJ
+            | 0xFE00..=0xFE0F   // Variation selectors
+            | 0xFE20..=0xFE2F   // Combining half marks
+            | 0x1F3FB..=0x1F3FF // Emoji skin tone modifiers
+            | 0xE0020..=0xE007F // Tag characters (subdivision flags)
+            | 0xE0100..=0xE01EF // Variation selectors supplement
+        )
+}
+
+/// Virama signs that join the following consonant into a conjunct
+fn is_virama(ch: char) -> bool {
+    let code = ch as u32;
+    INDIC_BLOCKS.iter().any(|&base| code == base + 0x4D) || code == 0x0DCA
+}
+
+fn is_regional_indicator(ch: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&ch)
+}
+
+/// Check whether a cluster is exactly one regional indicator (half a flag)
+fn is_single_regional_indicator(cluster: &str) -> bool {
+    let mut chars = cluster.chars();
+    matches!((chars.next(), chars.next()), (Some(ch), None) if is_regional_indicator(ch))
+}
+
+impl From<char> for Glyph {
+    fn from(ch: char) -> Self {
+        Glyph::from_char(ch)
+    }
+}
+
+impl PartialEq<char> for Glyph {
+    fn eq(&self, other: &char) -> bool {
+        self.is_single_char() && self.first_char() == *other
+    }
+}
+
+impl Default for Glyph {
+    fn default() -> Self {
+        Glyph::from_char(' ')
+    }
+}
+
+impl fmt::Display for Glyph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Glyph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Glyph({:?})", self.as_str())
+    }
+}
+
+impl Serialize for Glyph {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Glyph {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Glyph::new(&text).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "glyph must be 1-{} bytes of UTF-8",
+                MAX_GLYPH_BYTES
+            ))
+        })
+    }
+}
+
+/// Check whether a character is East Asian wide/fullwidth or an emoji presentation character
+fn is_wide(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x1100..=0x115F
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F1E6..=0x1F1FF
+            | 0x1F300..=0x1F64F
+            | 0x1F900..=0x1F9FF
+            | 0x20000..=0x3FFFD
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_char_matches_std_encoding() {
+        for ch in ['A', 'é', 'ア', 'ｱ', '𑇡', '😀'] {
+            let glyph = Glyph::from_char(ch);
+            assert_eq!(glyph.as_str(), ch.to_string());
+            assert!(glyph.is_single_char());
+            assert_eq!(glyph, ch);
+            assert_eq!(glyph.first_char(), ch);
+        }
+    }
+
+    #[test]
+    fn test_cluster_glyph() {
+        let glyph = Glyph::new("क्ष").unwrap();
+        assert_eq!(glyph.as_str(), "क्ष");
+        assert_eq!(glyph.chars().count(), 3);
+        assert!(!glyph.is_single_char());
+        assert_ne!(glyph, 'क');
+    }
+
+    #[test]
+    fn test_length_limits() {
+        assert!(Glyph::new("").is_none());
+        assert!(Glyph::new(&"a".repeat(MAX_GLYPH_BYTES)).is_some());
+        assert!(Glyph::new(&"a".repeat(MAX_GLYPH_BYTES + 1)).is_none());
+        // Family emoji: four people joined by ZWJ
+        assert!(Glyph::new("👨‍👩‍👧‍👦").is_some());
+
+        // Truncation keeps whole characters
+        let long = "é".repeat(MAX_GLYPH_BYTES);
+        assert_eq!(Glyph::truncated(&long).unwrap().len(), MAX_GLYPH_BYTES);
+        let odd = format!("a{}", long);
+        assert_eq!(Glyph::truncated(&odd).unwrap().len(), MAX_GLYPH_BYTES - 1);
+        assert!(Glyph::truncated("").is_none());
+    }
+
+    #[test]
+    fn test_segment_clusters() {
+        let as_strings = |text: &str| -> Vec<String> {
+            Glyph::segment(text)
+                .iter()
+                .map(|g| g.as_str().to_string())
+                .collect()
+        };
+
+        assert_eq!(as_strings("NEO"), vec!["N", "E", "O"]);
+        assert_eq!(as_strings("क्षत्र"), vec!["क्ष", "त्र"]);
+        assert_eq!(as_strings("கொடி"), vec!["கொ", "டி"]);
+        assert_eq!(as_strings("🇯🇵🇰🇷"), vec!["🇯🇵", "🇰🇷"]);
+        assert_eq!(as_strings("👩‍💻!"), vec!["👩‍💻", "!"]);
+        assert_eq!(as_strings("e\u{301}"), vec!["e\u{301}"]);
+        assert_eq!(as_strings("ไม้"), vec!["ไ", "ม้"]);
+
+        // An overlong cluster is cut short instead of dropped
+        let zalgo = format!("Z{}A", "\u{301}".repeat(20));
+        let glyphs = Glyph::segment(&zalgo);
+        assert_eq!(glyphs.len(), 2);
+        assert_eq!(glyphs[0].first_char(), 'Z');
+        assert_eq!(glyphs[0].len(), 1 + 2 * 15);
+        assert_eq!(glyphs[1], 'A');
+    }
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(Glyph::from_char('A').display_width(), 1);
+        assert_eq!(Glyph::from_char('ア').display_width(), 2);
+        assert_eq!(Glyph::from_char('한').display_width(), 2);
+        assert_eq!(Glyph::new("क्ष").unwrap().display_width(), 1);
+        assert_eq!(Glyph::new("🇯🇵").unwrap().display_width(), 2);
+        assert_eq!(Glyph::from_char('ｱ').display_width(), 1); // Halfwidth katakana
+    }
+
+    #[test]
+    fn test_serde_as_string() {
+        let glyph = Glyph::new("🇯🇵").unwrap();
+        let json = serde_json::to_string(&glyph).unwrap();
+        assert_eq!(json, "\"🇯🇵\"");
+        let back: Glyph = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, glyph);
+        assert!(serde_json::from_str::<Glyph>("\"\"").is_err());
+    }
+}
@@ -1,7 +1,37 @@
 //! Rendering module for drawing the Matrix rain effect
 
+mod backdrop;
+mod batch;
+#[cfg(feature = "software")]
+mod bloom;
 mod color;
+#[cfg(feature = "software")]
+mod crt;
+mod extended;
+mod glyph;
 mod renderer;
+#[cfg(feature = "software")]
+mod software;
+mod svg;
+#[cfg(feature = "terminal")]
+mod terminal;
+mod transform;
 
-pub use color::Color;
-pub use renderer::{RenderChar, Renderer};
+pub use backdrop::Backdrop;
+pub use batch::{batch_render_chars, quantize_color, FrameStats, RenderBatch, BATCH_COLOR_STEPS};
+#[cfg(feature = "software")]
+pub use bloom::Bloom;
+pub use color::{linear_to_srgb, srgb_to_linear, BlendMode, Color};
+#[cfg(feature = "software")]
+pub use crt::Crt;
+pub use extended::ExtendedColor;
+pub use glyph::{Glyph, MAX_GLYPH_BYTES};
+pub use renderer::{RenderChar, Renderer, CELL_HEIGHT_RATIO, CELL_WIDTH_RATIO};
+#[cfg(all(test, feature = "software"))]
+pub(crate) use software::tests::TestRasterizer;
+#[cfg(feature = "software")]
+pub use software::{FontRasterizer, Framebuffer, GlyphBitmap, GlyphRasterizer, SoftwareRenderer};
+pub use svg::SvgRenderer;
+#[cfg(feature = "terminal")]
+pub use terminal::{Cell, ColorMode, TerminalRenderer};
+pub use transform::GlyphTransform;
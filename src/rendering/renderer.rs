@@ -1,12 +1,20 @@
 //! Abstract renderer interface
 
-use super::Color;
+use super::{Backdrop, Color, ExtendedColor, Glyph, GlyphTransform, RenderBatch};
+
+/// Cell width as a fraction of the font size (matches the engine's monospace grid)
+pub const CELL_WIDTH_RATIO: f32 = 0.6;
+/// Cell height as a fraction of the font size (matches the engine's line spacing)
+pub const CELL_HEIGHT_RATIO: f32 = 1.2;
 
 /// A character to be rendered at a specific position
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RenderChar {
-    /// The character to render
-    pub character: char,
+    /// The glyph to render (a character or grapheme cluster)
+    pub character: Glyph,
+    /// Index of the glyph in the engine's glyph pool, and so in glyph atlases built from it
+    /// None for glyphs outside the pool, such as the characters of sequence text
+    pub glyph_index: Option<u32>,
     /// X position in pixels
     pub x: f32,
     /// Y position in pixels
@@ -15,6 +23,23 @@ pub struct RenderChar {
     pub color: Color,
     /// Font size
     pub font_size: f32,
+    /// Mirror/rotation to apply around the glyph centre
+    pub transform: GlyphTransform,
+    /// Whether this is a leader (head) glyph
+    pub leader: bool,
+    /// Glow/halo intensity renderers may draw around the glyph (0.0 = none)
+    pub glow: f32,
+    /// Brightness relative to SDR white for extended-range output (1.0 = `color` as is)
+    /// `color` itself always stays within SDR range.
+    pub intensity: f32,
+}
+
+impl RenderChar {
+    /// The glyph colour in extended range, with `intensity` applied
+    /// Hosts with EDR headroom draw the glyph and its glow in this colour.
+    pub fn extended_color(&self) -> ExtendedColor {
+        ExtendedColor::from_color(self.color).scaled(self.intensity)
+    }
 }
 
 /// Trait for rendering the matrix rain effect
@@ -23,6 +48,12 @@ pub trait Renderer {
     /// Clear the screen with the specified background color
     fn clear(&mut self, color: Color);
 
+    /// Paint the configured backdrop
+    /// Renderers that can draw gradients override this; the default clears to one colour
+    fn clear_backdrop(&mut self, backdrop: &Backdrop) {
+        self.clear(backdrop.base_color());
+    }
+
     /// Draw a single character at the specified position
     fn draw_char(&mut self, render_char: &RenderChar);
 
@@ -33,6 +64,13 @@ pub trait Renderer {
         }
     }
 
+    /// Draw glyphs that share a font size, glow and quantized colour
+    /// Renderers that can reuse state across glyphs override this; the default draws
+    /// them one by one
+    fn draw_batch(&mut self, batch: &RenderBatch) {
+        self.draw_chars(&batch.chars);
+    }
+
     /// Present/flush the rendered frame to screen
     fn present(&mut self);
 
@@ -52,6 +90,7 @@ mod tests {
         width: u32,
         height: u32,
         chars_drawn: Vec<RenderChar>,
+        cleared_with: Option<Color>,
     }
 
     impl MockRenderer {
@@ -60,12 +99,14 @@ mod tests {
                 width,
                 height,
                 chars_drawn: Vec::new(),
+                cleared_with: None,
             }
         }
     }
 
     impl Renderer for MockRenderer {
-        fn clear(&mut self, _color: Color) {
+        fn clear(&mut self, color: Color) {
+            self.cleared_with = Some(color);
             self.chars_drawn.clear();
         }
 
@@ -93,11 +134,16 @@ mod tests {
         assert_eq!(renderer.height(), 1080);
 
         let render_char = RenderChar {
-            character: 'A',
+            character: 'A'.into(),
+            glyph_index: None,
             x: 100.0,
             y: 200.0,
             color: Color::MATRIX_GREEN,
             font_size: 16.0,
+            transform: GlyphTransform::NONE,
+            leader: false,
+            glow: 0.0,
+            intensity: 1.0,
         };
 
         renderer.draw_char(&render_char);
@@ -111,18 +157,28 @@ mod tests {
 
         let chars = vec![
             RenderChar {
-                character: 'A',
+                character: 'A'.into(),
+                glyph_index: None,
                 x: 0.0,
                 y: 0.0,
                 color: Color::MATRIX_GREEN,
                 font_size: 16.0,
+                transform: GlyphTransform::NONE,
+                leader: false,
+                glow: 0.0,
+                intensity: 1.0,
             },
             RenderChar {
-                character: 'B',
+                character: 'B'.into(),
+                glyph_index: None,
                 x: 20.0,
                 y: 0.0,
                 color: Color::MATRIX_GREEN,
                 font_size: 16.0,
+                transform: GlyphTransform::NONE,
+                leader: false,
+                glow: 0.0,
+                intensity: 1.0,
             },
         ];
 
@@ -130,16 +186,43 @@ mod tests {
         assert_eq!(renderer.chars_drawn.len(), 2);
     }
 
+    #[test]
+    fn test_draw_batch_defaults_to_each_char() {
+        let mut renderer = MockRenderer::new(1920, 1080);
+        let chars = ['A', 'B', 'A'].map(|ch| RenderChar {
+            character: ch.into(),
+            glyph_index: None,
+            x: 0.0,
+            y: 0.0,
+            color: Color::MATRIX_GREEN,
+            font_size: 16.0,
+            transform: GlyphTransform::NONE,
+            leader: false,
+            glow: 0.0,
+            intensity: 1.0,
+        });
+
+        let batches = crate::rendering::batch_render_chars(chars);
+        assert_eq!(batches.len(), 1);
+        renderer.draw_batch(&batches[0]);
+        assert_eq!(renderer.chars_drawn, batches[0].chars);
+    }
+
     #[test]
     fn test_clear() {
         let mut renderer = MockRenderer::new(1920, 1080);
 
         let render_char = RenderChar {
-            character: 'A',
+            character: 'A'.into(),
+            glyph_index: None,
             x: 100.0,
             y: 200.0,
             color: Color::MATRIX_GREEN,
             font_size: 16.0,
+            transform: GlyphTransform::NONE,
+            leader: false,
+            glow: 0.0,
+            intensity: 1.0,
         };
 
         renderer.draw_char(&render_char);
@@ -148,4 +231,14 @@ mod tests {
         renderer.clear(Color::BLACK);
         assert_eq!(renderer.chars_drawn.len(), 0);
     }
+
+    #[test]
+    fn test_clear_backdrop_defaults_to_clear() {
+        let mut renderer = MockRenderer::new(800, 600);
+        renderer.clear_backdrop(&Backdrop::Radial {
+            center: Color::rgb(0, 100, 0),
+            edge: Color::BLACK,
+        });
+        assert_eq!(renderer.cleared_with, Some(Color::rgb(0, 50, 0)));
+    }
 }
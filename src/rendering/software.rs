@@ -0,0 +1,810 @@
+//! Headless CPU renderer: rasterizes glyphs from a TTF/OTF font into an RGBA framebuffer
+//!
+//! Works anywhere (no windowing system), so frames can be exported, previewed
+//! or checked pixel by pixel in tests.
+
+use super::{
+    Backdrop, BlendMode, Bloom, Color, Crt, Glyph, GlyphTransform, RenderChar, Renderer,
+    CELL_HEIGHT_RATIO, CELL_WIDTH_RATIO,
+};
+use crate::config::{BloomConfig, CrtConfig};
+use crate::fonts::FontError;
+use ab_glyph::{Font, FontArc, FontVec, PxScale, ScaleFont};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use std::thread;
+
+/// An RGBA image stored as premultiplied linear-light colour
+///
+/// Blending happens in linear light; `to_rgba8` converts back to 8-bit sRGB.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Framebuffer {
+    width: u32,
+    height: u32,
+    /// Premultiplied linear [r*a, g*a, b*a, a], row-major from the top left
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Framebuffer {
+    /// Create a fully transparent framebuffer
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0.0; 4]; width as usize * height as usize],
+        }
+    }
+
+    /// Width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Fill every pixel with one colour
+    pub fn clear(&mut self, color: Color) {
+        self.pixels.fill(color.premultiplied());
+    }
+
+    /// Paint a backdrop (gradients are evaluated per pixel)
+    pub fn fill_backdrop(&mut self, backdrop: &Backdrop) {
+        if let Backdrop::Solid(color) = backdrop {
+            self.clear(*color);
+            return;
+        }
+        let (width, height) = (self.width.max(1) as f32, self.height.max(1) as f32);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let color = backdrop.color_at((x as f32 + 0.5) / width, (y as f32 + 0.5) / height);
+                self.pixels[(y * self.width + x) as usize] = color.premultiplied();
+            }
+        }
+    }
+
+    /// Premultiplied linear pixels, row-major from the top left
+    pub fn pixels(&self) -> &[[f32; 4]] {
+        &self.pixels
+    }
+
+    /// Mutable access to the premultiplied linear pixels, for post-processing
+    pub fn pixels_mut(&mut self) -> &mut [[f32; 4]] {
+        &mut self.pixels
+    }
+
+    /// Get a pixel as an 8-bit colour (None if out of bounds)
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(Color::from_premultiplied(
+            self.pixels[(y * self.width + x) as usize],
+        ))
+    }
+
+    /// Blend a premultiplied linear colour onto a pixel (out-of-bounds pixels are ignored)
+    pub fn blend(&mut self, x: i32, y: i32, src: [f32; 4], mode: BlendMode) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let pixel = &mut self.pixels[(y as u32 * self.width + x as u32) as usize];
+        *pixel = mode.blend(src, *pixel);
+    }
+
+    /// The image as 8-bit sRGB RGBA with straight alpha, row-major from the top left
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.pixels.len() * 4);
+        for &pixel in &self.pixels {
+            let color = Color::from_premultiplied(pixel);
+            bytes.extend([color.r, color.g, color.b, (color.a * 255.0).round() as u8]);
+        }
+        bytes
+    }
+}
+
+/// Fewest rows worth handing to a thread of their own
+const MIN_BAND_ROWS: usize = 32;
+
+/// Threads to spread a pass over, looked up once
+pub(super) fn thread_count() -> usize {
+    static THREADS: OnceLock<usize> = OnceLock::new();
+    *THREADS.get_or_init(|| thread::available_parallelism().map_or(1, |count| count.get()))
+}
+
+/// Run `work` over bands of whole rows of a `width`-wide pixel buffer, in
+/// parallel when the buffer is big enough; post-processing passes use this
+///
+/// `work` receives the index of the band's first row and the band itself.
+pub(super) fn for_each_band<F>(pixels: &mut [[f32; 4]], width: usize, work: F)
+where
+    F: Fn(usize, &mut [[f32; 4]]) + Sync,
+{
+    let rows = pixels.len() / width;
+    let threads = thread_count().min(rows / MIN_BAND_ROWS).max(1);
+    if threads == 1 {
+        work(0, pixels);
+        return;
+    }
+
+    let band_rows = rows.div_ceil(threads);
+    let work = &work;
+    thread::scope(|scope| {
+        for (i, band) in pixels.chunks_mut(band_rows * width).enumerate() {
+            scope.spawn(move || work(i * band_rows, band));
+        }
+    });
+}
+
+/// Coverage mask of a rasterized glyph, positioned relative to its cell's top left
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphBitmap {
+    /// Width in pixels
+    pub width: usize,
+    /// Height in pixels
+    pub height: usize,
+    /// Offset of the bitmap's left edge from the cell's left edge
+    pub left: i32,
+    /// Offset of the bitmap's top edge from the cell's top edge
+    pub top: i32,
+    /// Coverage (0.0-1.0), row-major
+    pub coverage: Vec<f32>,
+}
+
+impl GlyphBitmap {
+    /// Coverage at a pixel of the bitmap
+    pub fn coverage_at(&self, x: usize, y: usize) -> f32 {
+        self.coverage[y * self.width + x]
+    }
+
+    /// Apply a glyph transform around the centre of a cell of the given size
+    /// Mirroring is applied first, then rotation.
+    pub fn transformed(&self, transform: GlyphTransform, cell: (f32, f32)) -> GlyphBitmap {
+        if transform.is_none() {
+            return self.clone();
+        }
+        let center = (cell.0 / 2.0, cell.1 / 2.0);
+        let mut bitmap = self.clone();
+        if transform.contains(GlyphTransform::MIRROR_X) {
+            bitmap = bitmap.remapped(bitmap.width, bitmap.height, |x, y, b| (b.width - 1 - x, y));
+            bitmap.left = (2.0 * center.0).round() as i32 - self.left - self.width as i32;
+        }
+        if transform.contains(GlyphTransform::MIRROR_Y) {
+            let left = bitmap.left;
+            bitmap = bitmap.remapped(bitmap.width, bitmap.height, |x, y, b| (x, b.height - 1 - y));
+            bitmap.left = left;
+            bitmap.top = (2.0 * center.1).round() as i32 - self.top - self.height as i32;
+        }
+        for _ in 0..(transform.rotation_degrees() / 90.0) as u32 {
+            bitmap = bitmap.rotated_clockwise(center);
+        }
+        bitmap
+    }
+
+    /// Build a bitmap of a new size whose pixels come from `source(x, y)` of this one
+    fn remapped(
+        &self,
+        width: usize,
+        height: usize,
+        source: impl Fn(usize, usize, &GlyphBitmap) -> (usize, usize),
+    ) -> GlyphBitmap {
+        let mut coverage = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = source(x, y, self);
+                coverage.push(self.coverage_at(sx, sy));
+            }
+        }
+        GlyphBitmap {
+            width,
+            height,
+            left: self.left,
+            top: self.top,
+            coverage,
+        }
+    }
+
+    /// Rotate 90 degrees clockwise around a point relative to the cell's top left
+    fn rotated_clockwise(&self, (cx, cy): (f32, f32)) -> GlyphBitmap {
+        let mut bitmap = self.remapped(self.height, self.width, |x, y, b| (y, b.height - 1 - x));
+        bitmap.left = (cx + cy).round() as i32 - self.top - self.height as i32;
+        bitmap.top = (cy - cx).round() as i32 + self.left;
+        bitmap
+    }
+}
+
+/// Turns glyphs into coverage bitmaps
+pub trait GlyphRasterizer {
+    /// Rasterize a glyph for a cell of `font_size`
+    /// Returns None for glyphs with nothing to draw (spaces, glyphs missing from the font).
+    /// Bitmaps are shared, so a cache can hand them out without copying.
+    fn rasterize(&mut self, glyph: Glyph, font_size: f32) -> Option<Arc<GlyphBitmap>>;
+
+    /// Horizontal advance of a glyph at `font_size`
+    /// Defaults to the cell width, the grid glyphs are laid out on.
+    fn advance(&mut self, _glyph: Glyph, font_size: f32) -> f32 {
+        font_size * CELL_WIDTH_RATIO
+    }
+
+    /// Distance from the top of a cell of `font_size` to the baseline glyphs sit on
+    /// Defaults to four fifths of the cell height.
+    fn baseline(&mut self, font_size: f32) -> f32 {
+        font_size * CELL_HEIGHT_RATIO * 0.8
+    }
+}
+
+/// Rasterizer backed by a TrueType/OpenType font, caching bitmaps per glyph and size
+/// Clones share the loaded font.
+#[derive(Clone)]
+pub struct FontRasterizer {
+    font: FontArc,
+    cache: HashMap<(Glyph, u32), Option<Arc<GlyphBitmap>>>,
+}
+
+impl FontRasterizer {
+    /// Load a font file (`.ttf`, `.otf`, or the first face of a `.ttc`)
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        Self::from_file_index(path, 0)
+    }
+
+    /// Load a specific face from a font file or collection
+    pub fn from_file_index(path: impl AsRef<Path>, face_index: u32) -> Result<Self, FontError> {
+        Self::from_bytes(std::fs::read(path)?, face_index)
+    }
+
+    /// Load a font from memory, choosing a face of a collection by index
+    pub fn from_bytes(data: Vec<u8>, face_index: u32) -> Result<Self, FontError> {
+        let font = FontVec::try_from_vec_and_index(data, face_index)
+            .map_err(|_| FontError::Malformed("font has no usable outlines"))?;
+        Ok(Self {
+            font: FontArc::new(font),
+            cache: HashMap::new(),
+        })
+    }
+
+    /// Check whether the font has an outline for a character
+    pub fn has_glyph(&self, ch: char) -> bool {
+        self.font.glyph_id(ch).0 != 0
+    }
+
+    /// Baseline that centres the font's line height in a cell
+    fn baseline_offset(&self, font_size: f32) -> f32 {
+        let scaled = self.font.as_scaled(PxScale::from(font_size));
+        let cell_height = font_size * CELL_HEIGHT_RATIO;
+        (cell_height - (scaled.ascent() - scaled.descent())) / 2.0 + scaled.ascent()
+    }
+
+    fn render(&self, glyph: Glyph, font_size: f32) -> Option<GlyphBitmap> {
+        let scaled = self.font.as_scaled(PxScale::from(font_size));
+        let cell = (font_size * CELL_WIDTH_RATIO, font_size * CELL_HEIGHT_RATIO);
+
+        // Lay the cluster's characters out side by side (no shaping), skipping missing ones
+        let mut caret = 0.0;
+        let mut outlines = Vec::new();
+        for ch in glyph.chars() {
+            let id = self.font.glyph_id(ch);
+            if id.0 == 0 {
+                continue;
+            }
+            let positioned =
+                id.with_scale_and_position(scaled.scale(), ab_glyph::point(caret, 0.0));
+            caret += scaled.h_advance(id);
+            outlines.extend(self.font.outline_glyph(positioned));
+        }
+        if outlines.is_empty() {
+            return None;
+        }
+
+        // Centre horizontally in the cell and vertically by the font's line height
+        let origin_x = (cell.0 - caret) / 2.0;
+        let baseline = self.baseline_offset(font_size);
+
+        let bounds = outlines
+            .iter()
+            .map(|outline| outline.px_bounds())
+            .reduce(|a, b| ab_glyph::Rect {
+                min: ab_glyph::point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+                max: ab_glyph::point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+            })?;
+        let width = (bounds.max.x - bounds.min.x).ceil() as usize;
+        let height = (bounds.max.y - bounds.min.y).ceil() as usize;
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let mut coverage = vec![0.0f32; width * height];
+        for outline in &outlines {
+            let offset = outline.px_bounds().min;
+            let dx = (offset.x - bounds.min.x) as usize;
+            let dy = (offset.y - bounds.min.y) as usize;
+            outline.draw(|x, y, c| {
+                let (x, y) = (x as usize + dx, y as usize + dy);
+                if x < width && y < height {
+                    let pixel = &mut coverage[y * width + x];
+                    *pixel = (*pixel + c).min(1.0);
+                }
+            });
+        }
+
+        Some(GlyphBitmap {
+            width,
+            height,
+            left: (origin_x + bounds.min.x).round() as i32,
+            top: (baseline + bounds.min.y).round() as i32,
+            coverage,
+        })
+    }
+}
+
+impl GlyphRasterizer for FontRasterizer {
+    fn rasterize(&mut self, glyph: Glyph, font_size: f32) -> Option<Arc<GlyphBitmap>> {
+        let key = (glyph, font_size.to_bits());
+        if let Some(bitmap) = self.cache.get(&key) {
+            return bitmap.clone();
+        }
+        let bitmap = self.render(glyph, font_size).map(Arc::new);
+        self.cache.insert(key, bitmap.clone());
+        bitmap
+    }
+
+    fn advance(&mut self, glyph: Glyph, font_size: f32) -> f32 {
+        let scaled = self.font.as_scaled(PxScale::from(font_size));
+        glyph
+            .chars()
+            .map(|ch| self.font.glyph_id(ch))
+            .filter(|id| id.0 != 0)
+            .map(|id| scaled.h_advance(id))
+            .sum()
+    }
+
+    fn baseline(&mut self, font_size: f32) -> f32 {
+        self.baseline_offset(font_size)
+    }
+}
+
+/// A `Renderer` that draws into a `Framebuffer` on the CPU
+///
+/// Glyphs are blended with the configured blend mode in linear light. Per-glyph
+/// glow is not drawn; bloom and the CRT effects, when enabled, are applied to the
+/// whole frame on `present`.
+pub struct SoftwareRenderer<R: GlyphRasterizer = FontRasterizer> {
+    framebuffer: Framebuffer,
+    rasterizer: R,
+    blend_mode: BlendMode,
+    bloom: Option<Bloom>,
+    crt: Option<Crt>,
+    frames_presented: u64,
+}
+
+impl<R: GlyphRasterizer> SoftwareRenderer<R> {
+    /// Create a renderer with a transparent framebuffer
+    pub fn new(width: u32, height: u32, rasterizer: R) -> Self {
+        Self {
+            framebuffer: Framebuffer::new(width, height),
+            rasterizer,
+            blend_mode: BlendMode::Normal,
+            bloom: None,
+            crt: None,
+            frames_presented: 0,
+        }
+    }
+
+    /// Add bloom to every presented frame
+    pub fn with_bloom(mut self, config: BloomConfig) -> Self {
+        self.set_bloom(config);
+        self
+    }
+
+    /// Change the bloom settings (an inactive config turns bloom off)
+    pub fn set_bloom(&mut self, config: BloomConfig) {
+        match &mut self.bloom {
+            Some(bloom) => bloom.set_config(config),
+            None if config.is_active() => self.bloom = Some(Bloom::new(config)),
+            None => {}
+        }
+    }
+
+    /// Add CRT effects to every presented frame
+    pub fn with_crt(mut self, config: CrtConfig) -> Self {
+        self.set_crt(config);
+        self
+    }
+
+    /// Change the CRT settings (an inactive config turns the effects off)
+    pub fn set_crt(&mut self, config: CrtConfig) {
+        match &mut self.crt {
+            Some(crt) => crt.set_config(config),
+            None if config.is_active() => self.crt = Some(Crt::new(config)),
+            None => {}
+        }
+    }
+
+    /// Set how glyphs blend with what is already drawn
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    /// The rendered image
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    /// Take the rendered image
+    pub fn into_framebuffer(self) -> Framebuffer {
+        self.framebuffer
+    }
+
+    /// Number of frames finished with `present`
+    pub fn frames_presented(&self) -> u64 {
+        self.frames_presented
+    }
+}
+
+impl<R: GlyphRasterizer> Renderer for SoftwareRenderer<R> {
+    fn clear(&mut self, color: Color) {
+        self.framebuffer.clear(color);
+    }
+
+    fn clear_backdrop(&mut self, backdrop: &Backdrop) {
+        self.framebuffer.fill_backdrop(backdrop);
+    }
+
+    fn draw_char(&mut self, render_char: &RenderChar) {
+        let Some(bitmap) = self
+            .rasterizer
+            .rasterize(render_char.character, render_char.font_size)
+        else {
+            return;
+        };
+        let cell = (
+            render_char.font_size * CELL_WIDTH_RATIO,
+            render_char.font_size * CELL_HEIGHT_RATIO,
+        );
+        // Most glyphs are drawn as rasterized, straight from the cache
+        let transformed;
+        let bitmap = if render_char.transform.is_none() {
+            &*bitmap
+        } else {
+            transformed = bitmap.transformed(render_char.transform, cell);
+            &transformed
+        };
+
+        let color = render_char.color.premultiplied();
+        let origin_x = render_char.x.round() as i32 + bitmap.left;
+        let origin_y = render_char.y.round() as i32 + bitmap.top;
+        for y in 0..bitmap.height {
+            for x in 0..bitmap.width {
+                let coverage = bitmap.coverage_at(x, y);
+                if coverage <= 0.0 {
+                    continue;
+                }
+                self.framebuffer.blend(
+                    origin_x + x as i32,
+                    origin_y + y as i32,
+                    color.map(|channel| channel * coverage),
+                    self.blend_mode,
+                );
+            }
+        }
+    }
+
+    fn present(&mut self) {
+        // Trails persist before the glow is added, or the glow would build up frame on frame
+        if let Some(crt) = &mut self.crt {
+            crt.persist(&mut self.framebuffer);
+        }
+        if let Some(bloom) = &mut self.bloom {
+            bloom.apply(&mut self.framebuffer);
+        }
+        if let Some(crt) = &mut self.crt {
+            crt.finish(&mut self.framebuffer);
+        }
+        self.frames_presented += 1;
+    }
+
+    fn width(&self) -> u32 {
+        self.framebuffer.width
+    }
+
+    fn height(&self) -> u32 {
+        self.framebuffer.height
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{MatrixRain, ScreenSaverConfig};
+
+    /// Font-free rasterizer for tests
+    ///
+    /// Draws every glyph as a solid block filling its cell, and nothing for
+    /// spaces. The builder methods shape the block for what a test needs to see.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub(crate) struct TestRasterizer {
+        margin: usize,
+        notched: bool,
+        soft: bool,
+        varied: bool,
+        empty: bool,
+    }
+
+    impl TestRasterizer {
+        /// Blocks filling each glyph's cell
+        pub(crate) fn blocks() -> Self {
+            Self::default()
+        }
+
+        /// Draws nothing at all, as if the font had no glyphs
+        #[cfg(feature = "png")]
+        pub(crate) fn empty() -> Self {
+            Self {
+                empty: true,
+                ..Self::default()
+            }
+        }
+
+        /// Leave `margin` empty pixels between the block and each edge of the cell
+        pub(crate) fn with_margin(mut self, margin: usize) -> Self {
+            self.margin = margin;
+            self
+        }
+
+        /// Leave the block's top-left pixel empty, so transforms can be observed
+        pub(crate) fn notched(mut self) -> Self {
+            self.notched = true;
+            self
+        }
+
+        /// Ramp coverage across each row, so frames contain partial coverage
+        #[cfg(feature = "animation")]
+        pub(crate) fn soft(mut self) -> Self {
+            self.soft = true;
+            self
+        }
+
+        /// Shrink each block by a glyph-dependent 0-4 pixels, so glyphs differ in size
+        pub(crate) fn varied(mut self) -> Self {
+            self.varied = true;
+            self
+        }
+    }
+
+    impl GlyphRasterizer for TestRasterizer {
+        fn rasterize(&mut self, glyph: Glyph, font_size: f32) -> Option<Arc<GlyphBitmap>> {
+            if self.empty || glyph == ' ' {
+                return None;
+            }
+            let shrink = 2 * self.margin
+                + if self.varied {
+                    glyph.first_char() as usize % 5
+                } else {
+                    0
+                };
+            let width = ((font_size * CELL_WIDTH_RATIO) as usize).saturating_sub(shrink);
+            let height = ((font_size * CELL_HEIGHT_RATIO) as usize).saturating_sub(shrink);
+            if width == 0 || height == 0 {
+                return None;
+            }
+            let mut coverage: Vec<f32> = (0..width * height)
+                .map(|i| if self.soft { (i % 4) as f32 / 3.0 } else { 1.0 })
+                .collect();
+            if self.notched {
+                coverage[0] = 0.0;
+            }
+            Some(Arc::new(GlyphBitmap {
+                width,
+                height,
+                left: self.margin as i32,
+                top: self.margin as i32,
+                coverage,
+            }))
+        }
+    }
+
+    /// Boxes with a 1px margin and a notch, to check positions and transforms
+    fn box_rasterizer() -> TestRasterizer {
+        TestRasterizer::blocks().with_margin(1).notched()
+    }
+
+    fn render_char(x: f32, y: f32, color: Color) -> RenderChar {
+        RenderChar {
+            character: 'A'.into(),
+            glyph_index: None,
+            x,
+            y,
+            color,
+            font_size: 10.0,
+            transform: GlyphTransform::NONE,
+            leader: false,
+            glow: 0.0,
+            intensity: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_framebuffer_clear_and_export() {
+        let mut framebuffer = Framebuffer::new(2, 2);
+        assert_eq!(framebuffer.pixel(0, 0), Some(Color::rgba(0, 0, 0, 0.0)));
+        framebuffer.clear(Color::MATRIX_GREEN);
+        assert_eq!(framebuffer.pixel(1, 1), Some(Color::MATRIX_GREEN));
+        assert_eq!(framebuffer.pixel(2, 0), None);
+        assert_eq!(&framebuffer.to_rgba8()[..4], &[0, 255, 70, 255]);
+
+        framebuffer.fill_backdrop(&Backdrop::Vertical {
+            top: Color::BLACK,
+            bottom: Color::WHITE,
+        });
+        let top = framebuffer.pixel(0, 0).unwrap();
+        let bottom = framebuffer.pixel(0, 1).unwrap();
+        assert!(top.r < bottom.r);
+    }
+
+    #[test]
+    fn test_bands_cover_every_row_once() {
+        let mut pixels = vec![[0.0f32; 4]; 3 * 257];
+        for_each_band(&mut pixels, 3, |first, band| {
+            for (y, row) in (first..).zip(band.chunks_exact_mut(3)) {
+                for pixel in row {
+                    pixel[0] += y as f32;
+                    pixel[1] += 1.0;
+                }
+            }
+        });
+        for (y, row) in pixels.chunks_exact(3).enumerate() {
+            assert!(row
+                .iter()
+                .all(|pixel| pixel[0] == y as f32 && pixel[1] == 1.0));
+        }
+    }
+
+    #[test]
+    fn test_draws_glyph_in_cell() {
+        let mut renderer = SoftwareRenderer::new(20, 20, box_rasterizer());
+        renderer.clear(Color::BLACK);
+        renderer.draw_char(&render_char(6.0, 0.0, Color::WHITE));
+        renderer.present();
+
+        let framebuffer = renderer.framebuffer();
+        // Box covers x 7..11, y 1..11 (cell is 6x12 at font size 10)
+        assert_eq!(framebuffer.pixel(8, 5), Some(Color::WHITE));
+        assert_eq!(framebuffer.pixel(7, 1), Some(Color::BLACK)); // Empty corner
+        assert_eq!(framebuffer.pixel(6, 5), Some(Color::BLACK));
+        assert_eq!(framebuffer.pixel(11, 5), Some(Color::BLACK));
+        assert_eq!(renderer.frames_presented(), 1);
+
+        // Spaces draw nothing
+        renderer.draw_char(&RenderChar {
+            character: ' '.into(),
+            glyph_index: None,
+            ..render_char(0.0, 0.0, Color::WHITE)
+        });
+        assert_eq!(renderer.framebuffer().pixel(2, 5), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn test_alpha_and_blend_modes() {
+        let mut renderer = SoftwareRenderer::new(8, 14, box_rasterizer());
+        renderer.clear(Color::BLACK);
+        renderer.draw_char(&render_char(0.0, 0.0, Color::WHITE.with_alpha(0.5)));
+        // Half the light in linear terms
+        assert_eq!(renderer.framebuffer().pixel(3, 5).unwrap().r, 188);
+
+        let mut renderer =
+            SoftwareRenderer::new(8, 14, box_rasterizer()).with_blend_mode(BlendMode::Additive);
+        renderer.clear(Color::BLACK);
+        renderer.draw_char(&render_char(0.0, 0.0, Color::rgb(255, 0, 0)));
+        renderer.draw_char(&render_char(0.0, 0.0, Color::rgb(0, 255, 0)));
+        assert_eq!(
+            renderer.framebuffer().pixel(3, 5),
+            Some(Color::rgb(255, 255, 0))
+        );
+    }
+
+    #[test]
+    fn test_transforms() {
+        let bitmap = TestRasterizer::blocks()
+            .with_margin(1)
+            .notched()
+            .rasterize('A'.into(), 10.0)
+            .unwrap();
+        let cell = (6.0, 12.0);
+        assert_eq!(bitmap.transformed(GlyphTransform::NONE, cell), *bitmap);
+
+        // Mirroring keeps the box in place and moves the empty corner
+        let mirrored = bitmap.transformed(GlyphTransform::MIRROR_X, cell);
+        assert_eq!((mirrored.left, mirrored.top), (1, 1));
+        assert_eq!(mirrored.coverage_at(mirrored.width - 1, 0), 0.0);
+        let flipped = bitmap.transformed(GlyphTransform::MIRROR_Y, cell);
+        assert_eq!(flipped.coverage_at(0, flipped.height - 1), 0.0);
+
+        // Rotation swaps the dimensions around the cell centre
+        let rotated = bitmap.transformed(GlyphTransform::ROTATE_90, cell);
+        assert_eq!(
+            (rotated.width, rotated.height),
+            (bitmap.height, bitmap.width)
+        );
+        assert_eq!((rotated.left, rotated.top), (-2, 4));
+        assert_eq!(rotated.coverage_at(rotated.width - 1, 0), 0.0);
+
+        let half_turn = bitmap.transformed(GlyphTransform::ROTATE_180, cell);
+        assert_eq!((half_turn.left, half_turn.top), (1, 1));
+        assert_eq!(
+            half_turn.coverage_at(half_turn.width - 1, half_turn.height - 1),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_renders_engine_frame() {
+        let config = ScreenSaverConfig {
+            screen_width: 320,
+            screen_height: 240,
+            ..Default::default()
+        };
+        let mut matrix = MatrixRain::new(config);
+        for _ in 0..80 {
+            matrix.update();
+        }
+
+        let mut renderer = SoftwareRenderer::new(320, 240, box_rasterizer());
+        matrix.render(&mut renderer);
+        let pixels = renderer.framebuffer().to_rgba8();
+        assert_eq!(pixels.len(), 320 * 240 * 4);
+        // Some green rain on an opaque black background
+        assert!(pixels.chunks_exact(4).all(|p| p[3] == 255));
+        assert!(pixels.chunks_exact(4).any(|p| p[1] > 100 && p[0] < p[1]));
+    }
+
+    #[test]
+    fn test_invalid_font() {
+        assert!(matches!(
+            FontRasterizer::from_bytes(vec![0; 16], 0),
+            Err(FontError::Malformed(_))
+        ));
+        assert!(matches!(
+            FontRasterizer::from_file("/nonexistent/font.ttf"),
+            Err(FontError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_system_font() {
+        // Only runs where one of these common fonts is installed
+        let candidates = [
+            "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf",
+            "/System/Library/Fonts/Supplemental/Courier New.ttf",
+            "/Library/Fonts/Arial Unicode.ttf",
+        ];
+        let Some(mut rasterizer) = candidates
+            .iter()
+            .find_map(|path| FontRasterizer::from_file(path).ok())
+        else {
+            return;
+        };
+
+        assert!(rasterizer.has_glyph('A'));
+        let bitmap = rasterizer.rasterize('A'.into(), 32.0).unwrap();
+        assert!(bitmap.coverage.iter().any(|&c| c > 0.9));
+        // Inside the 19x38 cell
+        assert!(bitmap.left >= 0 && bitmap.left + bitmap.width as i32 <= 20);
+        assert!(bitmap.top >= 0 && bitmap.top + bitmap.height as i32 <= 39);
+        assert!(rasterizer.rasterize(' '.into(), 32.0).is_none());
+        // The baseline sits below the glyph's top, inside the cell
+        let baseline = rasterizer.baseline(32.0);
+        assert!(baseline > bitmap.top as f32 && baseline < 38.4);
+        assert!(rasterizer.advance('A'.into(), 32.0) > 0.0);
+
+        // Cached per size, and shared rather than copied
+        let cached = rasterizer.rasterize('A'.into(), 32.0).unwrap();
+        assert!(Arc::ptr_eq(&cached, &bitmap));
+        assert_ne!(
+            rasterizer.rasterize('A'.into(), 16.0),
+            rasterizer.rasterize('A'.into(), 32.0)
+        );
+    }
+}
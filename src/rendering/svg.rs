@@ -0,0 +1,472 @@
+//! SVG renderer: writes each frame as a vector document of `<text>` elements
+//!
+//! Output scales to any size for print and slides, and is byte-for-byte
+//! deterministic, so a rendered frame doubles as a text-diffable test snapshot.
+
+use super::{
+    Backdrop, Color, RenderBatch, RenderChar, Renderer, CELL_HEIGHT_RATIO, CELL_WIDTH_RATIO,
+};
+use crate::fonts::{subset_font, FontError};
+use std::fmt::Write;
+
+/// Family name the embedded font subset is registered under
+const EMBEDDED_FAMILY: &str = "Matrix Rain Embedded";
+
+/// A `Renderer` that builds an SVG document per frame
+///
+/// Each glyph becomes a `<text>` element centred in its cell, with its fill,
+/// opacity and font size taken from the `RenderChar`. Viewers pick the font
+/// from `font-family` unless a subset is embedded. Glow is not drawn.
+#[derive(Debug, Clone)]
+pub struct SvgRenderer {
+    width: u32,
+    height: u32,
+    font_family: String,
+    /// `@font-face` rule for the embedded subset
+    font_face: Option<String>,
+    /// Gradient definitions and the background rectangle
+    background: String,
+    /// `<text>` elements drawn since the last clear
+    glyphs: String,
+    document: String,
+    frames_presented: u64,
+}
+
+impl SvgRenderer {
+    /// Create a renderer for frames of the given size in pixels
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            font_family: "monospace".to_string(),
+            font_face: None,
+            background: String::new(),
+            glyphs: String::new(),
+            document: String::new(),
+            frames_presented: 0,
+        }
+    }
+
+    /// Set the CSS font family list viewers choose the font from (default `monospace`)
+    pub fn with_font_family(mut self, family: impl Into<String>) -> Self {
+        self.font_family = family.into();
+        self
+    }
+
+    /// Embed a subset of a TrueType font covering `chars`, so the document looks the
+    /// same everywhere
+    ///
+    /// The subset is used ahead of the font family, which remains the fallback.
+    pub fn with_embedded_font(
+        mut self,
+        data: &[u8],
+        face_index: u32,
+        chars: impl IntoIterator<Item = char>,
+    ) -> Result<Self, FontError> {
+        let subset = subset_font(data, face_index, chars)?;
+        self.font_face = Some(format!(
+            "@font-face{{font-family:\"{}\";src:url(data:font/ttf;base64,{}) format(\"truetype\")}}",
+            EMBEDDED_FAMILY,
+            base64(&subset)
+        ));
+        Ok(self)
+    }
+
+    /// Change the frame size (takes effect from the next presented frame)
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    /// The SVG document of the last presented frame (empty before the first)
+    pub fn document(&self) -> &str {
+        &self.document
+    }
+
+    /// Number of frames presented so far
+    pub fn frames_presented(&self) -> u64 {
+        self.frames_presented
+    }
+
+    fn font_family_attribute(&self) -> String {
+        let family = if self.font_face.is_some() {
+            format!("'{}', {}", EMBEDDED_FAMILY, self.font_family)
+        } else {
+            self.font_family.clone()
+        };
+        escape(&family)
+    }
+
+    /// Write a glyph's `<text>` element, leaving out the font size when a group sets it
+    fn write_text(&mut self, render_char: &RenderChar, font_size: bool) {
+        let text = render_char.character.as_str();
+        if render_char.color.a <= 0.0 || text.trim().is_empty() {
+            return;
+        }
+
+        let center_x = render_char.x + render_char.font_size * CELL_WIDTH_RATIO / 2.0;
+        let center_y = render_char.y + render_char.font_size * CELL_HEIGHT_RATIO / 2.0;
+        let _ = write!(
+            self.glyphs,
+            "<text x=\"{}\" y=\"{}\" fill=\"{}\"{}",
+            number(center_x),
+            number(center_y),
+            hex(render_char.color),
+            opacity_attribute("fill-opacity", render_char.color.a),
+        );
+        if font_size {
+            let _ = write!(
+                self.glyphs,
+                " font-size=\"{}\"",
+                number(render_char.font_size)
+            );
+        }
+
+        // Mirror first, then rotate clockwise, both about the cell centre
+        let transform = render_char.transform;
+        if !transform.is_none() {
+            let (scale_x, scale_y) = transform.mirror_scale();
+            let _ = write!(
+                self.glyphs,
+                " transform=\"translate({} {}) rotate({}) scale({} {}) translate({} {})\"",
+                number(center_x),
+                number(center_y),
+                number(transform.rotation_degrees()),
+                number(scale_x),
+                number(scale_y),
+                number(-center_x),
+                number(-center_y)
+            );
+        }
+        let _ = writeln!(self.glyphs, ">{}</text>", escape(text));
+    }
+}
+
+impl Renderer for SvgRenderer {
+    fn clear(&mut self, color: Color) {
+        self.clear_backdrop(&Backdrop::Solid(color));
+    }
+
+    fn clear_backdrop(&mut self, backdrop: &Backdrop) {
+        self.glyphs.clear();
+        self.background.clear();
+
+        // Gradients use bounding box units, matching the normalized coordinates of
+        // `Backdrop::color_at`; both interpolate in sRGB
+        let stops = match *backdrop {
+            Backdrop::Transparent => return,
+            Backdrop::Solid(color) => {
+                let _ = writeln!(
+                    self.background,
+                    "<rect width=\"100%\" height=\"100%\" fill=\"{}\"{}/>",
+                    hex(color),
+                    opacity_attribute("fill-opacity", color.a)
+                );
+                return;
+            }
+            Backdrop::Vertical { top, bottom } => {
+                self.background.push_str(
+                    "<defs><linearGradient id=\"backdrop\" x1=\"0\" y1=\"0\" x2=\"0\" y2=\"1\">",
+                );
+                [top, bottom]
+            }
+            Backdrop::Radial { center, edge } => {
+                self.background.push_str(
+                    "<defs><radialGradient id=\"backdrop\" cx=\"0.5\" cy=\"0.5\" r=\"0.7071\">",
+                );
+                [center, edge]
+            }
+        };
+        for (offset, color) in [0, 1].into_iter().zip(stops) {
+            let _ = write!(
+                self.background,
+                "<stop offset=\"{}\" stop-color=\"{}\"{}/>",
+                offset,
+                hex(color),
+                opacity_attribute("stop-opacity", color.a)
+            );
+        }
+        let element = if matches!(backdrop, Backdrop::Vertical { .. }) {
+            "linearGradient"
+        } else {
+            "radialGradient"
+        };
+        let _ = writeln!(
+            self.background,
+            "</{}></defs>\n<rect width=\"100%\" height=\"100%\" fill=\"url(#backdrop)\"/>",
+            element
+        );
+    }
+
+    fn draw_char(&mut self, render_char: &RenderChar) {
+        self.write_text(render_char, true);
+    }
+
+    /// Wraps the batch in a group carrying the shared font size
+    fn draw_batch(&mut self, batch: &RenderBatch) {
+        let _ = writeln!(self.glyphs, "<g font-size=\"{}\">", number(batch.font_size));
+        for render_char in &batch.chars {
+            self.write_text(render_char, false);
+        }
+        self.glyphs.push_str("</g>\n");
+    }
+
+    fn present(&mut self) {
+        let mut document = String::new();
+        let _ = writeln!(document, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+        let _ = writeln!(
+            document,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+            self.width, self.height
+        );
+        if let Some(font_face) = &self.font_face {
+            let _ = writeln!(document, "<style>{}</style>", font_face);
+        }
+        document.push_str(&self.background);
+        let _ = writeln!(
+            document,
+            "<g font-family=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">",
+            self.font_family_attribute()
+        );
+        document.push_str(&self.glyphs);
+        document.push_str("</g>\n</svg>\n");
+
+        self.document = document;
+        self.frames_presented += 1;
+    }
+
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// Format a coordinate with at most two decimals and no trailing zeros
+fn number(value: f32) -> String {
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_string(),
+        text => text.to_string(),
+    }
+}
+
+/// `#rrggbb` for a colour, ignoring alpha
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// An opacity attribute, omitted when fully opaque
+fn opacity_attribute(name: &str, alpha: f32) -> String {
+    if alpha >= 1.0 {
+        String::new()
+    } else {
+        let text = format!("{:.3}", alpha.max(0.0));
+        let text = text.trim_end_matches('0').trim_end_matches('.');
+        format!(" {}=\"{}\"", name, text)
+    }
+}
+
+/// Escape text for use in XML content or a double-quoted attribute
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Standard base64 with padding, for `data:` URIs
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - i * 6)) as usize & 63] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::{Glyph, GlyphTransform};
+    use crate::{MatrixRain, ScreenSaverConfig};
+
+    fn render_char(ch: char, color: Color) -> RenderChar {
+        RenderChar {
+            character: Glyph::from(ch),
+            glyph_index: None,
+            x: 9.6,
+            y: 19.2,
+            color,
+            font_size: 16.0,
+            transform: GlyphTransform::NONE,
+            leader: false,
+            glow: 0.0,
+            intensity: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_text_elements() {
+        let mut renderer = SvgRenderer::new(64, 48);
+        renderer.clear(Color::BLACK);
+        renderer.draw_char(&render_char('ア', Color::rgb(0, 255, 65)));
+        renderer.draw_char(&render_char('<', Color::rgba(0, 128, 0, 0.5)));
+        renderer.draw_char(&render_char(' ', Color::WHITE));
+        renderer.present();
+
+        let document = renderer.document();
+        assert!(document.starts_with("<?xml"));
+        assert!(document.contains("viewBox=\"0 0 64 48\""));
+        assert!(document.contains("<rect width=\"100%\" height=\"100%\" fill=\"#000000\"/>"));
+        assert!(document
+            .contains("<text x=\"14.4\" y=\"28.8\" fill=\"#00ff41\" font-size=\"16\">ア</text>"));
+        assert!(
+            document.contains("fill=\"#008000\" fill-opacity=\"0.5\" font-size=\"16\">&lt;</text>")
+        );
+        assert_eq!(document.matches("<text").count(), 2);
+        assert!(document.contains("font-family=\"monospace\""));
+    }
+
+    #[test]
+    fn test_batches_share_font_size() {
+        let mut renderer = SvgRenderer::new(64, 48);
+        let chars = ['A', 'B'].map(|ch| render_char(ch, Color::WHITE));
+        renderer.draw_batch(&crate::rendering::batch_render_chars(chars)[0]);
+        renderer.present();
+
+        let document = renderer.document();
+        assert!(document.contains(
+            "<g font-size=\"16\">\n<text x=\"14.4\" y=\"28.8\" fill=\"#ffffff\">A</text>"
+        ));
+        assert!(!document.contains("font-size=\"16\">A"));
+        assert_eq!(document.matches("font-size").count(), 1);
+    }
+
+    #[test]
+    fn test_clear_starts_a_new_frame() {
+        let mut renderer = SvgRenderer::new(64, 48);
+        renderer.clear(Color::BLACK);
+        renderer.draw_char(&render_char('A', Color::WHITE));
+        renderer.present();
+        renderer.clear(Color::BLACK);
+        renderer.present();
+
+        assert!(!renderer.document().contains("<text"));
+        assert_eq!(renderer.frames_presented(), 2);
+    }
+
+    #[test]
+    fn test_transform_about_cell_centre() {
+        let mut renderer = SvgRenderer::new(64, 48);
+        let mut ch = render_char('A', Color::WHITE);
+        ch.transform = GlyphTransform::MIRROR_X | GlyphTransform::ROTATE_90;
+        renderer.draw_char(&ch);
+        renderer.present();
+
+        assert!(renderer.document().contains(
+            "transform=\"translate(14.4 28.8) rotate(90) scale(-1 1) translate(-14.4 -28.8)\""
+        ));
+    }
+
+    #[test]
+    fn test_backdrop_gradients() {
+        let mut renderer = SvgRenderer::new(64, 48);
+        renderer.clear_backdrop(&Backdrop::Vertical {
+            top: Color::rgb(0, 16, 0),
+            bottom: Color::BLACK,
+        });
+        renderer.present();
+        let document = renderer.document().to_string();
+        assert!(document.contains("<linearGradient id=\"backdrop\""));
+        assert!(document.contains("<stop offset=\"0\" stop-color=\"#001000\"/>"));
+        assert!(document.contains("fill=\"url(#backdrop)\""));
+
+        renderer.clear_backdrop(&Backdrop::Radial {
+            center: Color::rgba(0, 32, 0, 0.5),
+            edge: Color::BLACK,
+        });
+        renderer.present();
+        assert!(renderer
+            .document()
+            .contains("<radialGradient id=\"backdrop\""));
+        assert!(renderer.document().contains("stop-opacity=\"0.5\""));
+
+        renderer.clear_backdrop(&Backdrop::Transparent);
+        renderer.present();
+        assert!(!renderer.document().contains("<rect"));
+    }
+
+    #[test]
+    fn test_seeded_frames_are_identical() {
+        let config = ScreenSaverConfig {
+            screen_width: 320,
+            screen_height: 240,
+            ..Default::default()
+        };
+        let render = || {
+            let mut matrix = MatrixRain::with_seed(config.clone(), 9);
+            matrix.advance(2.0);
+            let mut renderer = SvgRenderer::new(320, 240);
+            matrix.render(&mut renderer);
+            renderer.document().to_string()
+        };
+
+        let document = render();
+        assert!(document.contains("<text"));
+        assert_eq!(document, render());
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(number(12.0), "12");
+        assert_eq!(number(9.6), "9.6");
+        assert_eq!(number(-0.001), "0");
+        assert_eq!(escape("a&\"b\""), "a&amp;&quot;b&quot;");
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"M"), "TQ==");
+        assert_eq!(base64(b""), "");
+    }
+
+    #[test]
+    fn test_embedded_font() {
+        let path = "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf";
+        let Ok(data) = std::fs::read(path) else {
+            return;
+        };
+        let mut renderer = SvgRenderer::new(64, 48)
+            .with_embedded_font(&data, 0, "0123456789".chars())
+            .unwrap();
+        renderer.draw_char(&render_char('7', Color::WHITE));
+        renderer.present();
+
+        let document = renderer.document();
+        assert!(document.contains("<style>@font-face{font-family:\"Matrix Rain Embedded\""));
+        assert!(document.contains("data:font/ttf;base64,AAEAAA"));
+        assert!(document.contains("font-family=\"'Matrix Rain Embedded', monospace\""));
+        assert!(document.len() < data.len() / 4);
+    }
+}
@@ -0,0 +1,568 @@
+//! Terminal renderer: maps glyphs onto a character-cell grid and writes ANSI escapes
+//!
+//! Output is diffed against the previous frame, so only changed cells are
+//! rewritten. The renderer only writes bytes; putting the terminal into raw
+//! mode and reading its size is left to the caller.
+
+use super::{Backdrop, BlendMode, Color, Glyph, RenderChar, Renderer};
+use std::io::{self, Write};
+
+/// Enter the alternate screen and hide the cursor
+const ENTER: &str = "\x1b[?1049h\x1b[?25l";
+/// Reset attributes, show the cursor and leave the alternate screen
+const LEAVE: &str = "\x1b[0m\x1b[?25h\x1b[?1049l";
+
+/// The xterm default values of the 16 standard ANSI colours
+const ANSI_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel levels of the 6×6×6 colour cube in the 256-colour palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// How many colours the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// 24-bit colour
+    TrueColor,
+    /// The xterm 256-colour palette
+    Ansi256,
+    /// The 16 standard ANSI colours
+    Ansi16,
+}
+
+impl ColorMode {
+    /// Pick a mode from the `COLORTERM` and `TERM` values
+    pub fn detect(colorterm: Option<&str>, term: Option<&str>) -> ColorMode {
+        match (colorterm, term) {
+            (Some("truecolor" | "24bit"), _) => ColorMode::TrueColor,
+            (_, Some(term)) if term.contains("256color") => ColorMode::Ansi256,
+            _ => ColorMode::Ansi16,
+        }
+    }
+
+    /// Pick a mode from the process environment
+    pub fn from_env() -> ColorMode {
+        let colorterm = std::env::var("COLORTERM").ok();
+        let term = std::env::var("TERM").ok();
+        Self::detect(colorterm.as_deref(), term.as_deref())
+    }
+
+    /// Look up a mode by name ("truecolor", "256" or "16")
+    pub fn from_id(id: &str) -> Option<ColorMode> {
+        match id.to_ascii_lowercase().as_str() {
+            "truecolor" | "24bit" => Some(ColorMode::TrueColor),
+            "256" => Some(ColorMode::Ansi256),
+            "16" => Some(ColorMode::Ansi16),
+            _ => None,
+        }
+    }
+
+    /// SGR parameters selecting a foreground colour
+    fn foreground(&self, (r, g, b): (u8, u8, u8)) -> String {
+        match self {
+            ColorMode::TrueColor => format!("38;2;{};{};{}", r, g, b),
+            ColorMode::Ansi256 => format!("38;5;{}", ansi_256((r, g, b))),
+            ColorMode::Ansi16 => {
+                // Black would hide dim trails entirely; keep them in their hue
+                let index = if r.max(g).max(b) < 32 {
+                    0
+                } else {
+                    nearest(&ANSI_16[1..], (r, g, b)) + 1
+                };
+                sgr_16(index, 30)
+            }
+        }
+    }
+
+    /// SGR parameters selecting a background colour (transparent keeps the terminal's own)
+    fn background(&self, color: Color) -> String {
+        if color.a <= 0.0 {
+            return "49".to_string();
+        }
+        let rgb = (color.r, color.g, color.b);
+        match self {
+            ColorMode::TrueColor => format!("48;2;{};{};{}", rgb.0, rgb.1, rgb.2),
+            ColorMode::Ansi256 => format!("48;5;{}", ansi_256(rgb)),
+            ColorMode::Ansi16 => sgr_16(nearest(&ANSI_16, rgb), 40),
+        }
+    }
+}
+
+/// SGR code of a 16-colour index (`base` is 30 for foreground, 40 for background)
+fn sgr_16(index: usize, base: usize) -> String {
+    if index < 8 {
+        (base + index).to_string()
+    } else {
+        (base + 60 + index - 8).to_string()
+    }
+}
+
+/// Squared distance between colours, weighted roughly by the eye's sensitivity
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> i32 {
+    let dr = a.0 as i32 - b.0 as i32;
+    let dg = a.1 as i32 - b.1 as i32;
+    let db = a.2 as i32 - b.2 as i32;
+    2 * dr * dr + 4 * dg * dg + 3 * db * db
+}
+
+/// Index of the nearest colour in a palette
+fn nearest(palette: &[(u8, u8, u8)], rgb: (u8, u8, u8)) -> usize {
+    (0..palette.len())
+        .min_by_key(|&i| distance(palette[i], rgb))
+        .unwrap_or(0)
+}
+
+/// Nearest xterm 256-colour index (colour cube or grey ramp)
+fn ansi_256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |value: u8| nearest(&CUBE_LEVELS.map(|l| (l, l, l)), (value, value, value));
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let grey_step = ((average.saturating_sub(3)) / 10).min(23) as u8;
+    let grey_value = 8 + grey_step * 10;
+    let grey = (grey_value, grey_value, grey_value);
+
+    if distance(grey, rgb) < distance(cube, rgb) {
+        232 + grey_step
+    } else {
+        16 + 36 * r as u8 + 6 * g as u8 + b as u8
+    }
+}
+
+/// Contents of one terminal cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    /// Nothing drawn (background only)
+    Empty,
+    /// A glyph drawn in an opaque colour
+    Glyph {
+        /// The glyph
+        glyph: Glyph,
+        /// Foreground colour, already composited over the background
+        color: (u8, u8, u8),
+    },
+    /// The right half of a double-width glyph in the cell to the left
+    Continuation,
+}
+
+/// A `Renderer` that draws to a terminal through ANSI escape sequences
+///
+/// Each rain column maps to `column_width` terminal columns (2 for scripts
+/// with double-width glyphs) and each row of the engine's grid to one line.
+pub struct TerminalRenderer<W: Write> {
+    writer: W,
+    color_mode: ColorMode,
+    columns: usize,
+    rows: usize,
+    column_width: usize,
+    /// Engine cell size in pixels
+    cell_size: (f32, f32),
+    background: Color,
+    cells: Vec<Cell>,
+    /// What the terminal currently shows (None forces a full redraw)
+    shown: Option<Vec<Cell>>,
+    shown_background: Option<Color>,
+    entered: bool,
+    bytes_written: usize,
+}
+
+impl<W: Write> TerminalRenderer<W> {
+    /// Create a renderer for a terminal of `columns` × `rows` cells
+    /// `cell_size` is the engine's cell size in pixels (see `MatrixRain::cell_size`).
+    pub fn new(writer: W, columns: usize, rows: usize, cell_size: (f32, f32)) -> Self {
+        Self {
+            writer,
+            color_mode: ColorMode::TrueColor,
+            columns,
+            rows,
+            column_width: 1,
+            cell_size,
+            background: Color::BLACK,
+            cells: vec![Cell::Empty; columns * rows],
+            shown: None,
+            shown_background: None,
+            entered: false,
+            bytes_written: 0,
+        }
+    }
+
+    /// Set the colour mode
+    pub fn with_color_mode(mut self, color_mode: ColorMode) -> Self {
+        self.color_mode = color_mode;
+        self
+    }
+
+    /// Set how many terminal columns each rain column takes (1 or 2)
+    pub fn with_column_width(mut self, column_width: usize) -> Self {
+        self.column_width = column_width.clamp(1, 2);
+        self
+    }
+
+    /// Switch to the alternate screen and hide the cursor
+    pub fn enter(&mut self) -> io::Result<()> {
+        self.writer.write_all(ENTER.as_bytes())?;
+        self.writer.flush()?;
+        self.entered = true;
+        self.shown = None;
+        Ok(())
+    }
+
+    /// Restore the terminal (also done on drop after `enter`)
+    pub fn leave(&mut self) -> io::Result<()> {
+        if self.entered {
+            self.entered = false;
+            self.writer.write_all(LEAVE.as_bytes())?;
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Change the terminal size; the next frame is redrawn in full
+    pub fn resize(&mut self, columns: usize, rows: usize) {
+        self.columns = columns;
+        self.rows = rows;
+        self.cells = vec![Cell::Empty; columns * rows];
+        self.shown = None;
+    }
+
+    /// Terminal size in cells (columns, rows)
+    pub fn size(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+
+    /// Number of rain columns that fit across the terminal
+    pub fn rain_columns(&self) -> usize {
+        self.columns / self.column_width
+    }
+
+    /// Contents of a cell of the frame being drawn
+    pub fn cell(&self, column: usize, row: usize) -> Option<Cell> {
+        (column < self.columns && row < self.rows).then(|| self.cells[row * self.columns + column])
+    }
+
+    /// Total bytes written to the terminal so far
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    /// Get the writer
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Put a cell, clearing any double-width glyph it overlaps
+    fn put(&mut self, column: usize, row: usize, cell: Cell, width: usize) {
+        let index = row * self.columns + column;
+        if self.cells[index] == Cell::Continuation && column > 0 {
+            self.cells[index - 1] = Cell::Empty;
+        }
+        if column + 1 < self.columns && self.cells[index + 1] == Cell::Continuation {
+            self.cells[index + 1] = Cell::Empty;
+        }
+        self.cells[index] = cell;
+
+        if width == 2 && column + 1 < self.columns {
+            if column + 2 < self.columns && self.cells[index + 2] == Cell::Continuation {
+                self.cells[index + 2] = Cell::Empty;
+            }
+            self.cells[index + 1] = Cell::Continuation;
+        }
+    }
+
+    /// Build the escape sequence bytes for the current frame
+    fn frame_output(&self) -> String {
+        let mut out = String::new();
+        let full = self.shown.is_none() || self.shown_background != Some(self.background);
+        if full {
+            out.push_str(&format!(
+                "\x1b[0m\x1b[{}m\x1b[2J",
+                self.color_mode.background(self.background)
+            ));
+        }
+
+        let mut cursor = None;
+        let mut foreground = None;
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let index = row * self.columns + column;
+                let cell = self.cells[index];
+                let unchanged = match &self.shown {
+                    Some(shown) if !full => shown[index] == cell,
+                    _ => cell == Cell::Empty,
+                };
+                if unchanged || cell == Cell::Continuation {
+                    continue;
+                }
+
+                if cursor != Some((column, row)) {
+                    out.push_str(&format!("\x1b[{};{}H", row + 1, column + 1));
+                }
+                match cell {
+                    Cell::Glyph { glyph, color } => {
+                        let sgr = self.color_mode.foreground(color);
+                        if foreground.as_ref() != Some(&sgr) {
+                            out.push_str(&format!("\x1b[{}m", sgr));
+                            foreground = Some(sgr);
+                        }
+                        out.push_str(glyph.as_str());
+                        cursor = Some((column + glyph.display_width(), row));
+                    }
+                    _ => {
+                        out.push(' ');
+                        cursor = Some((column + 1, row));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl<W: Write> Renderer for TerminalRenderer<W> {
+    fn clear(&mut self, color: Color) {
+        self.background = color;
+        self.cells.fill(Cell::Empty);
+    }
+
+    fn clear_backdrop(&mut self, backdrop: &Backdrop) {
+        let color = match backdrop {
+            Backdrop::Transparent => Color::rgba(0, 0, 0, 0.0),
+            backdrop => backdrop.base_color(),
+        };
+        self.clear(color);
+    }
+
+    fn draw_char(&mut self, render_char: &RenderChar) {
+        let rain_column = (render_char.x / self.cell_size.0).round();
+        let row = (render_char.y / self.cell_size.1).round();
+        if rain_column < 0.0 || row < 0.0 || row as usize >= self.rows {
+            return;
+        }
+        let column = rain_column as usize * self.column_width;
+        if column >= self.columns {
+            return;
+        }
+
+        // Terminals draw glyphs opaque, so fade them into the background here
+        let background = if self.background.a > 0.0 {
+            self.background.with_alpha(1.0)
+        } else {
+            Color::BLACK
+        };
+        let color = render_char.color.blend_over(background, BlendMode::Normal);
+        let width = render_char.character.display_width();
+        let cell = Cell::Glyph {
+            glyph: render_char.character,
+            color: (color.r, color.g, color.b),
+        };
+        self.put(column, row as usize, cell, width);
+    }
+
+    fn present(&mut self) {
+        let out = self.frame_output();
+        // A terminal that has gone away can't be drawn to; the caller notices on exit
+        if self.writer.write_all(out.as_bytes()).is_ok() && self.writer.flush().is_ok() {
+            self.bytes_written += out.len();
+        }
+        self.shown = Some(self.cells.clone());
+        self.shown_background = Some(self.background);
+    }
+
+    fn width(&self) -> u32 {
+        (self.rain_columns() as f32 * self.cell_size.0) as u32
+    }
+
+    fn height(&self) -> u32 {
+        (self.rows as f32 * self.cell_size.1) as u32
+    }
+}
+
+impl<W: Write> Drop for TerminalRenderer<W> {
+    fn drop(&mut self) {
+        let _ = self.leave();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GlyphTransform;
+
+    const CELL: (f32, f32) = (10.0, 20.0);
+
+    fn render_char(glyph: char, column: usize, row: usize, color: Color) -> RenderChar {
+        RenderChar {
+            character: glyph.into(),
+            glyph_index: None,
+            x: column as f32 * CELL.0,
+            y: row as f32 * CELL.1,
+            color,
+            font_size: 16.0,
+            transform: GlyphTransform::NONE,
+            leader: false,
+            glow: 0.0,
+            intensity: 1.0,
+        }
+    }
+
+    fn output(renderer: &TerminalRenderer<Vec<u8>>) -> String {
+        String::from_utf8(renderer.writer().clone()).unwrap()
+    }
+
+    #[test]
+    fn test_color_mode_detection() {
+        assert_eq!(
+            ColorMode::detect(Some("truecolor"), Some("xterm")),
+            ColorMode::TrueColor
+        );
+        assert_eq!(
+            ColorMode::detect(None, Some("xterm-256color")),
+            ColorMode::Ansi256
+        );
+        assert_eq!(ColorMode::detect(None, Some("vt100")), ColorMode::Ansi16);
+        assert_eq!(ColorMode::detect(None, None), ColorMode::Ansi16);
+        assert_eq!(ColorMode::from_id("256"), Some(ColorMode::Ansi256));
+    }
+
+    #[test]
+    fn test_color_fallbacks() {
+        assert_eq!(
+            ColorMode::TrueColor.foreground((0, 255, 70)),
+            "38;2;0;255;70"
+        );
+        assert_eq!(ansi_256((0, 0, 0)), 16);
+        assert_eq!(ansi_256((255, 255, 255)), 231);
+        assert_eq!(ansi_256((0, 255, 0)), 46);
+        assert_eq!(ansi_256((128, 128, 128)), 244);
+        assert_eq!(ColorMode::Ansi16.foreground((0, 255, 70)), "92");
+        // Dim green stays green rather than turning black
+        assert_eq!(ColorMode::Ansi16.foreground((0, 90, 20)), "32");
+        assert_eq!(ColorMode::Ansi16.background(Color::BLACK), "40");
+        assert_eq!(
+            ColorMode::TrueColor.background(Color::rgba(0, 0, 0, 0.0)),
+            "49"
+        );
+    }
+
+    #[test]
+    fn test_draw_and_diff() {
+        let mut renderer = TerminalRenderer::new(Vec::new(), 8, 4, CELL);
+        renderer.clear(Color::BLACK);
+        renderer.draw_char(&render_char('A', 1, 2, Color::WHITE));
+        renderer.present();
+        assert_eq!(
+            renderer.cell(1, 2),
+            Some(Cell::Glyph {
+                glyph: 'A'.into(),
+                color: (255, 255, 255)
+            })
+        );
+        let first = output(&renderer);
+        assert!(first.contains("\x1b[2J"));
+        assert!(first.ends_with("\x1b[3;2H\x1b[38;2;255;255;255mA"));
+
+        // Same frame again: nothing to write
+        let before = renderer.bytes_written();
+        renderer.clear(Color::BLACK);
+        renderer.draw_char(&render_char('A', 1, 2, Color::WHITE));
+        renderer.present();
+        assert_eq!(renderer.bytes_written(), before);
+
+        // Only changed cells are rewritten; vanished glyphs are blanked
+        renderer.clear(Color::BLACK);
+        renderer.draw_char(&render_char('B', 2, 2, Color::WHITE));
+        renderer.present();
+        let update = &output(&renderer)[first.len()..];
+        assert_eq!(update, "\x1b[3;2H \x1b[38;2;255;255;255mB");
+    }
+
+    #[test]
+    fn test_alpha_fades_into_background() {
+        let mut renderer = TerminalRenderer::new(Vec::new(), 4, 2, CELL);
+        renderer.clear(Color::rgb(0, 0, 100));
+        renderer.draw_char(&render_char(
+            'x',
+            0,
+            0,
+            Color::rgb(0, 255, 0).with_alpha(0.5),
+        ));
+        let Some(Cell::Glyph { color, .. }) = renderer.cell(0, 0) else {
+            panic!("glyph not drawn");
+        };
+        assert!(color.1 > 100 && color.1 < 255);
+        assert!(color.2 > 0 && color.2 < 100);
+    }
+
+    #[test]
+    fn test_wide_glyphs() {
+        let mut renderer = TerminalRenderer::new(Vec::new(), 8, 2, CELL).with_column_width(2);
+        assert_eq!(renderer.rain_columns(), 4);
+        assert_eq!(renderer.width(), 40);
+        renderer.clear(Color::BLACK);
+        renderer.draw_char(&render_char('ア', 1, 0, Color::WHITE));
+        renderer.draw_char(&render_char('9', 3, 0, Color::WHITE));
+        renderer.draw_char(&render_char('ア', 4, 0, Color::WHITE)); // Off screen
+        assert!(matches!(renderer.cell(2, 0), Some(Cell::Glyph { .. })));
+        assert_eq!(renderer.cell(3, 0), Some(Cell::Continuation));
+        assert!(matches!(renderer.cell(6, 0), Some(Cell::Glyph { .. })));
+        assert_eq!(renderer.cell(7, 0), Some(Cell::Empty));
+        renderer.present();
+        // The continuation cell needs no cursor move or output of its own
+        assert!(output(&renderer).contains("\x1b[1;3H\x1b[38;2;255;255;255mア\x1b[1;7H9"));
+
+        // A narrow glyph over a wide one's right half clears the wide one
+        let mut renderer = TerminalRenderer::new(Vec::new(), 4, 1, CELL);
+        renderer.clear(Color::BLACK);
+        renderer.draw_char(&render_char('ア', 0, 0, Color::WHITE));
+        renderer.draw_char(&render_char('x', 1, 0, Color::WHITE));
+        assert_eq!(renderer.cell(0, 0), Some(Cell::Empty));
+    }
+
+    #[test]
+    fn test_resize_and_background_change_redraw() {
+        let mut renderer = TerminalRenderer::new(Vec::new(), 4, 2, CELL);
+        renderer.clear(Color::BLACK);
+        renderer.present();
+        let len = output(&renderer).len();
+
+        renderer.resize(6, 3);
+        assert_eq!(renderer.size(), (6, 3));
+        renderer.clear(Color::BLACK);
+        renderer.present();
+        assert!(output(&renderer)[len..].contains("\x1b[2J"));
+
+        let len = output(&renderer).len();
+        renderer.clear(Color::rgb(20, 0, 0));
+        renderer.present();
+        assert!(output(&renderer)[len..].contains("\x1b[48;2;20;0;0m\x1b[2J"));
+    }
+
+    #[test]
+    fn test_enter_and_restore() {
+        let mut buffer = Vec::new();
+        {
+            let mut renderer = TerminalRenderer::new(&mut buffer, 4, 2, CELL);
+            renderer.enter().unwrap();
+        }
+        let written = String::from_utf8(buffer).unwrap();
+        assert!(written.starts_with(ENTER));
+        assert!(written.ends_with(LEAVE));
+    }
+}
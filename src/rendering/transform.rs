@@ -0,0 +1,141 @@
+//! Per-glyph transform flags (mirroring and rotation)
+
+use serde::{Deserialize, Serialize};
+
+/// Transform applied to a single glyph when it is drawn
+///
+/// Stored as bit flags so it can be passed straight through FFI as a `u8`.
+/// Renderers apply mirroring first, then rotation, both around the glyph centre.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct GlyphTransform(u8);
+
+impl GlyphTransform {
+    /// No transform
+    pub const NONE: GlyphTransform = GlyphTransform(0);
+    /// Mirror horizontally (flip left/right)
+    pub const MIRROR_X: GlyphTransform = GlyphTransform(1 << 0);
+    /// Mirror vertically (flip top/bottom)
+    pub const MIRROR_Y: GlyphTransform = GlyphTransform(1 << 1);
+    /// Rotate 90 degrees clockwise
+    pub const ROTATE_90: GlyphTransform = GlyphTransform(1 << 2);
+    /// Rotate 180 degrees
+    pub const ROTATE_180: GlyphTransform = GlyphTransform(1 << 3);
+
+    const ALL_BITS: u8 = 0b1111;
+
+    /// Create a transform from raw bits, ignoring unknown flags
+    pub fn from_bits(bits: u8) -> Self {
+        Self(bits & Self::ALL_BITS)
+    }
+
+    /// Get the raw bit representation
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Check whether this is the identity transform
+    pub fn is_none(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Check whether all flags in `other` are set
+    pub fn contains(&self, other: GlyphTransform) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Set or clear the flags in `other`
+    pub fn set(&mut self, other: GlyphTransform, enabled: bool) {
+        if enabled {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+
+    /// Total clockwise rotation in degrees (0, 90, 180 or 270)
+    pub fn rotation_degrees(&self) -> f32 {
+        let mut degrees = 0.0;
+        if self.contains(Self::ROTATE_90) {
+            degrees += 90.0;
+        }
+        if self.contains(Self::ROTATE_180) {
+            degrees += 180.0;
+        }
+        degrees
+    }
+
+    /// Scale factors (x, y) that implement the mirror flags (1.0 or -1.0)
+    pub fn mirror_scale(&self) -> (f32, f32) {
+        (
+            if self.contains(Self::MIRROR_X) {
+                -1.0
+            } else {
+                1.0
+            },
+            if self.contains(Self::MIRROR_Y) {
+                -1.0
+            } else {
+                1.0
+            },
+        )
+    }
+}
+
+impl std::ops::BitOr for GlyphTransform {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for GlyphTransform {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_none() {
+        assert!(GlyphTransform::default().is_none());
+        assert_eq!(GlyphTransform::default(), GlyphTransform::NONE);
+    }
+
+    #[test]
+    fn test_combine_flags() {
+        let transform = GlyphTransform::MIRROR_X | GlyphTransform::ROTATE_180;
+        assert!(transform.contains(GlyphTransform::MIRROR_X));
+        assert!(transform.contains(GlyphTransform::ROTATE_180));
+        assert!(!transform.contains(GlyphTransform::MIRROR_Y));
+        assert_eq!(transform.bits(), 0b1001);
+    }
+
+    #[test]
+    fn test_set_and_clear() {
+        let mut transform = GlyphTransform::NONE;
+        transform.set(GlyphTransform::MIRROR_Y, true);
+        assert!(transform.contains(GlyphTransform::MIRROR_Y));
+        transform.set(GlyphTransform::MIRROR_Y, false);
+        assert!(transform.is_none());
+    }
+
+    #[test]
+    fn test_from_bits_masks_unknown() {
+        assert_eq!(GlyphTransform::from_bits(0xFF).bits(), 0b1111);
+    }
+
+    #[test]
+    fn test_rotation_and_mirror() {
+        let transform = GlyphTransform::ROTATE_90 | GlyphTransform::ROTATE_180;
+        assert_eq!(transform.rotation_degrees(), 270.0);
+        assert_eq!(transform.mirror_scale(), (1.0, 1.0));
+
+        let mirrored = GlyphTransform::MIRROR_X;
+        assert_eq!(mirrored.rotation_degrees(), 0.0);
+        assert_eq!(mirrored.mirror_scale(), (-1.0, 1.0));
+    }
+}